@@ -1,11 +1,31 @@
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) radius: f32,
+    @location(4) border_width: f32,
+    @location(5) border_color: vec4<f32>,
+    @location(6) opacity: f32,
+    @location(7) uv: vec4<f32>,
+    @location(8) tint: vec4<f32>,
+    @location(9) gradient: vec4<f32>,
+    @location(10) border_gradient: vec4<f32>,
+    @location(11) blur: f32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) radius: f32,
+    @location(3) border_width: f32,
+    @location(4) border_color: vec4<f32>,
+    @location(5) opacity: f32,
+    @location(6) uv: vec4<f32>,
+    @location(7) tint: vec4<f32>,
+    @location(8) gradient: vec4<f32>,
+    @location(9) border_gradient: vec4<f32>,
+    @location(10) blur: f32,
 };
 
 @vertex
@@ -13,6 +33,16 @@ fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
     out.clip_position = vec4<f32>(model.position, 1.0);
+    out.size = model.size;
+    out.radius = model.radius;
+    out.border_width = model.border_width;
+    out.border_color = model.border_color;
+    out.opacity = model.opacity;
+    out.uv = model.uv;
+    out.tint = model.tint;
+    out.gradient = model.gradient;
+    out.border_gradient = model.border_gradient;
+    out.blur = model.blur;
     return out;
 }
 
@@ -20,8 +50,99 @@ fn vs_main(model: VertexInput) -> VertexOutput {
 var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
+// gradient colors: the fill's along the top row, the border's along the bottom one
+@group(0) @binding(2)
+var t_ramp: texture_2d<f32>;
+
+const PI: f32 = 3.14159265;
+const RAMP_WIDTH: f32 = 256.0;
+
+// signed distance from p to a rounded box centered at the origin
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// how far along the gradient p (from the top left of the box) is, 0..1.
+// x is the kind: 1 linear, 2 radial, 3 conic, followed by its parameters
+fn gradient_offset(gradient: vec4<f32>, p: vec2<f32>, size: vec2<f32>) -> f32 {
+    let kind = i32(gradient.x + 0.5);
+    if kind == 1 {
+        // the gradient line goes through the center, long enough to reach the corners
+        let angle = gradient.y;
+        let direction = vec2<f32>(sin(angle), -cos(angle));
+        let len = abs(size.x * sin(angle)) + abs(size.y * cos(angle));
+        return dot(p - size * 0.5, direction) / max(len, 0.0001) + 0.5;
+    }
+
+    let center = gradient.yz * size;
+    let v = p - center;
+    if kind == 2 {
+        let corner = max(center, size - center);
+        if gradient.w > 0.5 {
+            return length(v) / max(length(corner), 0.0001);
+        }
+        return length(v / max(corner * sqrt(2.0), vec2<f32>(0.0001)));
+    }
+
+    let angle = atan2(v.x, -v.y) - gradient.w;
+    return fract(angle / (2.0 * PI));
+}
+
+fn ramp(gradient: vec4<f32>, p: vec2<f32>, size: vec2<f32>, row: f32) -> vec4<f32> {
+    let t = clamp(gradient_offset(gradient, p, size), 0.0, 1.0);
+    // onto the texel centers, so the ends are the first & last colors exactly
+    let u = (t * (RAMP_WIDTH - 1.0) + 0.5) / RAMP_WIDTH;
+    return textureSampleLevel(t_ramp, s_diffuse, vec2<f32>(u, row), 0.0);
+}
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let half_size = in.size * 0.5;
+    let p = in.tex_coords * in.size - half_size;
+
+    // a shadow's quad reaches 3 sigma (sigma being half the blur) past its box on every side,
+    // for the edge to fade out in
+    let box_size = max(half_size - vec2<f32>(in.blur * 1.5), vec2<f32>(0.0));
+    let radius = min(in.radius, min(box_size.x, box_size.y));
+
+    let dist = rounded_box(p, box_size, radius);
+    var coverage = clamp(0.5 - dist, 0.0, 1.0);
+    if in.blur > 0.0 {
+        // the gaussian's CDF across the edge, close enough as a logistic curve
+        coverage = 1.0 / (1.0 + exp(1.702 * dist / (in.blur * 0.5)));
+    }
+
+    // tex_coords span the whole quad for the shape, uv picks the part of the texture on it
+    let uv = in.uv.xy + in.tex_coords * in.uv.zw;
+    var color = textureSample(t_diffuse, s_diffuse, uv) * in.tint;
+    let position = in.tex_coords * in.size;
+    if in.gradient.x > 0.5 {
+        // the texture only masks the gradient, e.g. to the shape of text
+        let paint = ramp(in.gradient, position, in.size, 0.25);
+        color = vec4<f32>(paint.rgb, paint.a * color.a);
+    }
+    if in.border_width > 0.0 {
+        var border_color = in.border_color;
+        if in.border_gradient.x > 0.5 {
+            border_color = ramp(in.border_gradient, position, in.size, 0.75);
+        }
+        let inner = clamp(0.5 - (dist + in.border_width), 0.0, 1.0);
+        color = mix(border_color, color, inner);
+    }
+
+    return vec4<f32>(color.rgb, color.a * coverage * in.opacity);
+}
+
+// only marks the stencil, for the pixels inside the rounded box
+@fragment
+fn fs_mask(in: VertexOutput) -> @location(0) vec4<f32> {
+    let half_size = in.size * 0.5;
+    let p = in.tex_coords * in.size - half_size;
+    let radius = min(in.radius, min(half_size.x, half_size.y));
+
+    if rounded_box(p, half_size, radius) > 0.0 {
+        discard;
+    }
+    return vec4<f32>(0.0);
 }
@@ -3,10 +3,22 @@ struct VertexOutput {
     @location(0) uv: vec2<f32>,
 }
 
+// matches `Uniforms` in uniforms.rs
 struct Uniforms {
-    rect: vec4<f32>,
+    // where the frame's corners are drawn, in NDC: left then right, of the top & bottom edges
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+    // the part of the frame drawn: x, y, width & height in 0..1
+    source: vec4<f32>,
+    format: u32,
+    opacity: f32,
 }
 
+// matches `PixelFormat` in video.rs
+const FORMAT_RGBA: u32 = 0u;
+const FORMAT_NV12: u32 = 2u;
+
+// RGBA, or the Y plane
 @group(0) @binding(0)
 var t: texture_2d<f32>;
 
@@ -16,26 +28,65 @@ var s: sampler;
 @group(0) @binding(2)
 var<uniform> uniforms: Uniforms;
 
+// the U plane, or U & V interleaved for NV12
+@group(0) @binding(3)
+var t_u: texture_2d<f32>;
+
+@group(0) @binding(4)
+var t_v: texture_2d<f32>;
+
 @vertex
 fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
-    // let quad = array<vec2<f32>, 6>(
-    //     uniforms.rect.xy,
-    //     uniforms.rect.zy,
-    //     uniforms.rect.xw,
-    //     uniforms.rect.zy,
-    //     uniforms.rect.zw,
-    //     uniforms.rect.xw,
-    // );
+    // two triangles covering the rect, as corners of the unit square
+    var quad = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = quad[in_vertex_index];
 
     var out: VertexOutput;
-    out.uv = vec2<f32>(0.0);
-    out.uv.x = select(0.0, 2.0, in_vertex_index == 1u);
-    out.uv.y = select(0.0, 2.0, in_vertex_index == 2u);
-    out.position = vec4<f32>(out.uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 1.0, 1.0);
+    out.uv = uniforms.source.xy + corner * uniforms.source.zw;
+    let top = mix(uniforms.top.xy, uniforms.top.zw, corner.x);
+    let bottom = mix(uniforms.bottom.xy, uniforms.bottom.zw, corner.x);
+    out.position = vec4<f32>(mix(top, bottom, corner.y), 0.0, 1.0);
     return out;
 }
 
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + vec3<f32>(0.055)) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+// BT.601, limited range, to gamma encoded RGB
+fn yuv_to_rgb(y: f32, u: f32, v: f32) -> vec3<f32> {
+    let luma = 1.164 * (y - 16.0 / 255.0);
+    let cb = u - 0.5;
+    let cr = v - 0.5;
+    return clamp(vec3<f32>(
+        luma + 1.596 * cr,
+        luma - 0.392 * cb - 0.813 * cr,
+        luma + 2.017 * cb,
+    ), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t, s, in.uv);
+    // every plane is sampled, which ones mean something depends on the format
+    let main = textureSample(t, s, in.uv);
+    let u = textureSample(t_u, s, in.uv);
+    let v = textureSample(t_v, s, in.uv);
+
+    if uniforms.format == FORMAT_RGBA {
+        // the texture is sRGB, sampling already made it linear
+        return vec4<f32>(main.rgb, main.a * uniforms.opacity);
+    }
+
+    let chroma = select(vec2<f32>(u.r, v.r), u.rg, uniforms.format == FORMAT_NV12);
+    let rgb = yuv_to_rgb(main.r, chroma.x, chroma.y);
+    return vec4<f32>(srgb_to_linear(rgb), uniforms.opacity);
 }
@@ -0,0 +1,601 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::geometry::{Rect, Transform};
+use crate::style::{Border, Color, Style, TRANSPARENT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Property {
+    Left,
+    Top,
+    Width,
+    Height,
+    Background,
+    Foreground,
+    BorderColor,
+    BorderWidth,
+    Radius,
+    Opacity,
+    Transform,
+}
+
+impl Property {
+    pub const ALL: [Property; 11] = [
+        Self::Left,
+        Self::Top,
+        Self::Width,
+        Self::Height,
+        Self::Background,
+        Self::Foreground,
+        Self::BorderColor,
+        Self::BorderWidth,
+        Self::Radius,
+        Self::Opacity,
+        Self::Transform,
+    ];
+
+    // --- unset properties read as what they'd be painted as
+    pub fn get(&self, style: &Style, rect: &Rect) -> Value {
+        let border = style.border.unwrap_or_default();
+
+        match self {
+            Self::Left => Value::Number(rect.x),
+            Self::Top => Value::Number(rect.y),
+            Self::Width => Value::Number(rect.width),
+            Self::Height => Value::Number(rect.height),
+            Self::Background => Value::Color(style.background.unwrap_or(TRANSPARENT)),
+            Self::Foreground => Value::Color(style.foreground.unwrap_or(TRANSPARENT)),
+            Self::BorderColor => Value::Color(border.color),
+            Self::BorderWidth => Value::Number(border.width),
+            Self::Radius => Value::Number(style.radius.unwrap_or(0.)),
+            Self::Opacity => Value::Number(style.opacity.unwrap_or(1.)),
+            Self::Transform => Value::Transform(style.transform.unwrap_or_default()),
+        }
+    }
+
+    pub fn set(&self, style: &mut Style, rect: &mut Rect, value: Value) {
+        match (self, value) {
+            (Self::Left, Value::Number(n)) => rect.x = n,
+            (Self::Top, Value::Number(n)) => rect.y = n,
+            (Self::Width, Value::Number(n)) => rect.width = n.max(0.),
+            (Self::Height, Value::Number(n)) => rect.height = n.max(0.),
+            (Self::Background, Value::Color(c)) => style.background = Some(c),
+            (Self::Foreground, Value::Color(c)) => style.foreground = Some(c),
+            (Self::BorderColor, Value::Color(c)) => border(style).color = c,
+            (Self::BorderWidth, Value::Number(n)) => border(style).width = n.max(0.),
+            (Self::Radius, Value::Number(n)) => style.radius = Some(n.max(0.)),
+            (Self::Opacity, Value::Number(n)) => style.opacity = Some(n.clamp(0., 1.)),
+            (Self::Transform, Value::Transform(t)) => style.transform = Some(t),
+            (property, value) => log::warn!("Cannot animate {:?} with {:?}", property, value),
+        }
+    }
+}
+
+fn border(style: &mut Style) -> &mut Border {
+    style.border.get_or_insert_with(Border::default)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Color(Color),
+    Transform(Transform),
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<Color> for Value {
+    fn from(value: Color) -> Self {
+        Self::Color(value)
+    }
+}
+
+impl From<Transform> for Value {
+    fn from(value: Transform) -> Self {
+        Self::Transform(value)
+    }
+}
+
+impl Value {
+    // --- t may overshoot [0, 1] with springs & some bezier curves
+    pub fn lerp(&self, to: &Value, t: f32) -> Value {
+        match (self, to) {
+            (Self::Number(a), Self::Number(b)) => Self::Number(a + (b - a) * t),
+            (Self::Color(a), Self::Color(b)) => {
+                let mut out = [0.; 4];
+                for i in 0..4 {
+                    out[i] = (a[i] + (b[i] - a[i]) * t).clamp(0., 1.);
+                }
+                Self::Color(out)
+            }
+            (Self::Transform(a), Self::Transform(b)) => Self::Transform(a.lerp(b, t)),
+            (_, to) => *to,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    Linear,
+    #[default]
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    CubicBezier(f32, f32, f32, f32),
+    // --- a damped spring released from 0 towards 1, simulated over the animation's duration
+    Spring {
+        stiffness: f32,
+        damping: f32,
+        mass: f32,
+    },
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32, duration: Duration) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match *self {
+            Self::Linear => t,
+            Self::Ease => cubic_bezier(0.25, 0.1, 0.25, 1., t),
+            Self::EaseIn => cubic_bezier(0.42, 0., 1., 1., t),
+            Self::EaseOut => cubic_bezier(0., 0., 0.58, 1., t),
+            Self::EaseInOut => cubic_bezier(0.42, 0., 0.58, 1., t),
+            Self::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Self::Spring {
+                stiffness,
+                damping,
+                mass,
+            } => {
+                // --- make sure we land exactly on the target
+                if t >= 1. {
+                    return 1.;
+                }
+                spring(stiffness, damping, mass, t * duration.as_secs_f32())
+            }
+        }
+    }
+}
+
+// --- same curve as CSS `cubic-bezier()`, solved for x with newton's method
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let bezier = |p1: f32, p2: f32, s: f32| {
+        let inv = 1. - s;
+        3. * inv * inv * s * p1 + 3. * inv * s * s * p2 + s * s * s
+    };
+    let slope = |p1: f32, p2: f32, s: f32| {
+        let inv = 1. - s;
+        3. * inv * inv * p1 + 6. * inv * s * (p2 - p1) + 3. * s * s * (1. - p2)
+    };
+
+    let mut s = x;
+    for _ in 0..8 {
+        let dx = bezier(x1, x2, s) - x;
+        if dx.abs() < 1e-5 {
+            break;
+        }
+        let d = slope(x1, x2, s);
+        if d.abs() < 1e-6 {
+            break;
+        }
+        s = (s - dx / d).clamp(0., 1.);
+    }
+
+    bezier(y1, y2, s)
+}
+
+fn spring(stiffness: f32, damping: f32, mass: f32, time: f32) -> f32 {
+    let omega = (stiffness / mass).sqrt();
+    let zeta = damping / (2. * (stiffness * mass).sqrt());
+
+    if zeta < 1. {
+        let omega_d = omega * (1. - zeta * zeta).sqrt();
+        let envelope = (-zeta * omega * time).exp();
+        1. - envelope * ((omega_d * time).cos() + (zeta * omega / omega_d) * (omega_d * time).sin())
+    } else {
+        // --- critically damped, overdamped springs are close enough to it for UI purposes
+        1. - (-omega * time).exp() * (1. + omega * time)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Count(u32),
+    Forever,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub property: Property,
+    pub from: Value,
+    pub to: Value,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+    pub repeat: Repeat,
+    pub alternate: bool,
+    // --- set on the first frame the animation is seen
+    start: Option<Instant>,
+    // --- show `from` while delayed, like a CSS transition does
+    hold_during_delay: bool,
+}
+
+impl Animation {
+    pub fn new(property: Property, from: impl Into<Value>, to: impl Into<Value>) -> Self {
+        Self {
+            property,
+            from: from.into(),
+            to: to.into(),
+            duration: Duration::from_millis(300),
+            delay: Duration::ZERO,
+            easing: Easing::default(),
+            repeat: Repeat::Count(1),
+            alternate: false,
+            start: None,
+            hold_during_delay: false,
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    // --- every other iteration plays backwards
+    pub fn alternate(mut self) -> Self {
+        self.alternate = true;
+        self
+    }
+
+    pub fn restart(&mut self) {
+        self.start = None;
+    }
+
+    fn elapsed(&self, now: Instant) -> Option<Duration> {
+        self.start
+            .map(|start| now.saturating_duration_since(start))
+            .and_then(|elapsed| elapsed.checked_sub(self.delay))
+    }
+
+    fn is_finished(&self, now: Instant) -> bool {
+        match (self.elapsed(now), self.repeat) {
+            (None, _) => false,
+            // --- nothing to play, it lands on the end value as soon as it starts
+            (Some(_), _) if self.duration.is_zero() => true,
+            (Some(_), Repeat::Forever) => false,
+            (Some(elapsed), Repeat::Count(count)) => elapsed >= self.duration * count,
+        }
+    }
+
+    pub fn is_running(&self, now: Instant) -> bool {
+        self.start.is_none() || !self.is_finished(now)
+    }
+
+    // --- `None` while waiting for the delay, the last value is held once finished
+    pub fn value(&self, now: Instant) -> Option<Value> {
+        let Some(elapsed) = self.elapsed(now) else {
+            return self.hold_during_delay.then_some(self.from);
+        };
+
+        let (iteration, t) = if self.is_finished(now) {
+            let count = match self.repeat {
+                Repeat::Count(count) => count.max(1),
+                Repeat::Forever => 1,
+            };
+            (count - 1, 1.)
+        } else {
+            let progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+            (progress as u32, progress.fract())
+        };
+
+        let t = if self.alternate && iteration % 2 == 1 {
+            1. - t
+        } else {
+            t
+        };
+
+        Some(
+            self.from
+                .lerp(&self.to, self.easing.apply(t, self.duration)),
+        )
+    }
+}
+
+// --- the equivalent of a CSS `transition`, `None` as the property means `all`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub property: Option<Property>,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+}
+
+impl Transition {
+    pub fn new(property: Option<Property>, duration: Duration) -> Self {
+        Self {
+            property,
+            duration,
+            delay: Duration::ZERO,
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+// --- everything that moves on an element: its own animations,
+// --- plus the transitions started when its resolved style changes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Motion {
+    pub animations: Vec<Animation>,
+    transitions: Vec<Animation>,
+    target: Option<(Style, Rect)>,
+}
+
+impl Motion {
+    pub fn animate(&mut self, animation: Animation) {
+        self.animations.push(animation);
+    }
+
+    // --- called once per frame with the un-animated style & rect,
+    // --- returns whether anything will still be moving after this frame
+    pub fn update(&mut self, style: &Style, rect: &Rect, now: Instant) -> bool {
+        for animation in self.animations.iter_mut() {
+            animation.start.get_or_insert(now);
+        }
+
+        // --- finished animations hold their last value until a later one on the same
+        // --- property shows, from then on they're hidden for good & can go
+        let mut shown = HashSet::new();
+        let mut animations = std::mem::take(&mut self.animations);
+        animations.reverse();
+        animations.retain(|animation| {
+            let hidden = shown.contains(&animation.property);
+            if animation.value(now).is_some() {
+                shown.insert(animation.property);
+            }
+            !(hidden && animation.is_finished(now))
+        });
+        animations.reverse();
+        self.animations = animations;
+
+        if let Some((old_style, old_rect)) = self.target.take() {
+            let transitions = style.transitions.as_deref().unwrap_or_default();
+
+            if !transitions.is_empty() && (&old_style, &old_rect) != (style, rect) {
+                // --- what's on screen right now, so interrupted transitions don't jump
+                let (current_style, current_rect) = self.apply(old_style.clone(), old_rect, now);
+
+                for property in Property::ALL {
+                    let to = property.get(style, rect);
+                    if property.get(&old_style, &old_rect) == to {
+                        continue;
+                    }
+
+                    let spec = transitions
+                        .iter()
+                        .rev()
+                        .find(|t| t.property.is_none_or(|p| p == property));
+                    let Some(spec) = spec else {
+                        continue;
+                    };
+
+                    let from = property.get(&current_style, &current_rect);
+                    self.transitions.retain(|t| t.property != property);
+                    self.transitions.push(Animation {
+                        start: Some(now),
+                        hold_during_delay: true,
+                        ..Animation::new(property, from, to)
+                            .with_duration(spec.duration)
+                            .with_delay(spec.delay)
+                            .with_easing(spec.easing)
+                    });
+                }
+            }
+        }
+
+        self.target = Some((style.clone(), *rect));
+        self.transitions.retain(|t| t.is_running(now));
+
+        self.is_running(now)
+    }
+
+    pub fn is_running(&self, now: Instant) -> bool {
+        self.animations
+            .iter()
+            .chain(self.transitions.iter())
+            .any(|a| a.is_running(now))
+    }
+
+    // --- explicit animations are applied last so they win over transitions
+    pub fn apply(&self, mut style: Style, mut rect: Rect, now: Instant) -> (Style, Rect) {
+        for animation in self.transitions.iter().chain(self.animations.iter()) {
+            if let Some(value) = animation.value(now) {
+                animation.property.set(&mut style, &mut rect, value);
+            }
+        }
+
+        (style, rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = [1., 0., 0., 1.];
+    const BLUE: Color = [0., 0., 1., 1.];
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn number(value: Option<Value>) -> f32 {
+        match value {
+            Some(Value::Number(n)) => n,
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+
+    fn started(animation: Animation, start: Instant) -> Animation {
+        Animation {
+            start: Some(start),
+            ..animation
+        }
+    }
+
+    #[test]
+    fn easing_endpoints() {
+        let easings = [
+            Easing::Linear,
+            Easing::Ease,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier(0.68, -0.55, 0.27, 1.55),
+            Easing::Spring {
+                stiffness: 170.,
+                damping: 12.,
+                mass: 1.,
+            },
+        ];
+
+        for easing in easings {
+            assert!(easing.apply(0., ms(300)).abs() < 1e-4, "{:?}", easing);
+            assert!(
+                (easing.apply(1., ms(300)) - 1.).abs() < 1e-4,
+                "{:?}",
+                easing
+            );
+            // --- t outside 0..1 is clamped
+            assert_eq!(easing.apply(-1., ms(300)), easing.apply(0., ms(300)));
+            assert_eq!(easing.apply(2., ms(300)), easing.apply(1., ms(300)));
+        }
+
+        assert_eq!(Easing::Linear.apply(0.3, ms(300)), 0.3);
+        assert!(Easing::EaseIn.apply(0.5, ms(300)) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5, ms(300)) > 0.5);
+    }
+
+    #[test]
+    fn repeat() {
+        let start = Instant::now();
+        let animation = Animation::new(Property::Opacity, 0., 1.)
+            .with_duration(ms(100))
+            .with_easing(Easing::Linear);
+
+        let once = started(animation.clone(), start);
+        assert!((number(once.value(start + ms(25))) - 0.25).abs() < 1e-4);
+        assert!(once.is_running(start + ms(99)));
+        // --- the last value is held once finished
+        assert!(!once.is_running(start + ms(100)));
+        assert_eq!(number(once.value(start + ms(500))), 1.);
+
+        let twice = started(
+            animation.clone().with_repeat(Repeat::Count(2)).alternate(),
+            start,
+        );
+        assert!((number(twice.value(start + ms(125))) - 0.75).abs() < 1e-4);
+        assert!(twice.is_running(start + ms(150)));
+        assert_eq!(number(twice.value(start + ms(500))), 0.);
+
+        let forever = started(animation.clone().with_repeat(Repeat::Forever), start);
+        assert!((number(forever.value(start + ms(1025))) - 0.25).abs() < 1e-4);
+        assert!(forever.is_running(start + ms(100_000)));
+
+        let delayed = started(animation.with_delay(ms(50)), start);
+        assert_eq!(delayed.value(start + ms(20)), None);
+        assert!((number(delayed.value(start + ms(75))) - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zero_duration() {
+        let start = Instant::now();
+
+        for repeat in [Repeat::Count(3), Repeat::Forever] {
+            let animation = started(
+                Animation::new(Property::Opacity, 0., 1.)
+                    .with_duration(Duration::ZERO)
+                    .with_repeat(repeat),
+                start,
+            );
+            assert_eq!(number(animation.value(start)), 1., "{:?}", repeat);
+            assert!(!animation.is_running(start + ms(1)), "{:?}", repeat);
+        }
+    }
+
+    #[test]
+    fn transition() {
+        let rect = Rect::new(0., 0., 10., 10.);
+        let transition =
+            Transition::new(Some(Property::Background), ms(100)).with_easing(Easing::Linear);
+        let from = Style::new()
+            .with_background(RED)
+            .with_transition(transition);
+        let to = Style::new()
+            .with_background(BLUE)
+            .with_transition(transition);
+
+        let start = Instant::now();
+        let mut motion = Motion::default();
+        assert!(!motion.update(&from, &rect, start));
+        assert!(motion.update(&to, &rect, start));
+
+        let (style, _) = motion.apply(to.clone(), rect, start + ms(50));
+        assert_eq!(style.background, Some([0.5, 0., 0.5, 1.]));
+        // --- properties without a transition don't get one
+        assert_eq!(style.border, None);
+
+        assert!(!motion.update(&to, &rect, start + ms(100)));
+        let (style, _) = motion.apply(to.clone(), rect, start + ms(100));
+        assert_eq!(style.background, Some(BLUE));
+    }
+
+    #[test]
+    fn finished_animations_are_pruned() {
+        let rect = Rect::new(0., 0., 10., 10.);
+        let style = Style::new();
+        let mut now = Instant::now();
+        let mut motion = Motion::default();
+
+        motion.animate(Animation::new(Property::Radius, 0., 4.).with_repeat(Repeat::Forever));
+        for _ in 0..10 {
+            motion.animate(Animation::new(Property::Opacity, 1., 0.).with_duration(ms(10)));
+            motion.update(&style, &rect, now);
+            now += ms(20);
+        }
+
+        // --- the spinning one & the latest fade, which still holds its last value
+        assert_eq!(motion.animations.len(), 2);
+        motion.update(&style, &rect, now);
+        let (style, _) = motion.apply(style, rect, now);
+        assert_eq!(style.opacity, Some(0.));
+    }
+}
@@ -0,0 +1,177 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+    // --- nothing in the clipboard, or nothing in the requested format
+    Empty,
+    // --- the backend can't hold this kind of content
+    Unsupported,
+    // --- the backend failed, e.g. the system clipboard is held by someone else
+    Backend(String),
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "clipboard is empty"),
+            Self::Unsupported => write!(f, "clipboard doesn't support this content"),
+            Self::Backend(message) => write!(f, "clipboard: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+impl From<arboard::Error> for ClipboardError {
+    fn from(err: arboard::Error) -> Self {
+        match err {
+            arboard::Error::ContentNotAvailable => Self::Empty,
+            arboard::Error::ClipboardNotSupported => Self::Unsupported,
+            err => Self::Backend(err.to_string()),
+        }
+    }
+}
+
+// --- 8 bits per channel, rows top to bottom
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub trait ClipboardBackend {
+    fn text(&mut self) -> Result<String, ClipboardError>;
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+
+    fn image(&mut self) -> Result<ClipboardImage, ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    fn set_image(&mut self, _image: &ClipboardImage) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+}
+
+// --- lives only as long as the app, what headless runs & tests use
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+    image: Option<ClipboardImage>,
+}
+
+// --- like the system ones, setting one kind of content clears the other
+impl ClipboardBackend for MemoryClipboard {
+    fn text(&mut self) -> Result<String, ClipboardError> {
+        self.text.clone().ok_or(ClipboardError::Empty)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.text = Some(text.to_string());
+        self.image = None;
+        Ok(())
+    }
+
+    fn image(&mut self) -> Result<ClipboardImage, ClipboardError> {
+        self.image.clone().ok_or(ClipboardError::Empty)
+    }
+
+    fn set_image(&mut self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        self.image = Some(image.clone());
+        self.text = None;
+        Ok(())
+    }
+}
+
+pub struct SystemClipboard {
+    inner: arboard::Clipboard,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self, ClipboardError> {
+        Ok(Self {
+            inner: arboard::Clipboard::new()?,
+        })
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn text(&mut self) -> Result<String, ClipboardError> {
+        Ok(self.inner.get_text()?)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        Ok(self.inner.set_text(text)?)
+    }
+
+    fn image(&mut self) -> Result<ClipboardImage, ClipboardError> {
+        let image = self.inner.get_image()?;
+        Ok(ClipboardImage {
+            width: image.width as u32,
+            height: image.height as u32,
+            rgba: image.bytes.into_owned(),
+        })
+    }
+
+    fn set_image(&mut self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        Ok(self.inner.set_image(arboard::ImageData {
+            width: image.width as usize,
+            height: image.height as usize,
+            bytes: image.rgba.as_slice().into(),
+        })?)
+    }
+}
+
+// --- a cheap handle, clones share the same backend
+#[derive(Clone)]
+pub struct Clipboard {
+    backend: Rc<RefCell<dyn ClipboardBackend>>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl Clipboard {
+    pub fn new(backend: impl ClipboardBackend + 'static) -> Self {
+        Self {
+            backend: Rc::new(RefCell::new(backend)),
+        }
+    }
+
+    pub fn memory() -> Self {
+        Self::new(MemoryClipboard::default())
+    }
+
+    // --- falls back to an in-memory one where there's no system clipboard, e.g. on CI
+    pub fn system() -> Self {
+        match SystemClipboard::new() {
+            Ok(system) => Self::new(system),
+            Err(err) => {
+                log::warn!("No system clipboard ({}), using an in-memory one", err);
+                Self::memory()
+            }
+        }
+    }
+
+    pub fn text(&self) -> Result<String, ClipboardError> {
+        self.backend.borrow_mut().text()
+    }
+
+    pub fn set_text(&self, text: &str) -> Result<(), ClipboardError> {
+        self.backend.borrow_mut().set_text(text)
+    }
+
+    pub fn image(&self) -> Result<ClipboardImage, ClipboardError> {
+        self.backend.borrow_mut().image()
+    }
+
+    pub fn set_image(&self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        self.backend.borrow_mut().set_image(image)
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crate::geometry::Rect;
+
+// --- past this many separate rects, one around all of them is cheaper to draw
+const MAX_RECTS: usize = 8;
+
+// --- what was drawn last frame, as a signature & the window rect of every draw in painting
+// --- order, to tell which parts of the window need drawing again
+#[derive(Debug)]
+pub struct Damage {
+    last: Vec<(u64, Rect)>,
+    // --- what's on screen can't be trusted, e.g. after a resize
+    everything: bool,
+}
+
+// --- nothing has been drawn yet
+impl Default for Damage {
+    fn default() -> Self {
+        Self {
+            last: vec![],
+            everything: true,
+        }
+    }
+}
+
+impl Damage {
+    // --- the next frame is drawn whole
+    pub fn invalidate(&mut self) {
+        self.everything = true;
+    }
+
+    // --- the parts of `window` to draw again, whole pixels that don't overlap,
+    // --- none when the frame would look just like the last one
+    pub fn update(&mut self, draws: Vec<(u64, Rect)>, window: Rect) -> Vec<Rect> {
+        let last = std::mem::replace(&mut self.last, draws);
+        if std::mem::take(&mut self.everything) {
+            return vec![window];
+        }
+        let draws = &self.last;
+
+        // --- draws found in both frames, as many times as they're found in both
+        let mut counts = HashMap::<u64, (usize, usize)>::new();
+        for (signature, _) in &last {
+            counts.entry(*signature).or_default().0 += 1;
+        }
+        for (signature, _) in draws {
+            counts.entry(*signature).or_default().1 += 1;
+        }
+        let shared = |list: &[(u64, Rect)]| {
+            let mut left = counts
+                .iter()
+                .map(|(signature, (before, now))| (*signature, *before.min(now)))
+                .collect::<HashMap<_, _>>();
+            list.iter()
+                .map(|(signature, _)| {
+                    let left = left.get_mut(signature).filter(|left| **left > 0);
+                    left.map(|left| *left -= 1).is_some()
+                })
+                .collect::<Vec<_>>()
+        };
+        let (kept_before, kept_now) = (shared(&last), shared(draws));
+
+        // --- the ones that came or went
+        let mut rects = Vec::new();
+        for (list, kept) in [(&last, &kept_before), (draws, &kept_now)] {
+            let gone = list.iter().zip(kept).filter(|(_, kept)| !**kept);
+            rects.extend(gone.map(|((_, rect), _)| *rect));
+        }
+
+        // --- the ones that stayed but are painted in another order, from the first one
+        // --- out of place on, to keep it simple
+        let order = |list: &[(u64, Rect)], kept: &[bool]| {
+            list.iter()
+                .zip(kept)
+                .filter(|(_, kept)| **kept)
+                .map(|(draw, _)| *draw)
+                .collect::<Vec<_>>()
+        };
+        let (before, now) = (order(&last, &kept_before), order(draws, &kept_now));
+        let moved = before.iter().zip(&now).position(|((a, _), (b, _))| a != b);
+        if let Some(first) = moved {
+            rects.extend(before[first..].iter().map(|(_, rect)| *rect));
+            rects.extend(now[first..].iter().map(|(_, rect)| *rect));
+        }
+
+        let rects = rects
+            .into_iter()
+            .map(|rect| snap(&rect.intersect(&window)))
+            .filter(|rect| rect.width > 0. && rect.height > 0.)
+            .collect();
+        merge(rects)
+    }
+}
+
+// --- out to whole pixels
+fn snap(rect: &Rect) -> Rect {
+    let (x, y) = (rect.x.floor(), rect.y.floor());
+    Rect::new(x, y, rect.right().ceil() - x, rect.bottom().ceil() - y)
+}
+
+// --- overlapping rects become the one around them until none overlap
+fn merge(mut rects: Vec<Rect>) -> Vec<Rect> {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut idx = 0;
+        while idx < rects.len() {
+            let other = (idx + 1..rects.len()).find(|&other| rects[idx].overlaps(&rects[other]));
+            match other {
+                Some(other) => {
+                    let rect = rects.swap_remove(other);
+                    rects[idx] = rects[idx].union(&rect);
+                    merged = true;
+                }
+                None => idx += 1,
+            }
+        }
+    }
+
+    if rects.len() > MAX_RECTS {
+        let first = rects[0];
+        return vec![rects.iter().fold(first, |all, rect| all.union(rect))];
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Rect = Rect {
+        x: 0.,
+        y: 0.,
+        width: 100.,
+        height: 100.,
+    };
+
+    fn draw(signature: u64, x: f32, y: f32) -> (u64, Rect) {
+        (signature, Rect::new(x, y, 10., 10.))
+    }
+
+    // --- in a stable order, merging doesn't keep one
+    fn sorted(mut rects: Vec<Rect>) -> Vec<Rect> {
+        rects.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+        rects
+    }
+
+    #[test]
+    fn unchanged_frames() {
+        let mut damage = Damage::default();
+        let frame = vec![draw(1, 0., 0.), draw(2, 50., 50.)];
+
+        // --- nothing is on screen to begin with
+        assert_eq!(damage.update(frame.clone(), WINDOW), [WINDOW]);
+        assert_eq!(damage.update(frame.clone(), WINDOW), []);
+
+        damage.invalidate();
+        assert_eq!(damage.update(frame.clone(), WINDOW), [WINDOW]);
+        assert_eq!(damage.update(frame, WINDOW), []);
+    }
+
+    #[test]
+    fn changed_signatures() {
+        let mut damage = Damage::default();
+        damage.update(vec![draw(1, 0., 0.), draw(2, 50., 50.)], WINDOW);
+
+        // --- a draw that changed in place
+        let rects = damage.update(vec![draw(1, 0., 0.), draw(3, 50., 50.)], WINDOW);
+        assert_eq!(rects, [Rect::new(50., 50., 10., 10.)]);
+
+        // --- one that moved, where it was & where it is both need drawing
+        let rects = damage.update(vec![draw(4, 20., 0.), draw(3, 50., 50.)], WINDOW);
+        assert_eq!(
+            sorted(rects),
+            [Rect::new(0., 0., 10., 10.), Rect::new(20., 0., 10., 10.)]
+        );
+
+        // --- duplicates count, a second copy of the same draw is new
+        let rects = damage.update(
+            vec![draw(4, 20., 0.), draw(3, 50., 50.), draw(3, 50., 50.)],
+            WINDOW,
+        );
+        assert_eq!(rects, [Rect::new(50., 50., 10., 10.)]);
+    }
+
+    #[test]
+    fn painting_order() {
+        let mut damage = Damage::default();
+        let (a, b, c) = (draw(1, 0., 0.), draw(2, 5., 5.), draw(3, 80., 80.));
+        damage.update(vec![c, a, b], WINDOW);
+
+        // --- from the first one out of place on
+        let rects = damage.update(vec![c, b, a], WINDOW);
+        assert_eq!(rects, [Rect::new(0., 0., 15., 15.)]);
+    }
+
+    #[test]
+    fn merge_and_snap() {
+        let mut damage = Damage::default();
+        damage.update(vec![], WINDOW);
+
+        // --- overlapping rects become one, the part outside the window is dropped
+        let rects = damage.update(
+            vec![
+                (1, Rect::new(10.5, 10.5, 10., 10.)),
+                (2, Rect::new(15., 15., 10., 10.)),
+                (3, Rect::new(95., 95., 10., 10.)),
+            ],
+            WINDOW,
+        );
+        assert_eq!(
+            sorted(rects),
+            [Rect::new(10., 10., 15., 15.), Rect::new(95., 95., 5., 5.)]
+        );
+
+        // --- too many apart, one around all of them
+        let mut damage = Damage::default();
+        damage.update(vec![], WINDOW);
+        let scattered = (0..=MAX_RECTS)
+            .map(|idx| draw(idx as u64 + 10, idx as f32 * 11., 0.))
+            .collect();
+        let rects = damage.update(scattered, WINDOW);
+        assert_eq!(
+            rects,
+            [Rect::new(0., 0., MAX_RECTS as f32 * 11. + 10., 10.)]
+        );
+    }
+}
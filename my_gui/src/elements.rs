@@ -1,17 +1,304 @@
+// --- lets an element be used as its `ElementBase`, for the shared setters
+macro_rules! deref_base {
+    ($element:ty) => {
+        impl std::ops::Deref for $element {
+            type Target = $crate::elements::ElementBase;
+
+            fn deref(&self) -> &Self::Target {
+                &self.base
+            }
+        }
+
+        impl std::ops::DerefMut for $element {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.base
+            }
+        }
+    };
+}
+
+mod base;
 mod button;
+mod canvas;
+mod container;
+mod icon;
+mod image;
+mod scroll;
+mod text_area;
+mod text_input;
+mod video;
 
+#[cfg(test)]
+mod testing;
+
+pub use self::image::{Fit, Image, ImageStatus};
+pub use self::video::Video;
+pub use base::ElementBase;
 pub use button::Button;
+pub use canvas::{Canvas, Painter};
+pub use container::Container;
+pub use icon::Icon;
+pub use scroll::Scroll;
+pub use text_area::TextArea;
+pub use text_input::TextInput;
 
+use crate::animation::Motion;
+use crate::event::{ActionContext, Event, Listeners};
+use crate::geometry::Rect;
+use crate::path::Mesh;
+use crate::style::{Cascade, Color, ElementState, Gradient, Shadow, Style, Styles, TRANSPARENT};
+use crate::text::Bitmap;
+use crate::texture::Texture;
+use crate::theme::Theme;
 use crate::vertex::Vertex;
+use crate::video::VideoFrame;
+use crate::view::{Layout, Placement, View};
+
+use std::time::Instant;
+
+// --- texels along a gradient, the shader expects the same
+const RAMP_WIDTH: u32 = 256;
 
 pub fn button() -> Button {
     Button::default()
 }
 
+pub fn canvas() -> Canvas {
+    Canvas::default()
+}
+
+pub fn container() -> Container {
+    Container::default()
+}
+
+pub fn icon() -> Icon {
+    Icon::default()
+}
+
+pub fn image() -> Image {
+    Image::default()
+}
+
+pub fn video() -> Video {
+    Video::default()
+}
+
+pub fn scroll() -> Scroll {
+    Scroll::default()
+}
+
+pub fn text_input() -> TextInput {
+    TextInput::default()
+}
+
+pub fn text_area() -> TextArea {
+    TextArea::default()
+}
+
+// --- text being composed with an input method, shown at the caret
+// --- but not part of the value until it's committed
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Composition {
+    text: String,
+    cursor: Option<std::ops::Range<usize>>,
+}
+
+impl Composition {
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    // --- where the caret sits inside the composed text
+    fn caret(&self) -> usize {
+        self.cursor.as_ref().map_or(self.len(), |cursor| cursor.end)
+    }
+}
+
+// --- what an element draws on top of its own box: text, a caret, a selection...
+#[derive(Debug, Clone)]
+pub struct Layer {
+    // --- window coordinates
+    pub rect: Rect,
+    pub style: Style,
+    pub fill: Fill,
+}
+
+#[derive(Debug, Clone)]
+pub enum Fill {
+    Solid(Color),
+    // --- `source` is the part of it drawn, in 0..1 of its size, every pixel multiplied by `tint`
+    Texture {
+        texture: Texture,
+        source: Rect,
+        tint: Color,
+    },
+    // --- drawn through the video pipeline, `source` as for textures
+    Video {
+        frame: VideoFrame,
+        source: Rect,
+    },
+}
+
+impl Fill {
+    // --- all of the texture, as it is
+    pub fn texture(texture: Texture) -> Self {
+        Self::Texture {
+            texture,
+            source: Rect::new(0., 0., 1., 1.),
+            tint: [1., 1., 1., 1.],
+        }
+    }
+
+    pub fn vertices(
+        &self,
+        rect: &Rect,
+        style: &Style,
+        size: &winit::dpi::PhysicalSize<u32>,
+    ) -> [Vertex; 4] {
+        let mut vertices = quad(rect, style, size);
+        if let Self::Texture { source, tint, .. } = self {
+            for vertex in vertices.iter_mut() {
+                vertex.uv = [source.x, source.y, source.width, source.height];
+                vertex.tint = *tint;
+            }
+        }
+        vertices
+    }
+}
+
 pub trait IntoElement {
-    fn color(&self) -> [f32; 4];
+    // --- the name stylesheets select this element by, e.g. `button`
+    fn type_name(&self) -> &'static str;
 
-    fn vertices(&self, size: &winit::dpi::PhysicalSize<u32>) -> [Vertex; 4];
+    fn base(&self) -> &ElementBase;
+
+    fn base_mut(&mut self) -> &mut ElementBase;
+
+    fn id(&self) -> Option<&str> {
+        self.base().id.as_deref()
+    }
+
+    fn classes(&self) -> &[String] {
+        &self.base().classes
+    }
+
+    fn styles(&self) -> &Styles {
+        &self.base().styles
+    }
+
+    fn state(&self) -> ElementState {
+        self.base().state
+    }
+
+    fn state_mut(&mut self) -> &mut ElementState {
+        &mut self.base_mut().state
+    }
+
+    fn motion(&self) -> &Motion {
+        &self.base().motion
+    }
+
+    fn motion_mut(&mut self) -> &mut Motion {
+        &mut self.base_mut().motion
+    }
+
+    // --- margin box, relative to the content box of the parent
+    fn rect(&self) -> Rect {
+        self.base().rect()
+    }
+
+    fn children(&self) -> &[View] {
+        &[]
+    }
+
+    // --- how far the children are scrolled, they're drawn this much up & to the left
+    fn scroll_offset(&self) -> [f32; 2] {
+        [0., 0.]
+    }
+
+    // --- the window rect the children are cut to, from the element's own layout
+    fn clip(&self, _layout: &Layout) -> Option<Rect> {
+        None
+    }
+
+    // --- moves whatever the element animates on its own, returns whether it's still moving
+    fn step(&mut self, _now: Instant) -> bool {
+        false
+    }
+
+    // --- whether the element can take keyboard focus right now
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn tab_index(&self) -> Option<i32> {
+        self.base().tab_index
+    }
+
+    fn listeners(&self) -> &Listeners {
+        &self.base().listeners
+    }
+
+    // --- runs after the event went through the tree without `prevent_default`,
+    // --- returning true stops it from reaching the ancestors' default actions
+    fn default_action(&mut self, _event: &Event, _cx: &ActionContext) -> bool {
+        false
+    }
+
+    // --- drawn above the element's box, in order
+    fn layers(&self, _placement: &Placement) -> Vec<Layer> {
+        vec![]
+    }
+
+    // --- triangles drawn above the layers, in window coordinates
+    fn meshes(&self, _placement: &Placement) -> Vec<Mesh> {
+        vec![]
+    }
+
+    // --- where the caret is while the element is focused, `None` when it doesn't take text.
+    // --- input methods are only enabled for elements returning an area
+    fn ime_cursor_area(&self, _placement: &Placement) -> Option<Rect> {
+        None
+    }
+
+    fn children_mut(&mut self) -> &mut [View] {
+        &mut []
+    }
+
+    // --- what the element looks like when the user didn't style it
+    fn default_styles(&self, _theme: &Theme) -> Styles {
+        Styles::default()
+    }
+
+    // --- each layer is resolved separately so a more specific layer wins over
+    // --- every variant of the ones below: theme < stylesheet < inline
+    fn style(&self, cascade: &Cascade) -> Style {
+        let state = self.state();
+        let sheet = cascade
+            .stylesheet
+            .styles_for(self.type_name(), self.id(), self.classes());
+
+        self.default_styles(cascade.theme)
+            .resolve(state)
+            .merge(&sheet.resolve(state))
+            .merge(&self.styles().resolve(state))
+    }
+
+    // --- style & rect as they should be painted at `cascade.now`
+    fn animated(&self, cascade: &Cascade) -> (Style, Rect) {
+        self.motion()
+            .apply(self.style(cascade), self.rect(), cascade.now)
+    }
+
+    // --- rect is the border box in window coordinates
+    fn vertices(
+        &self,
+        rect: &Rect,
+        style: &Style,
+        size: &winit::dpi::PhysicalSize<u32>,
+    ) -> [Vertex; 4] {
+        quad(rect, style, size)
+    }
 
     #[rustfmt::skip]
     fn indices(&self, base: u32) -> [u32; 6] {
@@ -21,39 +308,141 @@ pub trait IntoElement {
         ]
     }
 
-    fn rgba(&self) -> Vec<u8> {
-        self.color()
-            .iter()
-            .map(|i| (*i * 255.) as u8)
-            .collect::<Vec<_>>()
+    // --- what the element's quad is filled with, the renderer keeps one texture per color
+    fn fill_color(&self, style: &Style) -> Color {
+        // --- a gradient takes the color from the ramp, the texture only masks it
+        match style.background_gradient {
+            Some(_) => [1., 1., 1., 1.],
+            None => style.background.unwrap_or(TRANSPARENT),
+        }
     }
+}
 
-    fn texture_view(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
-        let dimensions = (1u32, 1u32); // unicolored shape
+// --- a quad covering `rect` (window coordinates) in NDC,
+// --- carrying what the fragment shader needs for border & radius
+#[rustfmt::skip]
+pub fn quad(
+    rect: &Rect,
+    style: &Style,
+    size: &winit::dpi::PhysicalSize<u32>,
+) -> [Vertex; 4] {
+    let top    = 1. - (rect.y        / (size.height as f32 / 2.));
+    let left   = (rect.x             / (size.width as f32 / 2.)) - 1.;
+    let bottom = 1. - (rect.bottom() / (size.height as f32 / 2.));
+    let right  = (rect.right()       / (size.width as f32 / 2.)) - 1.;
 
-        let rgba = self.rgba();
+    let border = style.border.unwrap_or_default();
+    let base = Vertex {
+        position: [0., 0., 0.],
+        tex_coords: [0., 0.],
+        size: [rect.width, rect.height],
+        radius: style.radius.unwrap_or(0.),
+        border_width: border.width,
+        border_color: border.color,
+        opacity: style.opacity.unwrap_or(1.),
+        uv: [0., 0., 1., 1.],
+        tint: [1., 1., 1., 1.],
+        gradient: style.background_gradient.as_ref().map_or([0.; 4], Gradient::params),
+        border_gradient: style.border_gradient.as_ref().map_or([0.; 4], Gradient::params),
+        blur: 0.,
+    };
 
-        let size = wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
-            depth_or_array_layers: 1,
-        };
+    [
+        Vertex { position: [left,  top,    0.], tex_coords: [0., 0.], ..base },
+        Vertex { position: [left,  bottom, 0.], tex_coords: [0., 1.], ..base },
+        Vertex { position: [right, bottom, 0.], tex_coords: [1., 1.], ..base },
+        Vertex { position: [right, top,    0.], tex_coords: [1., 0.], ..base },
+    ]
+}
 
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Texture"),
-            size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-
-        // something sus is happening here
-        // my guess is, this thing needs to be called only once
-        // but then, how do i define the multiple texture?
+// --- `rect`'s shadow, offset & grown by how far the blur reaches (3 sigma, sigma being half
+// --- the blur like in CSS) for the shader to fade it out in. painted in the shadow's color
+pub fn shadow_quad(
+    rect: &Rect,
+    radius: f32,
+    shadow: &Shadow,
+    size: &winit::dpi::PhysicalSize<u32>,
+) -> [Vertex; 4] {
+    let blur = shadow.blur.max(0.);
+    let reach = blur * 1.5;
+    let [x, y] = shadow.offset;
+    let rect = Rect::new(
+        rect.x + x - reach,
+        rect.y + y - reach,
+        rect.width + reach * 2.,
+        rect.height + reach * 2.,
+    );
 
+    quad(&rect, &Style::new().with_radius(radius), size).map(|vertex| Vertex { blur, ..vertex })
+}
+
+pub fn solid_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    color: Color,
+) -> wgpu::TextureView {
+    let bitmap = Bitmap {
+        width: 1,
+        height: 1,
+        rgba: color.iter().map(|i| (*i * 255.) as u8).collect(), // unicolored shape
+    };
+
+    bitmap_texture(device, queue, &bitmap)
+}
+
+// --- the fill gradient's colors along the top row, the border gradient's along the bottom
+pub fn gradient_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    fill: Option<&Gradient>,
+    border: Option<&Gradient>,
+) -> wgpu::TextureView {
+    let rgba = [fill, border]
+        .into_iter()
+        .flat_map(|gradient| {
+            (0..RAMP_WIDTH).flat_map(move |i| {
+                let t = i as f32 / (RAMP_WIDTH - 1) as f32;
+                let color = gradient.map_or(TRANSPARENT, |gradient| gradient.color_at(t));
+                color.map(|c| (c.clamp(0., 1.) * 255.).round() as u8)
+            })
+        })
+        .collect();
+    let bitmap = Bitmap {
+        width: RAMP_WIDTH,
+        height: 2,
+        rgba,
+    };
+
+    bitmap_texture(device, queue, &bitmap)
+}
+
+pub fn bitmap_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bitmap: &Bitmap,
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: bitmap.width.max(1),
+        height: bitmap.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    // something sus is happening here
+    // my guess is, this thing needs to be called only once
+    // but then, how do i define the multiple texture?
+
+    if bitmap.width > 0 && bitmap.height > 0 {
         queue.write_texture(
             wgpu::ImageCopyTexture {
                 aspect: wgpu::TextureAspect::All,
@@ -61,27 +450,27 @@ pub trait IntoElement {
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
             },
-            &rgba,
+            &bitmap.rgba,
             wgpu::ImageDataLayout {
                 offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
+                bytes_per_row: Some(4 * bitmap.width),
+                rows_per_image: Some(bitmap.height),
             },
             size,
         );
-
-        texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
-    fn sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        })
-    }
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+pub fn sampler(device: &wgpu::Device) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    })
 }
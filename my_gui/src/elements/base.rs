@@ -0,0 +1,85 @@
+use crate::animation::{Animation, Motion};
+use crate::event::{Event, EventContext, EventKind, Listeners};
+use crate::geometry::Rect;
+use crate::style::{ElementState, Styles};
+
+// --- what every element carries: its position, what stylesheets select it by, its own styles,
+// --- state, animations, listeners & tab index. elements deref to it, so the setters below
+// --- are called on the element itself, e.g. `button.set_id("save")`
+#[derive(Clone, Default)]
+pub struct ElementBase {
+    pub top: u32,
+    pub left: u32,
+    pub bottom: u32,
+    pub right: u32,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub styles: Styles,
+    pub state: ElementState,
+    pub motion: Motion,
+    pub listeners: Listeners,
+    pub tab_index: Option<i32>,
+}
+
+impl ElementBase {
+    // --- `position` is [top, left, bottom, right], like `set_position`
+    pub fn at(position: [u32; 4]) -> Self {
+        let mut base = Self::default();
+        base.set_position(position);
+        base
+    }
+
+    pub fn styles(&self) -> &Styles {
+        &self.styles
+    }
+
+    pub fn set_style(&mut self, styles: impl Into<Styles>) {
+        self.styles = styles.into();
+    }
+
+    pub fn set_id(&mut self, id: impl Into<String>) {
+        self.id = Some(id.into());
+    }
+
+    pub fn add_class(&mut self, class: impl Into<String>) {
+        self.classes.push(class.into());
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.state.disabled = disabled;
+    }
+
+    // --- overrides the tree order used by Tab, negative takes it out of the tab order
+    pub fn set_tab_index(&mut self, tab_index: i32) {
+        self.tab_index = Some(tab_index);
+    }
+
+    // --- listens during the target & bubble phases
+    pub fn on(&mut self, kind: EventKind, f: impl Fn(&Event, &mut EventContext) + 'static) {
+        self.listeners.add(kind, false, f);
+    }
+
+    pub fn on_capture(&mut self, kind: EventKind, f: impl Fn(&Event, &mut EventContext) + 'static) {
+        self.listeners.add(kind, true, f);
+    }
+
+    pub fn animate(&mut self, animation: Animation) {
+        self.motion.animate(animation);
+    }
+
+    pub fn position(&self) -> [u32; 4] {
+        [self.top, self.left, self.bottom, self.right]
+    }
+
+    pub fn set_position(&mut self, position: [u32; 4]) {
+        self.top = position[0];
+        self.left = position[1];
+        self.bottom = position[2];
+        self.right = position[3];
+    }
+
+    // --- margin box, relative to the content box of the parent
+    pub fn rect(&self) -> Rect {
+        Rect::from_edges(self.position())
+    }
+}
@@ -1,70 +1,92 @@
-use super::IntoElement;
-use crate::vertex::Vertex;
+use super::{ElementBase, IntoElement};
+use crate::animation::{Easing, Property, Transition};
+use crate::event::{ActionContext, Event};
+use crate::style::{Edges, Style, Styles};
+use crate::theme::Theme;
 use crate::view::{IntoView, View};
 
+use std::rc::Rc;
+use std::time::Duration;
+
+use winit::window::CursorIcon;
+
 #[derive(Clone)]
 pub struct Button {
-    pub top: u32,
-    pub left: u32,
-    pub bottom: u32,
-    pub right: u32,
-    pub color: [f32; 4],
+    pub base: ElementBase,
+    pub on_click: Option<Rc<dyn Fn()>>,
 }
 
 impl Default for Button {
     fn default() -> Self {
         let position = [10, 10, 50, 120];
-        let color = [0.235, 0.639, 0.282, 1.];
 
         Self {
-            top: position[0],
-            left: position[1],
-            bottom: position[2],
-            right: position[3],
-            color,
+            base: ElementBase::at(position),
+            on_click: None,
         }
     }
 }
 
 impl Button {
-    pub fn color(&self) -> [f32; 4] {
-        self.color
+    pub fn on_click(&mut self, f: impl Fn() + 'static) {
+        self.on_click = Some(Rc::new(f));
     }
+}
 
-    pub fn set_color(&mut self, color: [f32; 4]) {
-        self.color = color;
+deref_base!(Button);
+
+impl IntoElement for Button {
+    fn type_name(&self) -> &'static str {
+        "button"
     }
 
-    pub fn position(&self) -> [u32; 4] {
-        [self.top, self.left, self.bottom, self.right]
+    fn base(&self) -> &ElementBase {
+        &self.base
     }
 
-    pub fn set_position(&mut self, position: [u32; 4]) {
-        self.top = position[0];
-        self.left = position[1];
-        self.bottom = position[2];
-        self.right = position[3];
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
     }
-}
 
-impl IntoElement for Button {
-    fn color(&self) -> [f32; 4] {
-        self.color()
+    fn default_styles(&self, theme: &Theme) -> Styles {
+        let palette = &theme.palette;
+
+        Styles::from(
+            Style::new()
+                .with_background(palette.primary)
+                .with_foreground(palette.on_primary)
+                .with_radius(theme.radii.sm)
+                .with_padding(Edges::symmetric(theme.spacing.sm, theme.spacing.md))
+                .with_font(theme.typography.font(theme.typography.body))
+                .with_cursor(CursorIcon::Pointer)
+                .with_transition(
+                    Transition::new(Some(Property::Background), Duration::from_millis(120))
+                        .with_easing(Easing::EaseOut),
+                ),
+        )
+        .with_hover(Style::new().with_background(palette.primary_hover))
+        .with_active(Style::new().with_background(palette.primary_active))
+        .with_disabled(
+            Style::new()
+                .with_opacity(0.5)
+                .with_cursor(CursorIcon::NotAllowed),
+        )
     }
 
-    #[rustfmt::skip]
-    fn vertices(&self, size: &winit::dpi::PhysicalSize<u32>) -> [Vertex; 4] {
-        let top    = 1. - (self.top    as f32 / (size.height as f32 / 2.));
-        let left   = (self.left as f32 / (size.width as f32 / 2.)) - 1.;
-        let bottom = 1. - (self.bottom as f32 / (size.height as f32 / 2.));
-        let right  = (self.right as f32 / (size.width as f32 / 2.)) - 1.;
-
-        [
-            Vertex { position: [left,  top,    0.], tex_coords: [0., 0.] },
-            Vertex { position: [left,  bottom, 0.], tex_coords: [0., 1.] },
-            Vertex { position: [right, bottom, 0.], tex_coords: [1., 1.] },
-            Vertex { position: [right, top,    0.], tex_coords: [1., 0.] },
-        ]
+    fn focusable(&self) -> bool {
+        !self.base.state.disabled
+    }
+
+    fn default_action(&mut self, event: &Event, _cx: &ActionContext) -> bool {
+        match event {
+            Event::Click { .. } if !self.base.state.disabled => {
+                if let Some(f) = self.on_click.as_ref() {
+                    f();
+                }
+                true
+            }
+            _ => false,
+        }
     }
 }
 
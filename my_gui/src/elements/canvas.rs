@@ -0,0 +1,104 @@
+use super::{ElementBase, IntoElement};
+use crate::path::{self, FillRule, Mesh, Path, Stroke};
+use crate::style::Color;
+use crate::view::{IntoView, Placement, View};
+
+use std::rc::Rc;
+
+type Draw = Rc<dyn Fn(&mut Painter)>;
+
+// --- collects what a canvas draws, in px from the top left of its content box
+pub struct Painter {
+    width: f32,
+    height: f32,
+    meshes: Vec<Mesh>,
+}
+
+impl Painter {
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn fill(&mut self, path: &Path, color: Color) {
+        self.fill_with(path, color, FillRule::default());
+    }
+
+    pub fn fill_with(&mut self, path: &Path, color: Color, rule: FillRule) {
+        self.push(path::fill(path, rule, color));
+    }
+
+    pub fn stroke(&mut self, path: &Path, stroke: &Stroke, color: Color) {
+        self.push(path::stroke(path, stroke, color));
+    }
+
+    fn push(&mut self, mesh: Mesh) {
+        if !mesh.is_empty() {
+            self.meshes.push(mesh);
+        }
+    }
+}
+
+// --- draws whatever `on_draw` paints every frame, sized by its content box.
+// --- paths going past the box aren't cut off
+#[derive(Clone, Default)]
+pub struct Canvas {
+    pub base: ElementBase,
+    pub on_draw: Option<Draw>,
+}
+
+impl Canvas {
+    pub fn on_draw(&mut self, f: impl Fn(&mut Painter) + 'static) {
+        self.on_draw = Some(Rc::new(f));
+    }
+}
+
+deref_base!(Canvas);
+
+impl IntoElement for Canvas {
+    fn type_name(&self) -> &'static str {
+        "canvas"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    fn meshes(&self, placement: &Placement) -> Vec<Mesh> {
+        let Some(draw) = self.on_draw.as_ref() else {
+            return vec![];
+        };
+        let target = placement.layout.content_box;
+
+        let mut painter = Painter {
+            width: target.width,
+            height: target.height,
+            meshes: vec![],
+        };
+        draw(&mut painter);
+
+        for mesh in painter.meshes.iter_mut() {
+            mesh.translate(target.x, target.y);
+        }
+        painter.meshes
+    }
+}
+
+impl IntoView for Canvas {
+    fn into_view(self) -> View {
+        View::Canvas(self)
+    }
+}
+
+impl IntoView for &Canvas {
+    fn into_view(self) -> View {
+        View::Canvas(self.to_owned())
+    }
+}
@@ -0,0 +1,52 @@
+use super::{ElementBase, IntoElement};
+use crate::view::{IntoView, View};
+
+// --- a plain box holding other views, children are positioned relative to its content box
+// --- and inherit its foreground, font, cursor & opacity
+#[derive(Clone, Default)]
+pub struct Container {
+    pub base: ElementBase,
+    pub children: Vec<View>,
+}
+
+impl Container {
+    pub fn add_child(&mut self, child: impl IntoView) {
+        self.children.push(child.into_view());
+    }
+}
+
+deref_base!(Container);
+
+impl IntoElement for Container {
+    fn type_name(&self) -> &'static str {
+        "container"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    fn children(&self) -> &[View] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [View] {
+        &mut self.children
+    }
+}
+
+impl IntoView for Container {
+    fn into_view(self) -> View {
+        View::Container(self)
+    }
+}
+
+impl IntoView for &Container {
+    fn into_view(self) -> View {
+        View::Container(self.to_owned())
+    }
+}
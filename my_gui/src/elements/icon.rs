@@ -0,0 +1,85 @@
+use super::{ElementBase, Fill, IntoElement, Layer};
+use crate::geometry::Rect;
+use crate::style::{Color, Style};
+use crate::svg::Svg;
+use crate::view::{IntoView, Placement, View};
+
+// --- an SVG drawn to fill the content box in proportion, rasterized at the size it's
+// --- drawn at so it stays sharp at any scale. `currentColor` is the foreground color
+#[derive(Clone, Default)]
+pub struct Icon {
+    pub base: ElementBase,
+    pub svg: Option<Svg>,
+    // --- what `currentColor` is instead of the foreground
+    pub color: Option<Color>,
+}
+
+impl Icon {
+    pub fn new(svg: Svg) -> Self {
+        Self {
+            svg: Some(svg),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_svg(&mut self, svg: Svg) {
+        self.svg = Some(svg);
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+}
+
+deref_base!(Icon);
+
+impl IntoElement for Icon {
+    fn type_name(&self) -> &'static str {
+        "icon"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let Some(svg) = self.svg.as_ref() else {
+            return vec![];
+        };
+        let style = &placement.style;
+        let target = placement.layout.content_box;
+
+        // --- whole pixels, one bitmap pixel to one on screen
+        let (width, height) = (target.width.round(), target.height.round());
+        if width < 1. || height < 1. {
+            return vec![];
+        }
+        let color = self.color.or(style.foreground).unwrap_or([0., 0., 0., 1.]);
+
+        vec![Layer {
+            rect: Rect::new(target.x.round(), target.y.round(), width, height),
+            style: Style::new().with_opacity(style.opacity.unwrap_or(1.)),
+            fill: Fill::Texture {
+                texture: svg.texture(width as u32, height as u32, color),
+                source: Rect::new(0., 0., 1., 1.),
+                tint: [1., 1., 1., 1.],
+            },
+        }]
+    }
+}
+
+impl IntoView for Icon {
+    fn into_view(self) -> View {
+        View::Icon(self)
+    }
+}
+
+impl IntoView for &Icon {
+    fn into_view(self) -> View {
+        View::Icon(self.to_owned())
+    }
+}
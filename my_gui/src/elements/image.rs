@@ -0,0 +1,358 @@
+use std::path::Path;
+use std::time::Instant;
+
+use super::{ElementBase, Fill, IntoElement, Layer};
+use crate::geometry::Rect;
+use crate::load::{self, Task};
+use crate::style::{Color, Style};
+use crate::text::Bitmap;
+use crate::texture::{ImageError, Texture};
+use crate::view::{IntoView, Placement, View};
+
+// --- how strong the box drawn while loading, or after failing without a fallback, is
+const PLACEHOLDER_ALPHA: f32 = 0.1;
+
+#[derive(Debug, Clone, Default)]
+pub enum ImageStatus {
+    // --- nothing was given to draw
+    #[default]
+    Empty,
+    // --- still being read or decoded on another thread
+    Loading,
+    Ready,
+    Failed(ImageError),
+}
+
+// --- how the picture is sized into the content box, it's always centered in it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Fit {
+    // --- stretched to the box, ignoring its aspect ratio
+    #[default]
+    Fill,
+    // --- as large as fits whole inside the box
+    Contain,
+    // --- as small as covers the whole box, the overflow is cut
+    Cover,
+    // --- at its natural size, cut where it's larger than the box
+    None,
+    // --- like `Contain`, but never made larger than its natural size
+    ScaleDown,
+}
+
+impl Fit {
+    // --- where the whole picture lands for a box, may be larger than the box
+    pub fn place(&self, natural: [f32; 2], target: &Rect) -> Rect {
+        let [width, height] = natural;
+        if *self == Self::Fill || width <= 0. || height <= 0. {
+            return *target;
+        }
+
+        let contain = (target.width / width).min(target.height / height);
+        let scale = match self {
+            Self::Contain => contain,
+            Self::Cover => (target.width / width).max(target.height / height),
+            Self::None => 1.,
+            Self::ScaleDown => contain.min(1.),
+            Self::Fill => unreachable!(),
+        };
+        let (width, height) = (width * scale, height * scale);
+
+        Rect::new(
+            target.x + (target.width - width) / 2.,
+            target.y + (target.height - height) / 2.,
+            width,
+            height,
+        )
+    }
+
+    // --- what's left of the picture after cutting it to the box: where that's drawn,
+    // --- and which part of the picture it is in 0..1 of its size. `None` when nothing is
+    pub fn crop(&self, natural: [f32; 2], target: &Rect) -> Option<(Rect, Rect)> {
+        let placed = self.place(natural, target);
+        let visible = placed.intersect(target);
+        if visible.width < 1. || visible.height < 1. {
+            return None;
+        }
+
+        let source = Rect::new(
+            (visible.x - placed.x) / placed.width,
+            (visible.y - placed.y) / placed.height,
+            visible.width / placed.width,
+            visible.height / placed.height,
+        );
+        Some((visible, source))
+    }
+}
+
+// --- a decoded picture drawn into its content box, shares its pixels with every clone
+#[derive(Clone, Default)]
+pub struct Image {
+    pub base: ElementBase,
+    pub fit: Fit,
+    // --- multiplied with every pixel, e.g. to color a white icon
+    pub tint: Option<Color>,
+    // --- drawn instead while loading, and after failing to load
+    pub placeholder: Option<Texture>,
+    pub fallback: Option<Texture>,
+    texture: Option<Texture>,
+    pending: Option<Task<Result<Bitmap, ImageError>>>,
+    error: Option<ImageError>,
+}
+
+impl Image {
+    pub fn new(texture: Texture) -> Self {
+        Self {
+            texture: Some(texture),
+            ..Default::default()
+        }
+    }
+
+    // --- PNG, JPEG or WebP
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        Ok(Self::new(Texture::decode(bytes)?))
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Ok(Self::new(Texture::open(path)?))
+    }
+
+    // --- read & decoded off the UI thread, drawn from the first frame after it's done
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let mut image = Self::default();
+        image.set_loading(load::open_image(path));
+        image
+    }
+
+    pub fn load_bytes(bytes: Vec<u8>) -> Self {
+        let mut image = Self::default();
+        image.set_loading(load::decode_image(bytes));
+        image
+    }
+
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref()
+    }
+
+    pub fn set_texture(&mut self, texture: Texture) {
+        self.texture = Some(texture);
+        self.pending = None;
+        self.error = None;
+    }
+
+    // --- what's drawn now is kept until the task is done
+    pub fn set_loading(&mut self, task: Task<Result<Bitmap, ImageError>>) {
+        self.pending = Some(task);
+        self.error = None;
+    }
+
+    pub fn status(&self) -> ImageStatus {
+        match (&self.pending, &self.error, &self.texture) {
+            (Some(_), _, _) => ImageStatus::Loading,
+            (_, Some(err), _) => ImageStatus::Failed(err.clone()),
+            (_, _, Some(_)) => ImageStatus::Ready,
+            _ => ImageStatus::Empty,
+        }
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: Texture) {
+        self.placeholder = Some(placeholder);
+    }
+
+    pub fn set_fallback(&mut self, fallback: Texture) {
+        self.fallback = Some(fallback);
+    }
+
+    // --- width & height of the decoded picture, `None` while there's none
+    pub fn natural_size(&self) -> Option<[u32; 2]> {
+        self.texture
+            .as_ref()
+            .map(|texture| [texture.width(), texture.height()])
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = Some(tint);
+    }
+}
+
+deref_base!(Image);
+
+impl IntoElement for Image {
+    fn type_name(&self) -> &'static str {
+        "image"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    // --- picks up the result of a load. nothing to animate while waiting on it,
+    // --- the task asks for a frame once it's done
+    fn step(&mut self, _now: Instant) -> bool {
+        let Some(task) = &self.pending else {
+            return false;
+        };
+
+        match task.get() {
+            None => return false,
+            Some(Ok(bitmap)) => {
+                self.texture = Some(Texture::new(bitmap.clone()));
+                self.error = None;
+            }
+            Some(Err(err)) => {
+                log::warn!("Failed to load image: {}", err);
+                self.error = Some(err.clone());
+            }
+        }
+        self.pending = None;
+        false
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let shown = match self.status() {
+            ImageStatus::Empty => return vec![],
+            ImageStatus::Ready => self.texture.as_ref(),
+            // --- a reload keeps the previous picture up until the new one is there
+            ImageStatus::Loading => self.texture.as_ref().or(self.placeholder.as_ref()),
+            ImageStatus::Failed(_) => self.fallback.as_ref(),
+        };
+
+        let style = &placement.style;
+        let target = placement.layout.content_box;
+        let border = style.border.map(|b| b.width).unwrap_or(0.);
+        let layer_style = Style::new()
+            .with_radius((style.radius.unwrap_or(0.) - border).max(0.))
+            .with_opacity(style.opacity.unwrap_or(1.));
+
+        let Some(texture) = shown else {
+            let color = style.foreground.unwrap_or([0., 0., 0., 1.]);
+            return vec![Layer {
+                rect: target,
+                style: layer_style,
+                fill: Fill::Solid([color[0], color[1], color[2], color[3] * PLACEHOLDER_ALPHA]),
+            }];
+        };
+
+        let natural = [texture.width() as f32, texture.height() as f32];
+        let Some((visible, source)) = self.fit.crop(natural, &target) else {
+            return vec![];
+        };
+
+        vec![Layer {
+            rect: visible,
+            style: layer_style,
+            fill: Fill::Texture {
+                texture: texture.clone(),
+                source,
+                tint: self.tint.unwrap_or([1., 1., 1., 1.]),
+            },
+        }]
+    }
+}
+
+impl IntoView for Image {
+    fn into_view(self) -> View {
+        View::Image(self)
+    }
+}
+
+impl IntoView for &Image {
+    fn into_view(self) -> View {
+        View::Image(self.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::testing::placement;
+
+    // --- twice as wide as it's tall
+    const WIDE: [f32; 2] = [200., 100.];
+
+    #[test]
+    fn place() {
+        let square = Rect::new(0., 0., 100., 100.);
+        assert_eq!(Fit::Fill.place(WIDE, &square), square);
+        assert_eq!(
+            Fit::Contain.place(WIDE, &square),
+            Rect::new(0., 25., 100., 50.)
+        );
+        assert_eq!(
+            Fit::Cover.place(WIDE, &square),
+            Rect::new(-50., 0., 200., 100.)
+        );
+        assert_eq!(
+            Fit::None.place(WIDE, &square),
+            Rect::new(-50., 0., 200., 100.)
+        );
+        assert_eq!(
+            Fit::ScaleDown.place(WIDE, &square),
+            Fit::Contain.place(WIDE, &square)
+        );
+
+        // --- only `ScaleDown` keeps from growing past the natural size
+        let large = Rect::new(0., 0., 400., 400.);
+        assert_eq!(
+            Fit::Contain.place(WIDE, &large),
+            Rect::new(0., 100., 400., 200.)
+        );
+        assert_eq!(
+            Fit::ScaleDown.place(WIDE, &large),
+            Rect::new(100., 150., 200., 100.)
+        );
+
+        // --- nothing to keep the aspect ratio of
+        assert_eq!(Fit::Contain.place([0., 100.], &square), square);
+    }
+
+    #[test]
+    fn crop() {
+        let square = Rect::new(10., 10., 100., 100.);
+
+        let (visible, source) = Fit::Cover.crop(WIDE, &square).unwrap();
+        assert_eq!(visible, square);
+        assert_eq!(source, Rect::new(0.25, 0., 0.5, 1.));
+
+        let (visible, source) = Fit::Contain.crop(WIDE, &square).unwrap();
+        assert_eq!(visible, Rect::new(10., 35., 100., 50.));
+        assert_eq!(source, Rect::new(0., 0., 1., 1.));
+
+        // --- squeezed below a pixel
+        assert_eq!(Fit::Contain.crop([1000., 1.], &square), None);
+    }
+
+    #[test]
+    fn layers() {
+        let bitmap = Bitmap {
+            width: 200,
+            height: 100,
+            rgba: vec![255; 200 * 100 * 4],
+        };
+        let mut image = Image::new(Texture::new(bitmap));
+        image.set_fit(Fit::Cover);
+        image.set_tint([1., 0., 0., 1.]);
+
+        let rect = Rect::new(0., 0., 100., 100.);
+        let layers = image.layers(&placement(rect));
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].rect, rect);
+        match &layers[0].fill {
+            Fill::Texture { source, tint, .. } => {
+                assert_eq!(*source, Rect::new(0.25, 0., 0.5, 1.));
+                assert_eq!(*tint, [1., 0., 0., 1.]);
+            }
+            fill => panic!("expected a texture, got {:?}", fill),
+        }
+
+        // --- nothing to show yet
+        assert!(Image::default().layers(&placement(rect)).is_empty());
+    }
+}
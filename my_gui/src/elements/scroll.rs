@@ -0,0 +1,536 @@
+use super::{ElementBase, Fill, IntoElement, Layer};
+use crate::event::{ActionContext, Event, WheelDelta};
+use crate::geometry::Rect;
+use crate::style::{Edges, Style};
+use crate::text;
+use crate::view::{IntoView, Layout, Placement, View};
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use winit::event::{MouseButton, TouchPhase};
+use winit::keyboard::{Key, NamedKey};
+
+type Callback = Rc<dyn Fn([f32; 2])>;
+
+// --- px, a scrollbar takes this much of the box on each axis that overflows
+const SCROLLBAR_WIDTH: f32 = 10.;
+const THUMB_INSET: f32 = 2.;
+const MIN_THUMB: f32 = 24.;
+const THUMB_ALPHA: f32 = 0.35;
+const THUMB_DRAG_ALPHA: f32 = 0.6;
+
+// --- lines of text a wheel notch or an arrow key moves
+const WHEEL_LINES: f32 = 3.;
+
+// --- share of the momentum left after a second of gliding
+const FRICTION: f32 = 0.05;
+// --- px/s, momentum stops below it
+const MIN_VELOCITY: f32 = 30.;
+// --- a longer pause before the fingers lift means they stopped, so no momentum
+const MOMENTUM_WINDOW: Duration = Duration::from_millis(80);
+
+const HORIZONTAL: usize = 0;
+const VERTICAL: usize = 1;
+
+// --- a box whose children can be larger than it, they're cut to its padding box
+// --- and moved around with the wheel, the scrollbars or the keyboard
+#[derive(Clone, Default)]
+pub struct Scroll {
+    pub base: ElementBase,
+    pub children: Vec<View>,
+    pub on_scroll: Option<Callback>,
+    offset: [f32; 2],
+    // --- largest offset on each axis, as of the last layout
+    max: Cell<[f32; 2]>,
+    // --- px/s of the last touchpad scroll, carried on by `step` once the fingers lift
+    velocity: [f32; 2],
+    last_wheel: Option<Instant>,
+    // --- while gliding, when the last step was taken
+    momentum: Option<Instant>,
+    drag: Option<Drag>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    axis: usize,
+    // --- where the thumb was grabbed, from its start
+    grab: f32,
+}
+
+// --- where everything sits for one layout
+struct Frame {
+    // --- the part of the padding box left to the children
+    viewport: Rect,
+    // --- how much of the content shows at once
+    page: [f32; 2],
+    max: [f32; 2],
+    // --- horizontal then vertical, only on the axes that overflow
+    tracks: [Option<Rect>; 2],
+}
+
+// --- start & length of the rect along the axis
+fn along(rect: &Rect, axis: usize) -> (f32, f32) {
+    match axis {
+        HORIZONTAL => (rect.x, rect.width),
+        _ => (rect.y, rect.height),
+    }
+}
+
+impl Scroll {
+    // --- [x, y] the children are scrolled by
+    pub fn offset(&self) -> [f32; 2] {
+        let max = self.max.get();
+        [self.offset[0].min(max[0]), self.offset[1].min(max[1])]
+    }
+
+    // --- doesn't call `on_scroll`, kept within the content once it's laid out
+    pub fn set_offset(&mut self, offset: [f32; 2]) {
+        self.offset = offset.map(|i| i.max(0.));
+        self.stop();
+    }
+
+    // --- as of the last layout, zero on an axis that doesn't overflow
+    pub fn max_offset(&self) -> [f32; 2] {
+        self.max.get()
+    }
+
+    // --- called with the new offset whenever the user scrolls
+    pub fn on_scroll(&mut self, f: impl Fn([f32; 2]) + 'static) {
+        self.on_scroll = Some(Rc::new(f));
+    }
+
+    pub fn add_child(&mut self, child: impl IntoView) {
+        self.children.push(child.into_view());
+    }
+
+    fn frame(&self, layout: &Layout) -> Frame {
+        let (padding, content) = (layout.padding_box, layout.content_box);
+        let extent = self.children.iter().fold([0f32; 2], |[w, h], child| {
+            let rect = child.element().rect();
+            [w.max(rect.right()), h.max(rect.bottom())]
+        });
+
+        // --- a scrollbar takes room from the other axis, which may then overflow too
+        let mut gutter = [0., 0.];
+        for _ in 0..2 {
+            gutter = [
+                match extent[VERTICAL] > content.height - gutter[VERTICAL] {
+                    true => SCROLLBAR_WIDTH,
+                    false => 0.,
+                },
+                match extent[HORIZONTAL] > content.width - gutter[HORIZONTAL] {
+                    true => SCROLLBAR_WIDTH,
+                    false => 0.,
+                },
+            ];
+        }
+
+        let viewport = Rect::new(
+            padding.x,
+            padding.y,
+            (padding.width - gutter[HORIZONTAL]).max(0.),
+            (padding.height - gutter[VERTICAL]).max(0.),
+        );
+        let page = [
+            (content.width - gutter[HORIZONTAL]).max(0.),
+            (content.height - gutter[VERTICAL]).max(0.),
+        ];
+        let max = [
+            (extent[HORIZONTAL] - page[HORIZONTAL]).max(0.),
+            (extent[VERTICAL] - page[VERTICAL]).max(0.),
+        ];
+
+        let tracks = [
+            (gutter[VERTICAL] > 0.).then(|| {
+                Rect::new(
+                    viewport.x,
+                    viewport.bottom(),
+                    viewport.width,
+                    SCROLLBAR_WIDTH,
+                )
+            }),
+            (gutter[HORIZONTAL] > 0.).then(|| {
+                Rect::new(
+                    viewport.right(),
+                    viewport.y,
+                    SCROLLBAR_WIDTH,
+                    viewport.height,
+                )
+            }),
+        ];
+
+        Frame {
+            viewport,
+            page,
+            max,
+            tracks,
+        }
+    }
+
+    // --- start & length of the thumb along its track
+    fn thumb(&self, frame: &Frame, axis: usize) -> Option<(f32, f32)> {
+        let (start, len) = along(frame.tracks[axis].as_ref()?, axis);
+        let page = frame.page[axis];
+        let max = frame.max[axis];
+
+        let thumb = (len * page / (page + max)).max(MIN_THUMB).min(len);
+        let progress = match max > 0. {
+            true => self.offset[axis].min(max) / max,
+            false => 0.,
+        };
+        Some((start + (len - thumb) * progress, thumb))
+    }
+
+    fn scroll_to(&mut self, offset: [f32; 2]) -> bool {
+        let max = self.max.get();
+        let offset = [offset[0].clamp(0., max[0]), offset[1].clamp(0., max[1])];
+        if offset == self.offset() {
+            return false;
+        }
+
+        self.offset = offset;
+        if let Some(f) = self.on_scroll.as_ref() {
+            f(offset);
+        }
+        true
+    }
+
+    fn scroll_by(&mut self, delta: [f32; 2]) -> bool {
+        let [x, y] = self.offset();
+        self.scroll_to([x + delta[0], y + delta[1]])
+    }
+
+    fn stop(&mut self) {
+        self.velocity = [0., 0.];
+        self.momentum = None;
+    }
+
+    fn wheel(&mut self, delta: &WheelDelta, phase: TouchPhase, line: f32) -> bool {
+        let now = Instant::now();
+        let since = self.last_wheel.map(|last| now - last);
+        self.last_wheel = Some(now);
+        self.momentum = None;
+
+        match (delta, phase) {
+            (WheelDelta::Lines(x, y), _) => {
+                self.stop();
+                self.scroll_by([-x * line, -y * line])
+            }
+            (_, TouchPhase::Started) => {
+                self.stop();
+                false
+            }
+            (WheelDelta::Pixels(x, y), TouchPhase::Moved) => {
+                let delta = [-x, -y];
+                let dt = since.map_or(0., |since| since.as_secs_f32());
+                if dt > 0. {
+                    // --- smoothed, single events are jittery
+                    for axis in [HORIZONTAL, VERTICAL] {
+                        self.velocity[axis] = (self.velocity[axis] + delta[axis] / dt) / 2.;
+                    }
+                }
+                self.scroll_by(delta)
+            }
+            (WheelDelta::Pixels(x, y), _) => {
+                let moved = self.scroll_by([-x, -y]);
+                let fast = self.velocity.iter().any(|v| v.abs() > MIN_VELOCITY);
+                let recent = since.is_some_and(|since| since < MOMENTUM_WINDOW);
+
+                if phase == TouchPhase::Ended && fast && recent {
+                    self.momentum = Some(now);
+                    true
+                } else {
+                    self.stop();
+                    moved
+                }
+            }
+        }
+    }
+
+    fn key_down(&mut self, key: &Key, frame: &Frame, line: f32) -> bool {
+        let [x, _] = self.offset();
+        // --- a page keeps a line of what was showing
+        let page = (frame.page[VERTICAL] - line).max(line);
+
+        match key {
+            Key::Named(NamedKey::ArrowUp) => self.scroll_by([0., -line]),
+            Key::Named(NamedKey::ArrowDown) => self.scroll_by([0., line]),
+            Key::Named(NamedKey::ArrowLeft) => self.scroll_by([-line, 0.]),
+            Key::Named(NamedKey::ArrowRight) => self.scroll_by([line, 0.]),
+            Key::Named(NamedKey::PageUp) => self.scroll_by([0., -page]),
+            Key::Named(NamedKey::PageDown) => self.scroll_by([0., page]),
+            Key::Named(NamedKey::Home) => self.scroll_to([x, 0.]),
+            Key::Named(NamedKey::End) => self.scroll_to([x, frame.max[VERTICAL]]),
+            _ => false,
+        }
+    }
+
+    fn pointer_down(&mut self, x: f32, y: f32, frame: &Frame) -> bool {
+        for axis in [HORIZONTAL, VERTICAL] {
+            if !frame.tracks[axis].is_some_and(|track| track.contains(x, y)) {
+                continue;
+            }
+            let Some((start, len)) = self.thumb(frame, axis) else {
+                continue;
+            };
+
+            let pos = [x, y][axis];
+            if (start..start + len).contains(&pos) {
+                self.drag = Some(Drag {
+                    axis,
+                    grab: pos - start,
+                });
+            } else {
+                // --- a click on the track pages towards it
+                let mut delta = [0., 0.];
+                delta[axis] = match pos < start {
+                    true => -frame.page[axis],
+                    false => frame.page[axis],
+                };
+                self.scroll_by(delta);
+            }
+            return true;
+        }
+
+        false
+    }
+
+    fn drag_to(&mut self, x: f32, y: f32, frame: &Frame) -> bool {
+        let Some(drag) = self.drag else {
+            return false;
+        };
+        let Some(track) = frame.tracks[drag.axis].as_ref() else {
+            return false;
+        };
+        let Some((_, thumb)) = self.thumb(frame, drag.axis) else {
+            return false;
+        };
+
+        let (start, len) = along(track, drag.axis);
+        let room = (len - thumb).max(1.);
+        let progress = ([x, y][drag.axis] - drag.grab - start) / room;
+
+        let mut offset = self.offset();
+        offset[drag.axis] = progress * frame.max[drag.axis];
+        self.scroll_to(offset);
+        true
+    }
+}
+
+deref_base!(Scroll);
+
+impl IntoElement for Scroll {
+    fn type_name(&self) -> &'static str {
+        "scroll"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    // --- so the keyboard can scroll it
+    fn focusable(&self) -> bool {
+        !self.base.state.disabled
+    }
+
+    fn scroll_offset(&self) -> [f32; 2] {
+        self.offset()
+    }
+
+    // --- keeps the largest offset up to date, this runs on every layout
+    fn clip(&self, layout: &Layout) -> Option<Rect> {
+        let frame = self.frame(layout);
+        self.max.set(frame.max);
+        Some(frame.viewport)
+    }
+
+    fn step(&mut self, now: Instant) -> bool {
+        let Some(last) = self.momentum else {
+            return false;
+        };
+
+        let dt = (now - last).as_secs_f32();
+        let decay = FRICTION.powf(dt);
+        let delta = self.velocity.map(|v| v * dt);
+        self.velocity = self.velocity.map(|v| v * decay);
+        self.momentum = Some(now);
+
+        let moved = self.scroll_by(delta);
+        if (!moved && dt > 0.) || self.velocity.iter().all(|v| v.abs() < MIN_VELOCITY) {
+            self.stop();
+            return false;
+        }
+        true
+    }
+
+    fn default_action(&mut self, event: &Event, cx: &ActionContext) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+
+        let frame = self.frame(&cx.placement.layout);
+        self.max.set(frame.max);
+        let font = cx.placement.style.font.clone().unwrap_or_default();
+        let line = text::line_height(&font) * WHEEL_LINES;
+
+        // --- at an end it's not handled, so an outer scroll can take over
+        match event {
+            Event::Wheel { delta, phase, .. } => self.wheel(delta, *phase, line),
+            Event::PointerDown {
+                x,
+                y,
+                button: MouseButton::Left,
+            } => {
+                self.stop();
+                self.pointer_down(*x, *y, &frame)
+            }
+            Event::PointerMove { x, y } => self.drag_to(*x, *y, &frame),
+            Event::PointerUp {
+                button: MouseButton::Left,
+                ..
+            } => self.drag.take().is_some(),
+            Event::KeyDown { key, .. } => self.key_down(key, &frame, line),
+            _ => false,
+        }
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let frame = self.frame(&placement.layout);
+        let style = &placement.style;
+        let color = style.foreground.unwrap_or([0., 0., 0., 1.]);
+
+        [HORIZONTAL, VERTICAL]
+            .into_iter()
+            .filter_map(|axis| {
+                let track = frame.tracks[axis]?;
+                let (start, len) = self.thumb(&frame, axis)?;
+                let rect = match axis {
+                    HORIZONTAL => Rect::new(start, track.y, len, track.height),
+                    _ => Rect::new(track.x, start, track.width, len),
+                };
+                let alpha = match self.drag.is_some_and(|drag| drag.axis == axis) {
+                    true => THUMB_DRAG_ALPHA,
+                    false => THUMB_ALPHA,
+                };
+
+                Some(Layer {
+                    rect: rect.inset(Edges::all(THUMB_INSET)),
+                    style: Style::new()
+                        .with_radius(SCROLLBAR_WIDTH / 2. - THUMB_INSET)
+                        .with_opacity(style.opacity.unwrap_or(1.)),
+                    fill: Fill::Solid([color[0], color[1], color[2], color[3] * alpha]),
+                })
+            })
+            .collect()
+    }
+
+    fn children(&self) -> &[View] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [View] {
+        &mut self.children
+    }
+}
+
+impl IntoView for Scroll {
+    fn into_view(self) -> View {
+        View::Scroll(self)
+    }
+}
+
+impl IntoView for &Scroll {
+    fn into_view(self) -> View {
+        View::Scroll(self.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::Clipboard;
+    use crate::elements::container;
+    use crate::elements::testing::placement;
+
+    use std::cell::RefCell;
+
+    // --- 200x200, holding a 300x500 child
+    fn scroll() -> (Scroll, Placement) {
+        let mut child = container();
+        child.set_position([0, 0, 500, 300]);
+
+        let mut scroll = Scroll::default();
+        scroll.set_position([0, 0, 200, 200]);
+        scroll.add_child(child);
+        let placement = placement(scroll.rect());
+        (scroll, placement)
+    }
+
+    fn act(scroll: &mut Scroll, placement: &Placement, event: Event) -> bool {
+        let clipboard = Clipboard::memory();
+        let cx = ActionContext {
+            placement,
+            clipboard: &clipboard,
+        };
+        scroll.default_action(&event, &cx)
+    }
+
+    fn key(key: NamedKey) -> Event {
+        Event::KeyDown {
+            key: Key::Named(key),
+            text: None,
+            repeat: false,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn offset_is_clamped() {
+        let (mut scroll, placement) = scroll();
+
+        // --- nothing to scroll before the first layout
+        scroll.set_offset([50., 50.]);
+        assert_eq!(scroll.offset(), [0., 0.]);
+
+        // --- each scrollbar takes room from the other axis
+        scroll.clip(&placement.layout);
+        let max = [300. - 200. + SCROLLBAR_WIDTH, 500. - 200. + SCROLLBAR_WIDTH];
+        assert_eq!(scroll.max_offset(), max);
+        assert_eq!(scroll.offset(), [50., 50.]);
+
+        scroll.set_offset([1000., -20.]);
+        assert_eq!(scroll.offset(), [max[0], 0.]);
+    }
+
+    #[test]
+    fn stops_at_the_ends() {
+        let (mut scroll, placement) = scroll();
+        let seen = Rc::new(RefCell::new(vec![]));
+        let log = seen.clone();
+        scroll.on_scroll(move |offset| log.borrow_mut().push(offset));
+        let max = 500. - 200. + SCROLLBAR_WIDTH;
+
+        assert!(act(&mut scroll, &placement, key(NamedKey::End)));
+        assert_eq!(scroll.offset(), [0., max]);
+        // --- not handled at the end, so an outer scroll can take over
+        assert!(!act(&mut scroll, &placement, key(NamedKey::ArrowDown)));
+
+        let wheel = Event::Wheel {
+            x: 0.,
+            y: 0.,
+            delta: WheelDelta::Lines(0., 100.),
+            phase: TouchPhase::Moved,
+        };
+        assert!(act(&mut scroll, &placement, wheel));
+        assert_eq!(scroll.offset(), [0., 0.]);
+        assert!(!act(&mut scroll, &placement, key(NamedKey::ArrowUp)));
+
+        assert_eq!(*seen.borrow(), [[0., max], [0., 0.]]);
+    }
+}
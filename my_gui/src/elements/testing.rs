@@ -0,0 +1,34 @@
+use super::IntoElement;
+use crate::clipboard::Clipboard;
+use crate::event::{ActionContext, Event};
+use crate::geometry::Rect;
+use crate::style::Style;
+use crate::view::{Layout, Placement};
+
+use winit::keyboard::{Key, ModifiersState};
+
+// --- laid out at `rect` without padding or border, untransformed
+pub(super) fn placement(rect: Rect) -> Placement {
+    let style = Style::new();
+    Placement {
+        layout: Layout::new(rect, &style),
+        style,
+        transform: Default::default(),
+    }
+}
+
+// --- Ctrl + `ch`, through the default action like a real key press
+pub(super) fn command(element: &mut impl IntoElement, ch: &str, clipboard: &Clipboard) -> bool {
+    let event = Event::KeyDown {
+        key: Key::Character(ch.into()),
+        text: None,
+        repeat: false,
+        modifiers: ModifiersState::CONTROL,
+    };
+    let placement = placement(element.rect());
+    let cx = ActionContext {
+        placement: &placement,
+        clipboard,
+    };
+    element.default_action(&event, &cx)
+}
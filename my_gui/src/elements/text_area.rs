@@ -0,0 +1,990 @@
+use super::{Composition, ElementBase, Fill, IntoElement, Layer};
+use crate::animation::{Easing, Property, Transition};
+use crate::clipboard::{Clipboard, ClipboardError};
+use crate::event::{ActionContext, Event, Ime, WheelDelta};
+use crate::geometry::Rect;
+use crate::rope::Rope;
+use crate::style::{Edges, Font, Style, Styles};
+use crate::text::{self, TextCache};
+use crate::theme::Theme;
+use crate::view::{IntoView, Placement, View};
+
+use std::cell::{Ref, RefCell};
+use std::ops::Range;
+use std::rc::Rc;
+use std::time::Duration;
+
+use winit::event::MouseButton;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::CursorIcon;
+
+const UNDO_LIMIT: usize = 100;
+const CARET_WIDTH: f32 = 1.;
+const SELECTION_ALPHA: f32 = 0.3;
+const PLACEHOLDER_ALPHA: f32 = 0.5;
+const UNDERLINE_WIDTH: f32 = 1.;
+// --- rows scrolled per wheel notch
+const WHEEL_ROWS: f32 = 3.;
+
+type Callback = Rc<dyn Fn(&Rope)>;
+
+// --- one change to the text, undone by putting `removed` back in place of `inserted`
+#[derive(Debug, Clone)]
+struct Edit {
+    at: usize,
+    removed: String,
+    inserted: String,
+    // --- before the change
+    caret: usize,
+    anchor: usize,
+}
+
+// --- how many rows every line takes once wrapped at `cols`, empty when it needs a rebuild
+#[derive(Debug, Clone, Default)]
+struct Wrap {
+    cols: usize,
+    rows: Vec<usize>,
+}
+
+impl Wrap {
+    fn total(&self) -> usize {
+        self.rows.iter().sum()
+    }
+
+    fn row_of_line(&self, line: usize) -> usize {
+        self.rows[..line.min(self.rows.len())].iter().sum()
+    }
+
+    // --- the line a row belongs to, and which of the line's rows it is
+    fn line_of_row(&self, row: usize) -> (usize, usize) {
+        let mut first = 0;
+        for (line, rows) in self.rows.iter().enumerate() {
+            if row < first + rows {
+                return (line, row - first);
+            }
+            first += rows;
+        }
+
+        let last = self.rows.len() - 1;
+        (last, self.rows[last] - 1)
+    }
+}
+
+// --- where the rows of a line start, breaking after the last whitespace that fits
+// --- or mid-word when the word alone is longer than a row
+fn wrap_line(line: &str, cols: usize) -> Vec<usize> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut starts = vec![0];
+    let mut start = 0;
+
+    while chars.len() - start > cols {
+        let end = start + cols;
+        let at = (start + 1..=end)
+            .rev()
+            .find(|idx| chars[idx - 1].is_whitespace())
+            .unwrap_or(end);
+        starts.push(at);
+        start = at;
+    }
+
+    starts
+}
+
+// --- sizes everything is laid out with, taken from the placement
+struct Metrics {
+    font: Font,
+    advance: f32,
+    line_height: f32,
+    left: f32,
+    top: f32,
+    width: f32,
+    // --- rows that fit in the content box
+    page: usize,
+    cols: usize,
+}
+
+// --- multi-line editable text, positions (caret, selection) count chars
+#[derive(Clone)]
+pub struct TextArea {
+    pub base: ElementBase,
+    pub placeholder: String,
+    // --- wraps long lines at word boundaries, otherwise they scroll horizontally
+    pub wrap: bool,
+    pub on_change: Option<Callback>,
+    buffer: Rope,
+    caret: usize,
+    anchor: usize,
+    // --- the column kept while moving up & down through shorter rows
+    goal: Option<usize>,
+    // --- first visible row
+    scroll: usize,
+    // --- pixels scrolled out on the left when not wrapping
+    scroll_x: f32,
+    // --- pixel wheel deltas that don't make up a whole row yet
+    wheel: f32,
+    dragging: bool,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    typing: bool,
+    // --- bumped on every change of the text
+    revision: u64,
+    wrapped: RefCell<Wrap>,
+    composition: Option<Composition>,
+    rendered: TextCache,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        let position = [10, 10, 170, 310];
+
+        Self {
+            base: ElementBase::at(position),
+            placeholder: String::new(),
+            wrap: true,
+            on_change: None,
+            buffer: Rope::new(),
+            caret: 0,
+            anchor: 0,
+            goal: None,
+            scroll: 0,
+            scroll_x: 0.,
+            wheel: 0.,
+            dragging: false,
+            undo: vec![],
+            redo: vec![],
+            typing: false,
+            revision: 0,
+            wrapped: RefCell::new(Wrap::default()),
+            composition: None,
+            rendered: TextCache::default(),
+        }
+    }
+}
+
+impl TextArea {
+    pub fn text(&self) -> &Rope {
+        &self.buffer
+    }
+
+    pub fn value(&self) -> String {
+        self.buffer.to_string()
+    }
+
+    // --- replaces the text without going through undo or `on_change`
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.buffer = Rope::from(value.into());
+        self.caret = self.caret.min(self.buffer.len_chars());
+        self.anchor = self.anchor.min(self.buffer.len_chars());
+        self.undo.clear();
+        self.redo.clear();
+        self.typing = false;
+        self.goal = None;
+        self.revision += 1;
+        self.wrapped.get_mut().rows.clear();
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: impl Into<String>) {
+        self.placeholder = placeholder.into();
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.scroll_x = 0.;
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    // --- the caret ends up at `range.end`
+    pub fn select(&mut self, range: Range<usize>) {
+        self.anchor = range.start.min(self.buffer.len_chars());
+        self.caret = range.end.min(self.buffer.len_chars());
+        self.typing = false;
+        self.goal = None;
+    }
+
+    pub fn on_change(&mut self, f: impl Fn(&Rope) + 'static) {
+        self.on_change = Some(Rc::new(f));
+    }
+
+    fn metrics(&self, placement: &Placement) -> Metrics {
+        let font = placement.style.font.clone().unwrap_or_default();
+        let content = placement.layout.content_box;
+        let advance = text::advance(&font);
+        let line_height = text::line_height(&font);
+
+        let cols = match self.wrap {
+            true => ((content.width - CARET_WIDTH) / advance).floor().max(1.) as usize,
+            false => usize::MAX,
+        };
+
+        Metrics {
+            advance,
+            line_height,
+            left: content.x.round(),
+            top: content.y.round(),
+            width: content.width.floor().max(0.),
+            page: (content.height / line_height).floor().max(1.) as usize,
+            cols,
+            font,
+        }
+    }
+
+    // --- rebuilt from scratch only when the width or the whole text changed,
+    // --- edits keep it up to date line by line
+    fn wrapped(&self, cols: usize) -> Ref<'_, Wrap> {
+        let stale = {
+            let wrapped = self.wrapped.borrow();
+            wrapped.cols != cols || wrapped.rows.len() != self.buffer.len_lines()
+        };
+
+        // --- only borrowed mutably when stale, callers may be holding on to it otherwise
+        if stale {
+            let mut wrapped = self.wrapped.borrow_mut();
+            wrapped.cols = cols;
+            wrapped.rows = self
+                .buffer
+                .to_string()
+                .split('\n')
+                .map(|line| wrap_line(line, cols).len())
+                .collect();
+        }
+        self.wrapped.borrow()
+    }
+
+    // --- every change of the text goes through here
+    fn splice(&mut self, range: Range<usize>, text: &str) {
+        let lines = self.buffer.len_lines();
+        let first = self.buffer.char_to_line(range.start);
+        let last = self.buffer.char_to_line(range.end);
+
+        self.buffer.remove(range.clone());
+        self.buffer.insert(range.start, text);
+        self.revision += 1;
+
+        let end = self.buffer.char_to_line(range.start + text.chars().count());
+        let wrapped = self.wrapped.get_mut();
+        if wrapped.rows.len() == lines {
+            let rows = (first..=end)
+                .map(|line| wrap_line(&self.buffer.line(line), wrapped.cols).len())
+                .collect::<Vec<_>>();
+            wrapped.rows.splice(first..=last, rows);
+        } else {
+            wrapped.rows.clear();
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(edit) = self.undo.pop() {
+            let inserted = edit.inserted.chars().count();
+            self.splice(edit.at..edit.at + inserted, &edit.removed);
+            self.caret = edit.caret;
+            self.anchor = edit.anchor;
+            self.redo.push(edit);
+            self.typing = false;
+            self.goal = None;
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(edit) = self.redo.pop() {
+            let removed = edit.removed.chars().count();
+            self.splice(edit.at..edit.at + removed, &edit.inserted);
+            self.caret = edit.at + edit.inserted.chars().count();
+            self.anchor = self.caret;
+            self.undo.push(edit);
+            self.typing = false;
+            self.goal = None;
+        }
+    }
+
+    fn move_caret(&mut self, to: usize, extend: bool) {
+        self.caret = to.min(self.buffer.len_chars());
+        if !extend {
+            self.anchor = self.caret;
+        }
+        self.typing = false;
+        self.goal = None;
+    }
+
+    // --- replaces the selection with `text`
+    fn replace(&mut self, text: &str, typing: bool) {
+        let selection = self.selection();
+        if selection.is_empty() && text.is_empty() {
+            return;
+        }
+
+        let typed_on = typing && self.typing && selection.is_empty();
+        let last = self.undo.last_mut().filter(|edit| {
+            typed_on
+                && edit.removed.is_empty()
+                && edit.at + edit.inserted.chars().count() == selection.start
+        });
+
+        match last {
+            Some(edit) => edit.inserted.push_str(text),
+            None => {
+                self.undo.push(Edit {
+                    at: selection.start,
+                    removed: self.buffer.slice(selection.clone()),
+                    inserted: text.to_string(),
+                    caret: self.caret,
+                    anchor: self.anchor,
+                });
+                if self.undo.len() > UNDO_LIMIT {
+                    self.undo.remove(0);
+                }
+            }
+        }
+        self.redo.clear();
+        self.typing = typing;
+
+        self.splice(selection.clone(), text);
+        self.caret = selection.start + text.chars().count();
+        self.anchor = self.caret;
+        self.goal = None;
+    }
+
+    fn copy(&self, clipboard: &Clipboard) {
+        let selection = self.selection();
+        if selection.is_empty() {
+            return;
+        }
+
+        if let Err(err) = clipboard.set_text(&self.buffer.slice(selection)) {
+            log::warn!("Failed to copy: {}", err);
+        }
+    }
+
+    fn paste(&mut self, clipboard: &Clipboard) {
+        match clipboard.text() {
+            Ok(text) => self.replace(&text.replace("\r\n", "\n"), false),
+            Err(ClipboardError::Empty) => {}
+            Err(err) => log::warn!("Failed to paste: {}", err),
+        }
+    }
+
+    fn word_left(&self) -> usize {
+        let is_space = |idx: usize| self.buffer.char(idx).is_some_and(char::is_whitespace);
+        let mut idx = self.caret;
+        while idx > 0 && is_space(idx - 1) {
+            idx -= 1;
+        }
+        while idx > 0 && !is_space(idx - 1) {
+            idx -= 1;
+        }
+        idx
+    }
+
+    fn word_right(&self) -> usize {
+        let len = self.buffer.len_chars();
+        let is_space = |idx: usize| self.buffer.char(idx).is_some_and(char::is_whitespace);
+        let mut idx = self.caret;
+        while idx < len && is_space(idx) {
+            idx += 1;
+        }
+        while idx < len && !is_space(idx) {
+            idx += 1;
+        }
+        idx
+    }
+
+    // --- (row, column) of a char position once wrapped
+    fn visual(&self, idx: usize, wrapped: &Wrap) -> (usize, usize) {
+        let line = self.buffer.char_to_line(idx);
+        let range = self.buffer.line_range(line);
+        let starts = wrap_line(&self.buffer.slice(range.clone()), wrapped.cols);
+
+        let offset = idx - range.start;
+        let row = starts
+            .iter()
+            .rposition(|start| *start <= offset)
+            .unwrap_or(0);
+        (wrapped.row_of_line(line) + row, offset - starts[row])
+    }
+
+    // --- char position at (row, column), clamped to the row
+    fn index_at_visual(&self, row: usize, col: usize, wrapped: &Wrap) -> usize {
+        let (line, row) = wrapped.line_of_row(row);
+        let range = self.buffer.line_range(line);
+        let starts = wrap_line(&self.buffer.slice(range.clone()), wrapped.cols);
+
+        // --- the caret can't sit after the last char of a wrapped row, that's the next row's start
+        let end = starts.get(row + 1).map_or(range.len(), |next| next - 1);
+        range.start + starts[row].saturating_add(col).min(end)
+    }
+
+    // --- the position `delta` rows up or down, and the column to keep going with
+    fn vertical(&self, delta: isize, cols: usize) -> (usize, usize) {
+        let wrapped = self.wrapped(cols);
+        let (row, col) = self.visual(self.caret, &wrapped);
+        let goal = self.goal.unwrap_or(col);
+        let row = row.saturating_add_signed(delta).min(wrapped.total() - 1);
+
+        (self.index_at_visual(row, goal, &wrapped), goal)
+    }
+
+    // --- start or end of the caret's row
+    fn row_edge(&self, end: bool, cols: usize) -> usize {
+        let wrapped = self.wrapped(cols);
+        let (row, _) = self.visual(self.caret, &wrapped);
+        self.index_at_visual(row, if end { usize::MAX } else { 0 }, &wrapped)
+    }
+
+    fn key_down(
+        &mut self,
+        key: &Key,
+        text: Option<&str>,
+        modifiers: ModifiersState,
+        clipboard: &Clipboard,
+        metrics: &Metrics,
+    ) -> bool {
+        // --- keys belong to the input method while composing
+        if self.composition.is_some() {
+            return true;
+        }
+
+        let command = modifiers.control_key() || modifiers.super_key();
+        let shift = modifiers.shift_key();
+        let selection = self.selection();
+        let len = self.buffer.len_chars();
+
+        match key {
+            Key::Named(NamedKey::ArrowLeft) => {
+                let to = match (command, shift || selection.is_empty()) {
+                    (true, _) => self.word_left(),
+                    (false, true) => self.caret.saturating_sub(1),
+                    (false, false) => selection.start,
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                let to = match (command, shift || selection.is_empty()) {
+                    (true, _) => self.word_right(),
+                    (false, true) => self.caret + 1,
+                    (false, false) => selection.end,
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(
+                named @ (NamedKey::ArrowUp
+                | NamedKey::ArrowDown
+                | NamedKey::PageUp
+                | NamedKey::PageDown),
+            ) => {
+                let page = metrics.page as isize;
+                let delta = match named {
+                    NamedKey::ArrowUp => -1,
+                    NamedKey::ArrowDown => 1,
+                    NamedKey::PageUp => -page,
+                    _ => page,
+                };
+                if delta.abs() > 1 {
+                    self.scroll = self.scroll.saturating_add_signed(delta);
+                }
+
+                let (to, goal) = self.vertical(delta, metrics.cols);
+                self.move_caret(to, shift);
+                self.goal = Some(goal);
+            }
+            Key::Named(NamedKey::Home) => {
+                let to = match command {
+                    true => 0,
+                    false => self.row_edge(false, metrics.cols),
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(NamedKey::End) => {
+                let to = match command {
+                    true => len,
+                    false => self.row_edge(true, metrics.cols),
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(NamedKey::Backspace) => {
+                if selection.is_empty() {
+                    self.anchor = match command {
+                        true => self.word_left(),
+                        false => self.caret.saturating_sub(1),
+                    };
+                }
+                self.replace("", false);
+            }
+            Key::Named(NamedKey::Delete) => {
+                if selection.is_empty() {
+                    self.anchor = match command {
+                        true => self.word_right(),
+                        false => (self.caret + 1).min(len),
+                    };
+                }
+                self.replace("", false);
+            }
+            Key::Named(NamedKey::Enter) => self.replace("\n", false),
+            Key::Character(ch) if command => match ch.to_lowercase().as_str() {
+                "a" => self.select(0..len),
+                "c" => self.copy(clipboard),
+                "x" => {
+                    self.copy(clipboard);
+                    self.replace("", false);
+                }
+                "v" => self.paste(clipboard),
+                "z" if shift => self.redo(),
+                "z" => self.undo(),
+                "y" => self.redo(),
+                _ => return false,
+            },
+            _ => match text.filter(|text| !text.chars().any(char::is_control)) {
+                Some(text) if !command => self.replace(text, true),
+                _ => return false,
+            },
+        }
+
+        true
+    }
+
+    fn ime(&mut self, ime: &Ime) -> bool {
+        match ime {
+            Ime::Preedit { text, cursor } => {
+                // --- composing replaces the selection, like typing does
+                if self.composition.is_none() && !text.is_empty() {
+                    self.replace("", false);
+                }
+                self.composition = (!text.is_empty()).then(|| Composition {
+                    text: text.clone(),
+                    cursor: cursor.clone(),
+                });
+            }
+            Ime::Commit(text) => {
+                self.composition = None;
+                self.replace(&text.replace("\r\n", "\n"), false);
+            }
+            Ime::Disabled => self.composition = None,
+            Ime::Enabled => return false,
+        }
+
+        true
+    }
+
+    // --- (row, column) of the caret as drawn, moved along the text being composed
+    fn display_caret(&self, wrapped: &Wrap) -> (usize, usize) {
+        let (row, col) = self.visual(self.caret, wrapped);
+        (
+            row,
+            col + self.composition.as_ref().map_or(0, Composition::caret),
+        )
+    }
+
+    // --- kept inside the content box when the caret's row is scrolled out
+    fn caret_rect(&self, metrics: &Metrics) -> Rect {
+        let (row, col) = self.display_caret(&self.wrapped(metrics.cols));
+        let visible = row.saturating_sub(self.scroll).min(metrics.page - 1);
+        let x = (col as f32 * metrics.advance - self.scroll_x)
+            .clamp(0., (metrics.width - CARET_WIDTH).max(0.))
+            .round();
+
+        Rect::new(
+            metrics.left + x,
+            metrics.top + visible as f32 * metrics.line_height,
+            CARET_WIDTH,
+            metrics.line_height,
+        )
+    }
+
+    // --- char position under (x, y) in window coordinates
+    fn index_at(&self, x: f32, y: f32, metrics: &Metrics) -> usize {
+        let row = ((y - metrics.top) / metrics.line_height).floor();
+        let row = (self.scroll as f32 + row).max(0.) as usize;
+        let col = ((x - metrics.left + self.scroll_x) / metrics.advance)
+            .round()
+            .max(0.) as usize;
+
+        let wrapped = self.wrapped(metrics.cols);
+        self.index_at_visual(row.min(wrapped.total() - 1), col, &wrapped)
+    }
+
+    fn max_scroll(&self, metrics: &Metrics) -> usize {
+        self.wrapped(metrics.cols)
+            .total()
+            .saturating_sub(metrics.page)
+    }
+
+    // --- scrolls just enough for the caret's row (and column, when not wrapping) to show
+    fn scroll_to_caret(&mut self, metrics: &Metrics) {
+        let (row, col) = self.display_caret(&self.wrapped(metrics.cols));
+
+        if row < self.scroll {
+            self.scroll = row;
+        }
+        if row >= self.scroll + metrics.page {
+            self.scroll = row + 1 - metrics.page;
+        }
+        self.scroll = self.scroll.min(self.max_scroll(metrics));
+
+        if !self.wrap {
+            let width = (metrics.width - CARET_WIDTH).max(0.);
+            let caret = col as f32 * metrics.advance;
+            if caret - self.scroll_x > width {
+                self.scroll_x = caret - width;
+            }
+            if caret < self.scroll_x {
+                self.scroll_x = caret;
+            }
+        }
+    }
+}
+
+deref_base!(TextArea);
+
+impl IntoElement for TextArea {
+    fn type_name(&self) -> &'static str {
+        "textarea"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    fn default_styles(&self, theme: &Theme) -> Styles {
+        let palette = &theme.palette;
+
+        Styles::from(
+            Style::new()
+                .with_background(palette.surface)
+                .with_foreground(palette.text)
+                .with_border(1., palette.border)
+                .with_radius(theme.radii.sm)
+                .with_padding(Edges::all(theme.spacing.sm))
+                .with_font(theme.typography.font(theme.typography.body))
+                .with_cursor(CursorIcon::Text)
+                .with_transition(
+                    Transition::new(Some(Property::BorderColor), Duration::from_millis(120))
+                        .with_easing(Easing::EaseOut),
+                ),
+        )
+        .with_focus(Style::new().with_border(1., palette.focus))
+        .with_disabled(
+            Style::new()
+                .with_opacity(0.5)
+                .with_cursor(CursorIcon::NotAllowed),
+        )
+    }
+
+    fn focusable(&self) -> bool {
+        !self.base.state.disabled
+    }
+
+    fn default_action(&mut self, event: &Event, cx: &ActionContext) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+
+        let metrics = self.metrics(cx.placement);
+        let revision = self.revision;
+
+        // --- whether it was handled, and whether the view should follow the caret
+        let (handled, follow) = match event {
+            Event::PointerDown {
+                x,
+                y,
+                button: MouseButton::Left,
+            } => {
+                let idx = self.index_at(*x, *y, &metrics);
+                self.move_caret(idx, false);
+                self.dragging = true;
+                (true, true)
+            }
+            Event::PointerMove { x, y } if self.dragging => {
+                let idx = self.index_at(*x, *y, &metrics);
+                self.move_caret(idx, true);
+                (true, true)
+            }
+            Event::PointerUp {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.dragging = false;
+                (true, false)
+            }
+            Event::Wheel { delta, .. } => {
+                let rows = match delta {
+                    WheelDelta::Lines(_, y) => -y * WHEEL_ROWS,
+                    WheelDelta::Pixels(_, y) => -y / metrics.line_height,
+                };
+                self.wheel += rows;
+                let whole = self.wheel.trunc();
+                self.wheel -= whole;
+
+                // --- not handled at the ends, so an ancestor can scroll instead
+                let before = self.scroll;
+                let max = self.max_scroll(&metrics) as f32;
+                self.scroll = (self.scroll as f32 + whole).clamp(0., max) as usize;
+                (self.scroll != before, false)
+            }
+            Event::KeyDown {
+                key,
+                text,
+                modifiers,
+                ..
+            } => {
+                let handled =
+                    self.key_down(key, text.as_deref(), *modifiers, cx.clipboard, &metrics);
+                (handled, handled)
+            }
+            Event::Ime(ime) => {
+                let handled = self.ime(ime);
+                (handled, handled)
+            }
+            _ => (false, false),
+        };
+
+        if follow {
+            self.scroll_to_caret(&metrics);
+        }
+        if self.revision != revision {
+            if let Some(f) = self.on_change.as_ref() {
+                f(&self.buffer);
+            }
+        }
+
+        handled
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let metrics = self.metrics(placement);
+        let style = &placement.style;
+        let color = style.foreground.unwrap_or([0., 0., 0., 1.]);
+        let opacity = Style::new().with_opacity(style.opacity.unwrap_or(1.));
+        let Metrics {
+            font,
+            advance,
+            line_height,
+            left,
+            top,
+            width,
+            ..
+        } = &metrics;
+
+        if *width < 1. {
+            return vec![];
+        }
+
+        // --- with a gradient the text is drawn in white, only to mask it.
+        // --- every line is a layer of its own, and gets the whole gradient
+        let (ink, text_style) = match style.text_gradient.clone() {
+            Some(gradient) => (
+                [1., 1., 1., 1.],
+                opacity.clone().with_background_gradient(gradient),
+            ),
+            None => (color, opacity.clone()),
+        };
+        let text_layer = |y: f32, text: &str, color| Layer {
+            rect: Rect::new(*left, y, *width, *line_height),
+            style: text_style.clone(),
+            fill: Fill::texture(self.rendered.texture(
+                text,
+                font,
+                color,
+                self.scroll_x,
+                *width as u32,
+            )),
+        };
+
+        // --- selections go below the text, the caret above it
+        let mut highlights = vec![];
+        let mut texts = vec![];
+        let mut carets = vec![];
+
+        if self.buffer.is_empty() && self.composition.is_none() {
+            let muted = [ink[0], ink[1], ink[2], ink[3] * PLACEHOLDER_ALPHA];
+            for (idx, line) in self.placeholder.lines().take(metrics.page).enumerate() {
+                texts.push(text_layer(top + idx as f32 * line_height, line, muted));
+            }
+        }
+
+        let wrapped = self.wrapped(metrics.cols);
+        let selection = self.selection();
+        let caret = self.visual(self.caret, &wrapped);
+        let x_of = |col: usize| (col as f32 * advance - self.scroll_x).clamp(0., *width);
+
+        let (mut line, mut row) = wrapped.line_of_row(self.scroll);
+        let (mut range, mut chars, mut starts) = (0..0, vec![], vec![]);
+        let mut loaded = None;
+
+        for visible in 0..metrics.page {
+            if line >= self.buffer.len_lines() {
+                break;
+            }
+
+            // --- a line's text & wrap points are shared by all of its rows
+            if loaded != Some(line) {
+                range = self.buffer.line_range(line);
+                chars = self.buffer.slice(range.clone()).chars().collect::<Vec<_>>();
+                starts = wrap_line(&chars.iter().collect::<String>(), wrapped.cols);
+                loaded = Some(line);
+            }
+
+            let start = starts[row];
+            let end = starts.get(row + 1).copied().unwrap_or(chars.len());
+            let y = top + visible as f32 * line_height;
+            let last_row = row + 1 == starts.len();
+
+            if self.base.state.focused && !selection.is_empty() {
+                let (from, to) = (
+                    selection.start.max(range.start + start),
+                    selection.end.min(range.start + end),
+                );
+                // --- a selected line break shows as a bit of extra highlight
+                let newline = last_row
+                    && line + 1 < self.buffer.len_lines()
+                    && selection.contains(&range.end);
+
+                if from < to || newline {
+                    let x0 = x_of(from.max(range.start + start) - range.start - start);
+                    let x1 = x_of(to.max(from) - range.start - start)
+                        + if newline { advance / 2. } else { 0. };
+                    highlights.push(Layer {
+                        rect: Rect::new(left + x0, y, (x1.min(*width) - x0).max(0.), *line_height),
+                        style: opacity.clone(),
+                        fill: Fill::Solid([
+                            color[0],
+                            color[1],
+                            color[2],
+                            color[3] * SELECTION_ALPHA,
+                        ]),
+                    });
+                }
+            }
+
+            let mut text = chars[start..end].iter().collect::<String>();
+            let caret_row = caret.0 == self.scroll + visible;
+
+            // --- the text being composed goes in at the caret, underlined
+            if let Some(composition) = self.composition.as_ref().filter(|_| caret_row) {
+                let at = text
+                    .char_indices()
+                    .nth(caret.1)
+                    .map_or(text.len(), |(byte, _)| byte);
+                text.insert_str(at, &composition.text);
+
+                let (x0, x1) = (x_of(caret.1), x_of(caret.1 + composition.len()));
+                carets.push(Layer {
+                    rect: Rect::new(
+                        left + x0,
+                        y + line_height - UNDERLINE_WIDTH,
+                        x1 - x0,
+                        UNDERLINE_WIDTH,
+                    ),
+                    style: opacity.clone(),
+                    fill: Fill::Solid(color),
+                });
+            }
+
+            if !text.is_empty() {
+                texts.push(text_layer(y, &text, ink));
+            }
+
+            let hidden = self
+                .composition
+                .as_ref()
+                .is_some_and(|composition| composition.cursor.is_none());
+            if self.base.state.focused && selection.is_empty() && caret_row && !hidden {
+                carets.push(Layer {
+                    rect: self.caret_rect(&metrics),
+                    style: opacity.clone(),
+                    fill: Fill::Solid(color),
+                });
+            }
+
+            row += 1;
+            if row >= starts.len() {
+                line += 1;
+                row = 0;
+            }
+        }
+
+        highlights.into_iter().chain(texts).chain(carets).collect()
+    }
+
+    fn ime_cursor_area(&self, placement: &Placement) -> Option<Rect> {
+        (!self.base.state.disabled).then(|| self.caret_rect(&self.metrics(placement)))
+    }
+}
+
+impl IntoView for TextArea {
+    fn into_view(self) -> View {
+        View::TextArea(self)
+    }
+}
+
+impl IntoView for &TextArea {
+    fn into_view(self) -> View {
+        View::TextArea(self.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::testing::command;
+
+    fn area(value: &str) -> TextArea {
+        let mut area = TextArea::default();
+        area.set_value(value);
+        area
+    }
+
+    #[test]
+    fn copy_spans_lines() {
+        let clipboard = Clipboard::memory();
+        let mut area = area("one\ntwo\nthree");
+        area.select(2..6);
+
+        assert!(command(&mut area, "c", &clipboard));
+        assert_eq!(clipboard.text().unwrap(), "e\ntw");
+        assert_eq!(area.value(), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn cut_removes_the_selection() {
+        let clipboard = Clipboard::memory();
+        let mut area = area("one\ntwo\nthree");
+        area.select(3..7);
+
+        command(&mut area, "x", &clipboard);
+        assert_eq!(clipboard.text().unwrap(), "\ntwo");
+        assert_eq!(area.value(), "one\nthree");
+    }
+
+    #[test]
+    fn paste_keeps_line_breaks() {
+        let clipboard = Clipboard::memory();
+        clipboard.set_text("a\r\nb").unwrap();
+        let mut area = area("xy");
+        area.select(1..1);
+
+        command(&mut area, "v", &clipboard);
+        assert_eq!(area.value(), "xa\nby");
+        assert_eq!(area.text().len_lines(), 2);
+    }
+
+    #[test]
+    fn cut_then_paste_puts_it_back() {
+        let clipboard = Clipboard::memory();
+        let mut area = area("hello\nworld");
+        area.select(0..6);
+
+        command(&mut area, "x", &clipboard);
+        assert_eq!(area.value(), "world");
+        command(&mut area, "v", &clipboard);
+        assert_eq!(area.value(), "hello\nworld");
+    }
+}
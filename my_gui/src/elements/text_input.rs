@@ -0,0 +1,724 @@
+use super::{Composition, ElementBase, Fill, IntoElement, Layer};
+use crate::animation::{Easing, Property, Transition};
+use crate::clipboard::{Clipboard, ClipboardError};
+use crate::event::{ActionContext, Event, Ime};
+use crate::geometry::Rect;
+use crate::style::{Edges, Style, Styles};
+use crate::text::{self, TextCache};
+use crate::theme::Theme;
+use crate::view::{IntoView, Placement, View};
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::rc::Rc;
+use std::time::Duration;
+
+use winit::event::MouseButton;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::CursorIcon;
+
+// --- shown instead of every character of a password
+const MASK: char = '*';
+const UNDO_LIMIT: usize = 100;
+const CARET_WIDTH: f32 = 1.;
+const SELECTION_ALPHA: f32 = 0.3;
+const PLACEHOLDER_ALPHA: f32 = 0.5;
+const UNDERLINE_WIDTH: f32 = 1.;
+
+type Callback = Rc<dyn Fn(&str)>;
+
+// --- what undo & redo go back to
+#[derive(Debug, Clone)]
+struct Snapshot {
+    value: String,
+    caret: usize,
+    anchor: usize,
+}
+
+// --- a single line of editable text, positions (caret, selection, max length) count chars
+#[derive(Clone)]
+pub struct TextInput {
+    pub base: ElementBase,
+    pub placeholder: String,
+    pub password: bool,
+    pub max_length: Option<usize>,
+    pub on_change: Option<Callback>,
+    pub on_submit: Option<Callback>,
+    value: String,
+    caret: usize,
+    // --- the other end of the selection, equal to the caret when nothing is selected
+    anchor: usize,
+    // --- pixels of the text scrolled out on the left to keep the caret visible
+    scroll: f32,
+    dragging: bool,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    // --- consecutive typing is undone all at once
+    typing: bool,
+    composition: Option<Composition>,
+    rendered: TextCache,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        let position = [10, 10, 46, 210];
+
+        Self {
+            base: ElementBase::at(position),
+            placeholder: String::new(),
+            password: false,
+            max_length: None,
+            on_change: None,
+            on_submit: None,
+            value: String::new(),
+            caret: 0,
+            anchor: 0,
+            scroll: 0.,
+            dragging: false,
+            undo: vec![],
+            redo: vec![],
+            typing: false,
+            composition: None,
+            rendered: TextCache::default(),
+        }
+    }
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    // --- replaces the text without going through undo or `on_change`
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        if let Some(max) = self.max_length {
+            self.value = self.value.chars().take(max).collect();
+        }
+        self.caret = self.caret.min(self.len());
+        self.anchor = self.anchor.min(self.len());
+        self.undo.clear();
+        self.redo.clear();
+        self.typing = false;
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: impl Into<String>) {
+        self.placeholder = placeholder.into();
+    }
+
+    // --- masks the text and keeps it out of the clipboard
+    pub fn set_password(&mut self, password: bool) {
+        self.password = password;
+    }
+
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        if let Some(max) = max_length {
+            self.set_value(self.value.chars().take(max).collect::<String>());
+        }
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    // --- the caret ends up at `range.end`
+    pub fn select(&mut self, range: Range<usize>) {
+        self.anchor = range.start.min(self.len());
+        self.caret = range.end.min(self.len());
+        self.typing = false;
+    }
+
+    pub fn on_change(&mut self, f: impl Fn(&str) + 'static) {
+        self.on_change = Some(Rc::new(f));
+    }
+
+    // --- Enter
+    pub fn on_submit(&mut self, f: impl Fn(&str) + 'static) {
+        self.on_submit = Some(Rc::new(f));
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte(&self, idx: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(idx)
+            .map_or(self.value.len(), |(byte, _)| byte)
+    }
+
+    // --- what's actually drawn, with the text being composed at the caret
+    fn display(&self) -> Cow<'_, str> {
+        match (self.password, self.composition.as_ref()) {
+            (true, _) => Cow::Owned(MASK.to_string().repeat(self.len())),
+            (false, Some(composition)) => {
+                let mut display = self.value.clone();
+                display.insert_str(self.byte(self.caret), &composition.text);
+                Cow::Owned(display)
+            }
+            (false, None) => Cow::Borrowed(&self.value),
+        }
+    }
+
+    // --- the caret as drawn, moved along the text being composed
+    fn display_caret(&self) -> usize {
+        self.caret + self.composition.as_ref().map_or(0, Composition::caret)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            value: self.value.clone(),
+            caret: self.caret,
+            anchor: self.anchor,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.value = snapshot.value;
+        self.caret = snapshot.caret;
+        self.anchor = snapshot.anchor;
+        self.typing = false;
+    }
+
+    fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            self.redo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            self.undo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn move_caret(&mut self, to: usize, extend: bool) {
+        self.caret = to.min(self.len());
+        if !extend {
+            self.anchor = self.caret;
+        }
+        self.typing = false;
+    }
+
+    // --- replaces the selection with `text`, cut short to fit in the max length
+    fn replace(&mut self, text: &str, typing: bool) {
+        let selection = self.selection();
+        let room = self.max_length.map_or(usize::MAX, |max| {
+            max.saturating_sub(self.len() - selection.len())
+        });
+        let text = text.chars().take(room).collect::<String>();
+
+        if selection.is_empty() && text.is_empty() {
+            return;
+        }
+
+        if !(typing && self.typing) {
+            self.undo.push(self.snapshot());
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.typing = typing;
+
+        let bytes = self.byte(selection.start)..self.byte(selection.end);
+        self.value.replace_range(bytes, &text);
+        self.caret = selection.start + text.chars().count();
+        self.anchor = self.caret;
+    }
+
+    fn copy(&self, clipboard: &Clipboard) {
+        let selection = self.selection();
+        if self.password || selection.is_empty() {
+            return;
+        }
+
+        let text = &self.value[self.byte(selection.start)..self.byte(selection.end)];
+        if let Err(err) = clipboard.set_text(text) {
+            log::warn!("Failed to copy: {}", err);
+        }
+    }
+
+    fn paste(&mut self, clipboard: &Clipboard) {
+        let text = match clipboard.text() {
+            Ok(text) => text,
+            Err(ClipboardError::Empty) => return,
+            Err(err) => return log::warn!("Failed to paste: {}", err),
+        };
+        // --- a single line, so line breaks become spaces
+        self.replace(&text.replace(['\r', '\n'], " "), false);
+    }
+
+    fn word_left(&self) -> usize {
+        let chars = self.value.chars().collect::<Vec<_>>();
+        let mut idx = self.caret;
+        while idx > 0 && chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        while idx > 0 && !chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    fn word_right(&self) -> usize {
+        let chars = self.value.chars().collect::<Vec<_>>();
+        let mut idx = self.caret;
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        while idx < chars.len() && !chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        idx
+    }
+
+    fn key_down(
+        &mut self,
+        key: &Key,
+        text: Option<&str>,
+        modifiers: ModifiersState,
+        clipboard: &Clipboard,
+    ) -> bool {
+        // --- keys belong to the input method while composing
+        if self.composition.is_some() {
+            return true;
+        }
+
+        let command = modifiers.control_key() || modifiers.super_key();
+        let shift = modifiers.shift_key();
+        let selection = self.selection();
+
+        match key {
+            Key::Named(NamedKey::ArrowLeft) => {
+                let to = match (command, shift || selection.is_empty()) {
+                    (true, _) => self.word_left(),
+                    (false, true) => self.caret.saturating_sub(1),
+                    // --- collapses the selection to its start
+                    (false, false) => selection.start,
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                let to = match (command, shift || selection.is_empty()) {
+                    (true, _) => self.word_right(),
+                    (false, true) => self.caret + 1,
+                    (false, false) => selection.end,
+                };
+                self.move_caret(to, shift);
+            }
+            Key::Named(NamedKey::Home | NamedKey::ArrowUp) => self.move_caret(0, shift),
+            Key::Named(NamedKey::End | NamedKey::ArrowDown) => self.move_caret(self.len(), shift),
+            Key::Named(NamedKey::Backspace) => {
+                if selection.is_empty() {
+                    self.anchor = match command {
+                        true => self.word_left(),
+                        false => self.caret.saturating_sub(1),
+                    };
+                }
+                self.replace("", false);
+            }
+            Key::Named(NamedKey::Delete) => {
+                if selection.is_empty() {
+                    self.anchor = match command {
+                        true => self.word_right(),
+                        false => (self.caret + 1).min(self.len()),
+                    };
+                }
+                self.replace("", false);
+            }
+            Key::Named(NamedKey::Enter) => {
+                if let Some(f) = self.on_submit.as_ref() {
+                    f(&self.value);
+                }
+            }
+            Key::Character(ch) if command => match ch.to_lowercase().as_str() {
+                "a" => self.select(0..self.len()),
+                "c" => self.copy(clipboard),
+                "x" => {
+                    self.copy(clipboard);
+                    if !self.password {
+                        self.replace("", false);
+                    }
+                }
+                "v" => self.paste(clipboard),
+                "z" if shift => self.redo(),
+                "z" => self.undo(),
+                "y" => self.redo(),
+                _ => return false,
+            },
+            _ => match text.filter(|text| !text.chars().any(char::is_control)) {
+                Some(text) if !command => self.replace(text, true),
+                _ => return false,
+            },
+        }
+
+        true
+    }
+
+    fn ime(&mut self, ime: &Ime) -> bool {
+        match ime {
+            Ime::Preedit { text, cursor } => {
+                // --- composing replaces the selection, like typing does
+                if self.composition.is_none() && !text.is_empty() {
+                    self.replace("", false);
+                }
+                self.composition = (!text.is_empty()).then(|| Composition {
+                    text: text.clone(),
+                    cursor: cursor.clone(),
+                });
+            }
+            Ime::Commit(text) => {
+                self.composition = None;
+                self.replace(&text.replace(['\r', '\n'], " "), false);
+            }
+            Ime::Disabled => self.composition = None,
+            Ime::Enabled => return false,
+        }
+
+        true
+    }
+
+    // --- the line of text inside the content box, vertically centered
+    fn line_box(&self, placement: &Placement) -> Rect {
+        let content = placement.layout.content_box;
+        let font = placement.style.font.clone().unwrap_or_default();
+        let height = text::line_height(&font);
+
+        Rect::new(
+            content.x.round(),
+            (content.y + (content.height - height) / 2.).round(),
+            content.width.floor().max(0.),
+            height,
+        )
+    }
+
+    fn caret_rect(&self, placement: &Placement) -> Rect {
+        let font = placement.style.font.clone().unwrap_or_default();
+        let line = self.line_box(placement);
+        let x = (self.display_caret() as f32 * text::advance(&font) - self.scroll)
+            .clamp(0., (line.width - CARET_WIDTH).max(0.))
+            .round();
+
+        Rect::new(line.x + x, line.y, CARET_WIDTH, line.height)
+    }
+
+    // --- char index under `x` (window coordinates)
+    fn index_at(&self, x: f32, placement: &Placement) -> usize {
+        let font = placement.style.font.clone().unwrap_or_default();
+        let x = x - placement.layout.content_box.x.round() + self.scroll;
+        text::index_at(&self.display(), &font, x)
+    }
+
+    // --- scrolls just enough for the caret to be inside the content box
+    fn scroll_to_caret(&mut self, placement: &Placement) {
+        let font = placement.style.font.clone().unwrap_or_default();
+        let width = (placement.layout.content_box.width - CARET_WIDTH).max(0.);
+        let caret = self.display_caret() as f32 * text::advance(&font);
+        let overflow = (text::measure(&self.display(), &font) - width).max(0.);
+
+        if caret - self.scroll > width {
+            self.scroll = caret - width;
+        }
+        if caret < self.scroll {
+            self.scroll = caret;
+        }
+        self.scroll = self.scroll.clamp(0., overflow);
+    }
+}
+
+deref_base!(TextInput);
+
+impl IntoElement for TextInput {
+    fn type_name(&self) -> &'static str {
+        "input"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    fn default_styles(&self, theme: &Theme) -> Styles {
+        let palette = &theme.palette;
+
+        Styles::from(
+            Style::new()
+                .with_background(palette.surface)
+                .with_foreground(palette.text)
+                .with_border(1., palette.border)
+                .with_radius(theme.radii.sm)
+                .with_padding(Edges::all(theme.spacing.sm))
+                .with_font(theme.typography.font(theme.typography.body))
+                .with_cursor(CursorIcon::Text)
+                .with_transition(
+                    Transition::new(Some(Property::BorderColor), Duration::from_millis(120))
+                        .with_easing(Easing::EaseOut),
+                ),
+        )
+        .with_focus(Style::new().with_border(1., palette.focus))
+        .with_disabled(
+            Style::new()
+                .with_opacity(0.5)
+                .with_cursor(CursorIcon::NotAllowed),
+        )
+    }
+
+    fn focusable(&self) -> bool {
+        !self.base.state.disabled
+    }
+
+    fn default_action(&mut self, event: &Event, cx: &ActionContext) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+
+        let placement = cx.placement;
+        let before = self.value.clone();
+        let handled = match event {
+            Event::PointerDown {
+                x,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let idx = self.index_at(*x, placement);
+                self.move_caret(idx, false);
+                self.dragging = true;
+                true
+            }
+            Event::PointerMove { x, .. } if self.dragging => {
+                let idx = self.index_at(*x, placement);
+                self.move_caret(idx, true);
+                true
+            }
+            Event::PointerUp {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.dragging = false;
+                true
+            }
+            Event::KeyDown {
+                key,
+                text,
+                modifiers,
+                ..
+            } => self.key_down(key, text.as_deref(), *modifiers, cx.clipboard),
+            Event::Ime(ime) => self.ime(ime),
+            _ => false,
+        };
+
+        if handled {
+            self.scroll_to_caret(placement);
+        }
+        if self.value != before {
+            if let Some(f) = self.on_change.as_ref() {
+                f(&self.value);
+            }
+        }
+
+        handled
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let style = &placement.style;
+        let font = style.font.clone().unwrap_or_default();
+        let color = style.foreground.unwrap_or([0., 0., 0., 1.]);
+        let opacity = Style::new().with_opacity(style.opacity.unwrap_or(1.));
+
+        let Rect {
+            x: left,
+            y: top,
+            width,
+            height,
+        } = self.line_box(placement);
+        if width < 1. {
+            return vec![];
+        }
+
+        let advance = text::advance(&font);
+        let x_of = |idx: usize| (idx as f32 * advance - self.scroll).clamp(0., width);
+        let mut layers = vec![];
+
+        let selection = self.selection();
+        if self.base.state.focused && !selection.is_empty() {
+            let (start, end) = (x_of(selection.start), x_of(selection.end));
+            layers.push(Layer {
+                rect: Rect::new(left + start, top, end - start, height),
+                style: opacity.clone(),
+                fill: Fill::Solid([color[0], color[1], color[2], color[3] * SELECTION_ALPHA]),
+            });
+        }
+
+        // --- with a gradient the text is drawn in white, only to mask it
+        let (ink, text_style) = match style.text_gradient.clone() {
+            Some(gradient) => (
+                [1., 1., 1., 1.],
+                opacity.clone().with_background_gradient(gradient),
+            ),
+            None => (color, opacity.clone()),
+        };
+        let rendered = &self.rendered;
+        let texture = match self.value.is_empty() && self.composition.is_none() {
+            true => {
+                let muted = [ink[0], ink[1], ink[2], ink[3] * PLACEHOLDER_ALPHA];
+                rendered.texture(&self.placeholder, &font, muted, 0., width as u32)
+            }
+            false => rendered.texture(&self.display(), &font, ink, self.scroll, width as u32),
+        };
+        layers.push(Layer {
+            rect: Rect::new(left, top, width, height),
+            style: text_style,
+            fill: Fill::texture(texture),
+        });
+
+        // --- the text being composed is underlined
+        if let Some(composition) = self.composition.as_ref() {
+            let (start, end) = (x_of(self.caret), x_of(self.caret + composition.len()));
+            layers.push(Layer {
+                rect: Rect::new(
+                    left + start,
+                    top + height - UNDERLINE_WIDTH,
+                    end - start,
+                    UNDERLINE_WIDTH,
+                ),
+                style: opacity.clone(),
+                fill: Fill::Solid(color),
+            });
+        }
+
+        let hidden = self
+            .composition
+            .as_ref()
+            .is_some_and(|composition| composition.cursor.is_none());
+        if self.base.state.focused && selection.is_empty() && !hidden {
+            layers.push(Layer {
+                rect: self.caret_rect(placement),
+                style: opacity,
+                fill: Fill::Solid(color),
+            });
+        }
+
+        layers
+    }
+
+    // --- no input method for passwords, candidates would show what's typed
+    fn ime_cursor_area(&self, placement: &Placement) -> Option<Rect> {
+        (!self.password && !self.base.state.disabled).then(|| self.caret_rect(placement))
+    }
+}
+
+impl IntoView for TextInput {
+    fn into_view(self) -> View {
+        View::TextInput(self)
+    }
+}
+
+impl IntoView for &TextInput {
+    fn into_view(self) -> View {
+        View::TextInput(self.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::testing::command;
+
+    fn input(value: &str) -> TextInput {
+        let mut input = TextInput::default();
+        input.set_value(value);
+        input
+    }
+
+    #[test]
+    fn copy_leaves_the_text() {
+        let clipboard = Clipboard::memory();
+        let mut input = input("hello world");
+        input.select(0..5);
+
+        assert!(command(&mut input, "c", &clipboard));
+        assert_eq!(clipboard.text().unwrap(), "hello");
+        assert_eq!(input.value(), "hello world");
+    }
+
+    #[test]
+    fn cut_removes_the_selection() {
+        let clipboard = Clipboard::memory();
+        let mut input = input("hello world");
+        input.select(5..11);
+
+        command(&mut input, "x", &clipboard);
+        assert_eq!(clipboard.text().unwrap(), " world");
+        assert_eq!(input.value(), "hello");
+        assert_eq!(input.caret(), 5);
+    }
+
+    #[test]
+    fn paste_replaces_the_selection_on_one_line() {
+        let clipboard = Clipboard::memory();
+        clipboard.set_text("big\nwide").unwrap();
+        let mut input = input("hello world");
+        input.select(6..11);
+
+        command(&mut input, "v", &clipboard);
+        assert_eq!(input.value(), "hello big wide");
+        assert_eq!(input.caret(), 14);
+    }
+
+    #[test]
+    fn paste_from_an_empty_clipboard_does_nothing() {
+        let clipboard = Clipboard::memory();
+        let mut input = input("hello");
+
+        command(&mut input, "v", &clipboard);
+        assert_eq!(input.value(), "hello");
+    }
+
+    #[test]
+    fn paste_is_cut_to_the_max_length() {
+        let clipboard = Clipboard::memory();
+        clipboard.set_text("12345").unwrap();
+        let mut input = input("abc");
+        input.set_max_length(Some(6));
+        input.select(3..3);
+
+        command(&mut input, "v", &clipboard);
+        assert_eq!(input.value(), "abc123");
+
+        // --- a selection makes room for what replaces it
+        input.select(0..3);
+        command(&mut input, "v", &clipboard);
+        assert_eq!(input.value(), "123123");
+    }
+
+    #[test]
+    fn passwords_stay_out_of_the_clipboard() {
+        let clipboard = Clipboard::memory();
+        clipboard.set_text("before").unwrap();
+        let mut input = input("secret");
+        input.set_password(true);
+        input.select(0..6);
+
+        command(&mut input, "c", &clipboard);
+        command(&mut input, "x", &clipboard);
+        assert_eq!(clipboard.text().unwrap(), "before");
+        assert_eq!(input.value(), "secret");
+
+        // --- pasting into one is fine
+        input.select(0..6);
+        command(&mut input, "v", &clipboard);
+        assert_eq!(input.value(), "before");
+    }
+}
@@ -0,0 +1,293 @@
+use super::{ElementBase, Fill, Fit, IntoElement, Layer};
+use crate::event::{ActionContext, Event};
+use crate::style::Style;
+use crate::video::{FrameSource, VideoFrame};
+use crate::view::{IntoView, Placement, View};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use winit::keyboard::{Key, NamedKey};
+
+type Feed = Rc<RefCell<dyn FnMut(Duration) -> Option<VideoFrame>>>;
+type Callback = Rc<dyn Fn(Duration)>;
+
+// --- how far the arrow keys seek
+const SEEK_STEP: Duration = Duration::from_secs(5);
+
+// --- plays frames handed to it by the app, either pushed ahead of time or asked for
+// --- by position. clicking, Enter or Space toggle playback, the arrow keys seek
+#[derive(Clone)]
+pub struct Video {
+    pub base: ElementBase,
+    pub fit: Fit,
+    pub on_seek: Option<Callback>,
+    // --- playback stops there when it's known, or starts over when looping
+    pub duration: Option<Duration>,
+    pub looping: bool,
+    feed: Option<Feed>,
+    // --- pushed frames not shown yet, in timestamp order
+    queue: VecDeque<VideoFrame>,
+    current: Option<VideoFrame>,
+    position: Duration,
+    playing: bool,
+    // --- when the position was last moved along while playing
+    last_tick: Option<Instant>,
+}
+
+impl Default for Video {
+    fn default() -> Self {
+        // --- 320x180, 16:9 like most video
+        let position = [10, 10, 190, 330];
+
+        Self {
+            base: ElementBase::at(position),
+            fit: Fit::default(),
+            on_seek: None,
+            duration: None,
+            looping: false,
+            feed: None,
+            queue: VecDeque::new(),
+            current: None,
+            position: Duration::ZERO,
+            playing: false,
+            last_tick: None,
+        }
+    }
+}
+
+impl Video {
+    // --- asked for the frame showing at each position it gets to,
+    // --- returning the same frame again keeps it up without another upload
+    pub fn set_feed(&mut self, feed: impl FnMut(Duration) -> Option<VideoFrame> + 'static) {
+        self.feed = Some(Rc::new(RefCell::new(feed)));
+    }
+
+    // --- frames are read as playback gets to them, the duration is taken from it
+    pub fn set_source(&mut self, mut source: impl FrameSource + 'static) {
+        self.duration = source.duration();
+
+        // --- the frame showing is kept, so it isn't read & uploaded again every step
+        let mut showing: Option<(u64, VideoFrame)> = None;
+        self.set_feed(move |position| {
+            let index = source.index_at(position)?;
+            if let Some((_, frame)) = showing.as_ref().filter(|(shown, _)| *shown == index) {
+                return Some(frame.clone());
+            }
+
+            match source.frame(index) {
+                Ok(frame) => {
+                    showing = Some((index, frame.clone()));
+                    Some(frame)
+                }
+                Err(err) => {
+                    log::warn!("Can't read video frame {}: {}", index, err);
+                    None
+                }
+            }
+        });
+        self.show();
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    // --- shown once playback gets to its timestamp, frames from the past are dropped
+    pub fn push_frame(&mut self, frame: VideoFrame) {
+        let idx = self
+            .queue
+            .partition_point(|queued| queued.timestamp() <= frame.timestamp());
+        self.queue.insert(idx, frame);
+    }
+
+    // --- frames waiting to be shown
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn frame(&self) -> Option<&VideoFrame> {
+        self.current.as_ref()
+    }
+
+    // --- how far into the video playback is
+    pub fn current_time(&self) -> Duration {
+        self.position
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn play(&mut self) {
+        if self
+            .duration
+            .is_some_and(|duration| self.position >= duration)
+        {
+            self.seek(Duration::ZERO);
+        }
+        self.playing = true;
+        self.last_tick = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.last_tick = None;
+    }
+
+    pub fn toggle(&mut self) {
+        match self.playing {
+            true => self.pause(),
+            false => self.play(),
+        }
+    }
+
+    // --- pushed frames are dropped, `on_seek` tells the app where to push from
+    pub fn seek(&mut self, position: Duration) {
+        let position = match self.duration {
+            Some(duration) => position.min(duration),
+            None => position,
+        };
+        self.position = position;
+        self.queue.clear();
+        self.last_tick = None;
+        self.show();
+
+        if let Some(f) = self.on_seek.as_ref() {
+            f(position);
+        }
+    }
+
+    pub fn on_seek(&mut self, f: impl Fn(Duration) + 'static) {
+        self.on_seek = Some(Rc::new(f));
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    // --- picks the frame for the current position
+    fn show(&mut self) {
+        if let Some(feed) = self.feed.as_ref() {
+            if let Some(frame) = (feed.borrow_mut())(self.position) {
+                self.current = Some(frame);
+            }
+        }
+
+        while self
+            .queue
+            .front()
+            .is_some_and(|frame| frame.timestamp() <= self.position)
+        {
+            self.current = self.queue.pop_front();
+        }
+    }
+
+    fn key_down(&mut self, key: &Key) -> bool {
+        match key {
+            Key::Named(NamedKey::ArrowLeft) => self.seek(self.position.saturating_sub(SEEK_STEP)),
+            Key::Named(NamedKey::ArrowRight) => self.seek(self.position + SEEK_STEP),
+            Key::Named(NamedKey::Home) => self.seek(Duration::ZERO),
+            _ => return false,
+        }
+        true
+    }
+}
+
+deref_base!(Video);
+
+impl IntoElement for Video {
+    fn type_name(&self) -> &'static str {
+        "video"
+    }
+
+    fn base(&self) -> &ElementBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ElementBase {
+        &mut self.base
+    }
+
+    // --- so the keyboard can control playback
+    fn focusable(&self) -> bool {
+        !self.base.state.disabled
+    }
+
+    // --- moves the position along while playing, frames keep coming until it's paused
+    fn step(&mut self, now: Instant) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        if let Some(last) = self.last_tick {
+            self.position += now.saturating_duration_since(last);
+        }
+        self.last_tick = Some(now);
+
+        if let Some(duration) = self.duration.filter(|duration| self.position >= *duration) {
+            // --- starting over is a seek, so an app pushing frames knows to start over too
+            if self.looping && !duration.is_zero() {
+                let nanos = self.position.as_nanos() % duration.as_nanos();
+                self.seek(Duration::from_nanos(nanos as u64));
+                self.last_tick = Some(now);
+                return true;
+            }
+            self.position = duration;
+            self.pause();
+        }
+        self.show();
+        self.playing
+    }
+
+    fn default_action(&mut self, event: &Event, _cx: &ActionContext) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+
+        match event {
+            Event::Click { .. } => {
+                self.toggle();
+                true
+            }
+            Event::KeyDown { key, .. } => self.key_down(key),
+            _ => false,
+        }
+    }
+
+    fn layers(&self, placement: &Placement) -> Vec<Layer> {
+        let Some(frame) = &self.current else {
+            return vec![];
+        };
+        let style = &placement.style;
+        let target = placement.layout.content_box;
+
+        let natural = [frame.width() as f32, frame.height() as f32];
+        let Some((visible, source)) = self.fit.crop(natural, &target) else {
+            return vec![];
+        };
+
+        vec![Layer {
+            rect: visible,
+            style: Style::new().with_opacity(style.opacity.unwrap_or(1.)),
+            fill: Fill::Video {
+                frame: frame.clone(),
+                source,
+            },
+        }]
+    }
+}
+
+impl IntoView for Video {
+    fn into_view(self) -> View {
+        View::Video(self)
+    }
+}
+
+impl IntoView for &Video {
+    fn into_view(self) -> View {
+        View::Video(self.to_owned())
+    }
+}
@@ -0,0 +1,423 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use winit::event::{MouseButton, TouchPhase};
+use winit::keyboard::{Key, ModifiersState};
+
+use crate::clipboard::Clipboard;
+use crate::geometry::Transform;
+use crate::view::{view_at, view_at_mut, Placement, View};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WheelDelta {
+    Lines(f32, f32),
+    Pixels(f32, f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    PointerDown {
+        x: f32,
+        y: f32,
+        button: MouseButton,
+    },
+    PointerUp {
+        x: f32,
+        y: f32,
+        button: MouseButton,
+    },
+    PointerMove {
+        x: f32,
+        y: f32,
+    },
+    // --- pointer down & up on the same view, or Enter/Space on the focused one
+    Click {
+        x: f32,
+        y: f32,
+    },
+    // --- touchpads report a phase, letting a scroll carry on with momentum once it ends
+    Wheel {
+        x: f32,
+        y: f32,
+        delta: WheelDelta,
+        phase: TouchPhase,
+    },
+    KeyDown {
+        key: Key,
+        text: Option<String>,
+        repeat: bool,
+        modifiers: ModifiersState,
+    },
+    KeyUp {
+        key: Key,
+        modifiers: ModifiersState,
+    },
+    // --- input method composition, sent to the focused view
+    Ime(Ime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ime {
+    Enabled,
+    // --- text being composed, an empty one ends the composition,
+    // --- `cursor` is the highlighted part of it in chars, `None` hides the caret
+    Preedit {
+        text: String,
+        cursor: Option<Range<usize>>,
+    },
+    Commit(String),
+    Disabled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    PointerDown,
+    PointerUp,
+    PointerMove,
+    Click,
+    Wheel,
+    KeyDown,
+    KeyUp,
+    Ime,
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::PointerDown { .. } => EventKind::PointerDown,
+            Self::PointerUp { .. } => EventKind::PointerUp,
+            Self::PointerMove { .. } => EventKind::PointerMove,
+            Self::Click { .. } => EventKind::Click,
+            Self::Wheel { .. } => EventKind::Wheel,
+            Self::KeyDown { .. } => EventKind::KeyDown,
+            Self::KeyUp { .. } => EventKind::KeyUp,
+            Self::Ime(_) => EventKind::Ime,
+        }
+    }
+
+    // --- the same event with its pointer position mapped, the rest are left alone
+    pub fn transformed(&self, transform: &Transform) -> Event {
+        let mut event = self.clone();
+        match &mut event {
+            Self::PointerDown { x, y, .. }
+            | Self::PointerUp { x, y, .. }
+            | Self::PointerMove { x, y }
+            | Self::Click { x, y }
+            | Self::Wheel { x, y, .. } => [*x, *y] = transform.apply([*x, *y]),
+            Self::KeyDown { .. } | Self::KeyUp { .. } | Self::Ime(_) => (),
+        }
+        event
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Capture,
+    Target,
+    Bubble,
+}
+
+// --- what a listener gets alongside the event, and how it talks back to the dispatcher
+#[derive(Debug)]
+pub struct EventContext {
+    pub phase: Phase,
+    pub target: Vec<usize>,
+    pub current_target: Vec<usize>,
+    propagation_stopped: bool,
+    default_prevented: bool,
+    pointer_capture: Option<bool>,
+}
+
+impl EventContext {
+    fn new(target: &[usize]) -> Self {
+        Self {
+            phase: Phase::Capture,
+            target: target.to_vec(),
+            current_target: vec![],
+            propagation_stopped: false,
+            default_prevented: false,
+            pointer_capture: None,
+        }
+    }
+
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    pub fn prevent_default(&mut self) {
+        self.default_prevented = true;
+    }
+
+    pub fn is_default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+
+    // --- every pointer event goes to the current target until the button is released
+    pub fn capture_pointer(&mut self) {
+        self.pointer_capture = Some(true);
+    }
+
+    pub fn release_pointer(&mut self) {
+        self.pointer_capture = Some(false);
+    }
+}
+
+pub type Listener = Rc<dyn Fn(&Event, &mut EventContext)>;
+
+#[derive(Clone, Default)]
+pub struct Listeners {
+    entries: Vec<(EventKind, bool, Listener)>,
+}
+
+impl Listeners {
+    pub fn add(
+        &mut self,
+        kind: EventKind,
+        capture: bool,
+        f: impl Fn(&Event, &mut EventContext) + 'static,
+    ) {
+        self.entries.push((kind, capture, Rc::new(f)));
+    }
+
+    // --- cloned out so no view is borrowed while the listeners run
+    fn matching(&self, kind: EventKind, phase: Phase) -> Vec<Listener> {
+        self.entries
+            .iter()
+            .filter(|(k, capture, _)| {
+                *k == kind
+                    && match phase {
+                        Phase::Capture => *capture,
+                        Phase::Target => true,
+                        Phase::Bubble => !*capture,
+                    }
+            })
+            .map(|(_, _, f)| f.clone())
+            .collect()
+    }
+}
+
+// --- what a default action gets alongside the event
+pub struct ActionContext<'a> {
+    // --- where the element sits & how it's styled right now
+    pub placement: &'a Placement,
+    pub clipboard: &'a Clipboard,
+}
+
+// --- what's left for the app to act on once the event went through the tree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    // --- whether any listener or default action ran, i.e. something may have changed
+    pub handled: bool,
+    pub default_prevented: bool,
+    // --- a default action took care of it, so the app shouldn't act on it too
+    pub default_handled: bool,
+    // --- `Some(true)` to start capturing the pointer for the target, `Some(false)` to release it
+    pub pointer_capture: Option<bool>,
+}
+
+// --- capture from the root down to the target's parent, the target itself,
+// --- then bubble back up, finally the default action walking up from the target.
+// --- `placements` go from the root down to the target. listeners get pointer positions
+// --- in the window, default actions get them in the view's layout, before its transform
+pub fn dispatch(
+    views: &mut [View],
+    target: &[usize],
+    placements: &[Placement],
+    clipboard: &Clipboard,
+    event: &Event,
+) -> Outcome {
+    let mut ctx = EventContext::new(target);
+    let mut handled = false;
+    let mut default_handled = false;
+    let kind = event.kind();
+    let ancestors = (1..target.len()).map(|depth| &target[..depth]);
+
+    let phases = ancestors
+        .clone()
+        .map(|path| (Phase::Capture, path))
+        .chain(std::iter::once((Phase::Target, target)))
+        .chain(ancestors.rev().map(|path| (Phase::Bubble, path)));
+
+    for (phase, path) in phases {
+        let Some(view) = view_at(views, path) else {
+            continue;
+        };
+
+        ctx.phase = phase;
+        ctx.current_target = path.to_vec();
+        for listener in view.element().listeners().matching(kind, phase) {
+            listener(event, &mut ctx);
+            handled = true;
+        }
+
+        if ctx.propagation_stopped {
+            break;
+        }
+    }
+
+    if !ctx.default_prevented {
+        for depth in (1..=target.len()).rev() {
+            let Some(placement) = placements.get(depth - 1) else {
+                continue;
+            };
+            let cx = ActionContext {
+                placement,
+                clipboard,
+            };
+            let local = match placement.transform.invert() {
+                Some(inverse) => event.transformed(&inverse),
+                None => continue,
+            };
+            let acted = view_at_mut(views, &target[..depth])
+                .is_some_and(|view| view.element_mut().default_action(&local, &cx));
+            if acted {
+                handled = true;
+                default_handled = true;
+                break;
+            }
+        }
+    }
+
+    Outcome {
+        handled,
+        default_prevented: ctx.default_prevented,
+        default_handled,
+        pointer_capture: ctx.pointer_capture,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{button, container, Button, Container};
+    use crate::geometry::Rect;
+    use crate::style::Style;
+    use crate::view::{IntoView, Layout};
+
+    use std::cell::{Cell, RefCell};
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    const CLICK: Event = Event::Click { x: 5., y: 5. };
+
+    fn placement() -> Placement {
+        let style = Style::new();
+        Placement {
+            layout: Layout::new(Rect::new(0., 0., 100., 100.), &style),
+            style,
+            transform: Default::default(),
+        }
+    }
+
+    // --- logs `<name> <phase>` for clicks reaching the listener
+    fn listen(listeners: &mut Listeners, log: &Log, name: &str, capture: bool) {
+        let (log, name) = (log.clone(), name.to_string());
+        listeners.add(EventKind::Click, capture, move |_, cx| {
+            log.borrow_mut().push(format!("{} {:?}", name, cx.phase));
+        });
+    }
+
+    // --- outer > inner > the button, clicked through its default action
+    fn tree(log: &Log, clicks: &Rc<Cell<u32>>) -> (Container, Container, Button) {
+        let (mut outer, mut inner, mut target) = (container(), container(), button());
+        for (name, listeners) in [
+            ("outer", &mut outer.listeners),
+            ("inner", &mut inner.listeners),
+            ("target", &mut target.listeners),
+        ] {
+            listen(listeners, log, name, true);
+            listen(listeners, log, name, false);
+        }
+
+        let clicks = clicks.clone();
+        target.on_click(move || clicks.set(clicks.get() + 1));
+        (outer, inner, target)
+    }
+
+    fn run(mut outer: Container, mut inner: Container, target: Button) -> Outcome {
+        inner.add_child(target);
+        outer.add_child(inner);
+
+        let mut views = vec![outer.into_view()];
+        let placements = [placement(), placement(), placement()];
+        dispatch(
+            &mut views,
+            &[0, 0, 0],
+            &placements,
+            &Clipboard::memory(),
+            &CLICK,
+        )
+    }
+
+    #[test]
+    fn phases() {
+        let log = Log::default();
+        let clicks = Rc::new(Cell::new(0));
+        let (outer, inner, target) = tree(&log, &clicks);
+
+        let outcome = run(outer, inner, target);
+        assert_eq!(
+            *log.borrow(),
+            [
+                "outer Capture",
+                "inner Capture",
+                "target Target",
+                "target Target",
+                "inner Bubble",
+                "outer Bubble",
+            ]
+        );
+        assert!(outcome.handled && outcome.default_handled);
+        assert!(!outcome.default_prevented);
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[test]
+    fn stop_propagation() {
+        let log = Log::default();
+        let clicks = Rc::new(Cell::new(0));
+        let (outer, mut inner, target) = tree(&log, &clicks);
+        inner.on_capture(EventKind::Click, |_, cx| cx.stop_propagation());
+
+        let outcome = run(outer, inner, target);
+        // --- the rest of the listeners on the view still run
+        assert_eq!(*log.borrow(), ["outer Capture", "inner Capture"]);
+        // --- the default action isn't a listener, it still runs
+        assert!(outcome.default_handled);
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[test]
+    fn prevent_default() {
+        let log = Log::default();
+        let clicks = Rc::new(Cell::new(0));
+        let (mut outer, inner, target) = tree(&log, &clicks);
+        outer.on_capture(EventKind::Click, |_, cx| cx.prevent_default());
+
+        let outcome = run(outer, inner, target);
+        assert_eq!(log.borrow().len(), 6);
+        assert!(outcome.handled && outcome.default_prevented);
+        assert!(!outcome.default_handled);
+        assert_eq!(clicks.get(), 0);
+    }
+
+    #[test]
+    fn default_action_walks_up() {
+        let log = Log::default();
+        let clicks = Rc::new(Cell::new(0));
+        let (outer, inner, mut target) = tree(&log, &clicks);
+        target.set_disabled(true);
+
+        // --- a disabled button passes, and containers have nothing to do with clicks
+        let outcome = run(outer, inner, target);
+        assert!(outcome.handled);
+        assert!(!outcome.default_handled);
+        assert_eq!(clicks.get(), 0);
+
+        // --- without listeners either, nothing took the click
+        let mut disabled = button();
+        disabled.set_disabled(true);
+        let outcome = run(container(), container(), disabled);
+        assert_eq!(outcome, Outcome::default());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::view::{view_at, view_at_mut, View};
+
+// --- keeps track of the focused view by its path in the view tree
+#[derive(Debug, Default)]
+pub struct FocusManager {
+    focused: Option<Vec<usize>>,
+}
+
+impl FocusManager {
+    pub fn focused(&self) -> Option<&[usize]> {
+        self.focused.as_deref()
+    }
+
+    // --- `None` blurs, returns whether the focus moved
+    pub fn focus(&mut self, views: &mut [View], path: Option<Vec<usize>>) -> bool {
+        if path == self.focused {
+            return false;
+        }
+
+        // --- a view that can't take the focus leaves it where it is
+        if let Some(new) = path.as_ref() {
+            let focusable = view_at(views, new).is_some_and(|view| view.element().focusable());
+            if !focusable {
+                return false;
+            }
+        }
+
+        if let Some(old) = self.focused.take() {
+            if let Some(view) = view_at_mut(views, &old) {
+                view.element_mut().state_mut().focused = false;
+            }
+        }
+        if let Some(view) = path.as_ref().and_then(|new| view_at_mut(views, new)) {
+            view.element_mut().state_mut().focused = true;
+        }
+
+        self.focused = path;
+        true
+    }
+
+    // --- Tab & Shift+Tab, wrapping around at both ends
+    pub fn traverse(&mut self, views: &mut [View], backwards: bool) -> bool {
+        let order = tab_order(views);
+        if order.is_empty() {
+            return false;
+        }
+
+        let current = self
+            .focused
+            .as_ref()
+            .and_then(|focused| order.iter().position(|path| path == focused));
+
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+            (Some(idx), false) => (idx + 1) % order.len(),
+            (Some(idx), true) => (idx + order.len() - 1) % order.len(),
+        };
+
+        self.focus(views, Some(order[next].clone()))
+    }
+
+    // --- the focused view may have moved or gone away after the tree changed
+    // --- when it can't be focused anymore it's blurred, whatever sits at its path is told so
+    pub fn validate(&mut self, views: &mut [View]) {
+        let still_there = self
+            .focused
+            .as_ref()
+            .and_then(|path| view_at(views, path))
+            .is_some_and(|view| view.element().focusable());
+
+        if !still_there {
+            self.focus(views, None);
+        }
+    }
+}
+
+// --- same rules as html: positive tab indices first in ascending order,
+// --- then the ones without (or with 0) in tree order, negative ones are skipped
+pub fn tab_order(views: &[View]) -> Vec<Vec<usize>> {
+    fn visit(views: &[View], path: &mut Vec<usize>, out: &mut Vec<(i32, Vec<usize>)>) {
+        for (idx, view) in views.iter().enumerate() {
+            path.push(idx);
+
+            let element = view.element();
+            let tab_index = element.tab_index().unwrap_or(0);
+            if element.focusable() && tab_index >= 0 {
+                out.push((tab_index, path.clone()));
+            }
+
+            visit(view.children(), path, out);
+            path.pop();
+        }
+    }
+
+    let mut order = Vec::new();
+    visit(views, &mut Vec::new(), &mut order);
+
+    // --- stable, so tree order is kept among equal indices
+    order.sort_by_key(|(tab_index, _)| match tab_index {
+        0 => i32::MAX,
+        positive => *positive,
+    });
+
+    order.into_iter().map(|(_, path)| path).collect()
+}
+
+pub fn find_by_id(views: &[View], id: &str) -> Option<Vec<usize>> {
+    views.iter().enumerate().find_map(|(idx, view)| {
+        if view.element().id() == Some(id) {
+            return Some(vec![idx]);
+        }
+
+        find_by_id(view.children(), id).map(|mut path| {
+            path.insert(0, idx);
+            path
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{button, container, Button};
+    use crate::view::IntoView;
+
+    fn tabbable(tab_index: Option<i32>) -> Button {
+        let mut button = button();
+        if let Some(tab_index) = tab_index {
+            button.set_tab_index(tab_index);
+        }
+        button
+    }
+
+    #[test]
+    fn order() {
+        let mut group = container();
+        group.add_child(tabbable(Some(-1)));
+        group.add_child(tabbable(Some(1)));
+        group.add_child(tabbable(None));
+
+        let mut disabled = button();
+        disabled.set_tab_index(1);
+        disabled.set_disabled(true);
+
+        let views = vec![
+            tabbable(Some(0)).into_view(),
+            tabbable(Some(2)).into_view(),
+            group.into_view(),
+            disabled.into_view(),
+            tabbable(Some(1)).into_view(),
+        ];
+
+        // --- 1s in tree order, then the 2, then 0 & none in tree order
+        assert_eq!(
+            tab_order(&views),
+            vec![vec![2, 1], vec![4], vec![1], vec![0], vec![2, 2]]
+        );
+    }
+
+    #[test]
+    fn traverse_wraps() {
+        let mut views = vec![tabbable(None).into_view(), tabbable(None).into_view()];
+        let mut focus = FocusManager::default();
+
+        assert!(focus.traverse(&mut views, true));
+        assert_eq!(focus.focused(), Some(&[1][..]));
+        assert!(focus.traverse(&mut views, false));
+        assert_eq!(focus.focused(), Some(&[0][..]));
+        assert!(views[0].element().state().focused);
+        assert!(!views[1].element().state().focused);
+    }
+
+    #[test]
+    fn validate_blurs() {
+        let mut views = vec![tabbable(None).into_view()];
+        let mut focus = FocusManager::default();
+        assert!(focus.focus(&mut views, Some(vec![0])));
+
+        if let View::Button(button) = &mut views[0] {
+            button.set_disabled(true);
+        }
+        focus.validate(&mut views);
+        assert_eq!(focus.focused(), None);
+        assert!(!views[0].element().state().focused);
+
+        // --- gone altogether
+        views[0] = tabbable(None).into_view();
+        assert!(focus.focus(&mut views, Some(vec![0])));
+        views.clear();
+        focus.validate(&mut views);
+        assert_eq!(focus.focused(), None);
+    }
+}
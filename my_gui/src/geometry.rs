@@ -0,0 +1,381 @@
+use crate::style::Edges;
+
+// --- everything here is in physical pixels, origin at the top-left of the window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // --- same ordering as `Button::position`: [top, left, bottom, right]
+    pub fn from_edges(position: [u32; 4]) -> Self {
+        let [top, left, bottom, right] = position.map(|i| i as f32);
+        Self::new(left, top, right - left, bottom - top)
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn translate(&self, dx: f32, dy: f32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.width, self.height)
+    }
+
+    pub fn inset(&self, edges: Edges) -> Self {
+        Self::new(
+            self.x + edges.left,
+            self.y + edges.top,
+            (self.width - edges.left - edges.right).max(0.),
+            (self.height - edges.top - edges.bottom).max(0.),
+        )
+    }
+
+    // --- the overlap of both, empty (but positioned) when they don't meet
+    pub fn intersect(&self, other: &Rect) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Self::new(
+            x,
+            y,
+            (self.right().min(other.right()) - x).max(0.),
+            (self.bottom().min(other.bottom()) - y).max(0.),
+        )
+    }
+
+    // --- the smallest rect holding both
+    pub fn union(&self, other: &Rect) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    // --- whether they share any area, touching edges don't count
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
+
+// --- an affine map like SVG's `matrix(a b c d e f)`: x' = a x + c y + e, y' = b x + d y + f
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self::new(1., 0., 0., 1., 0., 0.);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self::new(1., 0., 0., 1., x, y)
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0., 0., y, 0., 0.)
+    }
+
+    // --- radians, clockwise on screen
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0., 0.)
+    }
+
+    // --- radians the vertical & horizontal lines are tilted by
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(1., y.tan(), x.tan(), 1., 0., 0.)
+    }
+
+    // --- this one first, then `next`
+    pub fn then(&self, next: &Self) -> Self {
+        Self::new(
+            next.a * self.a + next.c * self.b,
+            next.b * self.a + next.d * self.b,
+            next.a * self.c + next.c * self.d,
+            next.b * self.c + next.d * self.d,
+            next.a * self.e + next.c * self.f + next.e,
+            next.b * self.e + next.d * self.f + next.f,
+        )
+    }
+
+    pub fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        ]
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    // --- `None` when it flattens everything onto a line or a point
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some(Self::new(
+            self.d / det,
+            -self.b / det,
+            -self.c / det,
+            self.a / det,
+            (self.c * self.f - self.d * self.e) / det,
+            (self.b * self.e - self.a * self.f) / det,
+        ))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    // --- only moves things, rects stay rects of the same size
+    pub fn is_translation(&self) -> bool {
+        (self.a, self.b, self.c, self.d) == (1., 0., 0., 1.)
+    }
+
+    // --- the smallest rect holding all four corners once mapped
+    pub fn map_rect(&self, rect: &Rect) -> Rect {
+        let corners = [
+            [rect.x, rect.y],
+            [rect.right(), rect.y],
+            [rect.right(), rect.bottom()],
+            [rect.x, rect.bottom()],
+        ]
+        .map(|corner| self.apply(corner));
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for [x, y] in corners {
+            min = [min[0].min(x), min[1].min(y)];
+            max = [max[0].max(x), max[1].max(y)];
+        }
+        Rect::new(min[0], min[1], max[0] - min[0], max[1] - min[1])
+    }
+
+    // --- split into translation, rotation, scale & skew, and blended part by part,
+    // --- so a turn stays a turn halfway through instead of shrinking, like CSS does
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        let (Some(from), Some(to)) = (self.decompose(), to.decompose()) else {
+            let mix = |a: f32, b: f32| a + (b - a) * t;
+            return Self::new(
+                mix(self.a, to.a),
+                mix(self.b, to.b),
+                mix(self.c, to.c),
+                mix(self.d, to.d),
+                mix(self.e, to.e),
+                mix(self.f, to.f),
+            );
+        };
+
+        // --- the short way around
+        let mut turn = to[2] - from[2];
+        if turn > std::f32::consts::PI {
+            turn -= std::f32::consts::TAU;
+        } else if turn < -std::f32::consts::PI {
+            turn += std::f32::consts::TAU;
+        }
+        let mut parts = [0.; 6];
+        for (idx, part) in parts.iter_mut().enumerate() {
+            *part = from[idx] + (to[idx] - from[idx]) * t;
+        }
+        parts[2] = from[2] + turn * t;
+
+        Self::compose(parts)
+    }
+
+    // --- [e, f, angle, scale x, scale y, skew], `None` when it's flattened to nothing
+    fn decompose(&self) -> Option<[f32; 6]> {
+        let scale_x = self.a.hypot(self.b);
+        if scale_x < 1e-6 {
+            return None;
+        }
+        let (cos, sin) = (self.a / scale_x, self.b / scale_x);
+        // --- how much of the second column goes along the first one, & across it
+        let skew = cos * self.c + sin * self.d;
+        let scale_y = cos * self.d - sin * self.c;
+
+        Some([self.e, self.f, sin.atan2(cos), scale_x, scale_y, skew])
+    }
+
+    fn compose([e, f, angle, scale_x, scale_y, skew]: [f32; 6]) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(
+            cos * scale_x,
+            sin * scale_x,
+            cos * skew - sin * scale_y,
+            sin * skew + cos * scale_y,
+            e,
+            f,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use super::*;
+
+    fn close(a: &Transform, b: &Transform) -> bool {
+        [
+            (a.a, b.a),
+            (a.b, b.b),
+            (a.c, b.c),
+            (a.d, b.d),
+            (a.e, b.e),
+            (a.f, b.f),
+        ]
+        .iter()
+        .all(|(a, b)| (a - b).abs() < 1e-4)
+    }
+
+    fn close_point(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4
+    }
+
+    fn samples() -> [Transform; 6] {
+        [
+            Transform::IDENTITY,
+            Transform::translate(12., -7.),
+            Transform::scale(2., 0.5),
+            Transform::rotate(0.7),
+            Transform::skew(0.3, -0.2),
+            Transform::new(1.5, 0.2, -0.4, 0.8, 30., 40.),
+        ]
+    }
+
+    #[test]
+    fn then_applies_in_order() {
+        let (move_right, double) = (Transform::translate(10., 0.), Transform::scale(2., 2.));
+        assert_eq!(move_right.then(&double).apply([1., 1.]), [22., 2.]);
+        assert_eq!(double.then(&move_right).apply([1., 1.]), [12., 2.]);
+
+        for a in samples() {
+            for b in samples() {
+                let p = [3., -5.];
+                assert!(close_point(a.then(&b).apply(p), b.apply(a.apply(p))));
+            }
+            assert!(close(&a.then(&Transform::IDENTITY), &a));
+            assert!(close(&Transform::IDENTITY.then(&a), &a));
+        }
+    }
+
+    #[test]
+    fn invert_round_trips() {
+        for t in samples() {
+            let inverse = t.invert().unwrap();
+            assert!(close(&t.then(&inverse), &Transform::IDENTITY), "{t:?}");
+            assert!(close(&inverse.then(&t), &Transform::IDENTITY), "{t:?}");
+            assert!(close_point(inverse.apply(t.apply([3., -5.])), [3., -5.]));
+        }
+
+        // --- undoing both is undoing the second one, then the first
+        let [_, a, b, ..] = samples();
+        let both = a.then(&b).invert().unwrap();
+        assert!(close(
+            &both,
+            &b.invert().unwrap().then(&a.invert().unwrap())
+        ));
+        assert_eq!(Transform::IDENTITY.invert(), Some(Transform::IDENTITY));
+    }
+
+    #[test]
+    fn singular_has_no_inverse() {
+        for t in [
+            Transform::scale(0., 1.),
+            Transform::scale(0., 0.),
+            Transform::new(1., 2., 2., 4., 5., 6.),
+            Transform::rotate(0.7).then(&Transform::scale(1., 0.)),
+        ] {
+            assert_eq!(t.invert(), None, "{t:?}");
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert!(Transform::default().is_identity());
+        assert!(Transform::translate(1., 2.).is_translation());
+        assert!(!Transform::translate(1., 2.).is_identity());
+        assert!(!Transform::scale(2., 2.).is_translation());
+        assert!(close_point(
+            Transform::rotate(FRAC_PI_2).apply([1., 0.]),
+            [0., 1.]
+        ));
+        assert!(close_point(
+            Transform::skew(FRAC_PI_4, 0.).apply([0., 1.]),
+            [1., 1.]
+        ));
+    }
+
+    #[test]
+    fn map_rect() {
+        let turned = Transform::rotate(FRAC_PI_2).map_rect(&Rect::new(0., 0., 20., 10.));
+        assert!(close_point([turned.x, turned.y], [-10., 0.]));
+        assert!(close_point([turned.width, turned.height], [10., 20.]));
+    }
+
+    #[test]
+    fn lerp() {
+        // --- halfway through a turn is half the turn, not a smaller square
+        let half = Transform::IDENTITY.lerp(&Transform::rotate(FRAC_PI_2), 0.5);
+        assert!(close(&half, &Transform::rotate(FRAC_PI_4)));
+
+        // --- the short way around, through 180° rather than 0°
+        let from = Transform::rotate(-170f32.to_radians());
+        let to = Transform::rotate(170f32.to_radians());
+        assert!(close(&from.lerp(&to, 0.5), &Transform::rotate(PI)));
+
+        let moved = Transform::translate(0., 0.).lerp(&Transform::translate(10., 20.), 0.25);
+        assert!(close(&moved, &Transform::translate(2.5, 5.)));
+
+        // --- nothing to split a flattened one into, its numbers are blended as they are
+        let flat = Transform::scale(0., 0.);
+        assert!(close(
+            &flat.lerp(&Transform::IDENTITY, 0.5),
+            &Transform::scale(0.5, 0.5)
+        ));
+
+        for t in samples() {
+            assert!(close(&t.lerp(&t, 0.3), &t), "{t:?}");
+        }
+    }
+}
@@ -1,41 +1,160 @@
+mod animation;
+mod clipboard;
+mod color;
+mod damage;
 mod elements;
+mod event;
+mod focus;
+mod geometry;
+mod load;
+mod path;
+mod profiler;
+mod rope;
+mod schedule;
+mod sequence;
+mod style;
+mod stylesheet;
+mod svg;
+mod text;
+mod texture;
+mod theme;
+mod uniforms;
 mod vertex;
+mod video;
 mod view;
 mod window;
 
-pub use elements::button;
+pub use animation::{Animation, Easing, Motion, Property, Repeat, Transition, Value};
+pub use clipboard::{
+    Clipboard, ClipboardBackend, ClipboardError, ClipboardImage, MemoryClipboard, SystemClipboard,
+};
+pub use elements::{
+    button, canvas, container, icon, image, scroll, text_area, text_input, video, Button, Canvas,
+    Container, ElementBase, Fit, Image, ImageStatus, Painter, Scroll, TextArea, TextInput, Video,
+};
+pub use event::{
+    ActionContext, Event, EventContext, EventKind, Ime, Listener, Outcome, Phase, WheelDelta,
+};
+pub use geometry::{Rect, Transform};
+pub use load::{decode_image, open_image, read_file, Task};
+pub use path::{FillRule, LineCap, LineJoin, Mesh, Path, Stroke};
+pub use profiler::{FramePhase, FrameStats, FrameTiming, StatsHandle};
+pub use rope::Rope;
+pub use schedule::RedrawHandle;
+pub use sequence::{RawReader, Y4mReader};
+pub use style::{
+    Border, Color, Edges, ElementState, Font, Gradient, GradientKind, Overflow, Shadow, Style,
+    Styles,
+};
+pub use stylesheet::{ParseError, Pseudo, Rule, Selector, Stylesheet};
+pub use svg::{Svg, SvgError};
+pub use text::Bitmap;
+pub use texture::{ImageError, Texture};
+pub use theme::{Palette, Radii, Spacing, Theme, ThemePreference, Typography};
+pub use video::{FrameSource, PixelFormat, TestPattern, VideoError, VideoFrame};
 pub use view::{IntoView, View};
+pub use window::PresentMode;
 
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{
+        ElementState as PressState, KeyEvent, MouseButton, MouseScrollDelta, StartCause,
+        TouchPhase, WindowEvent,
+    },
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{Key, ModifiersState, NamedKey},
     window::{Window, WindowId},
 };
 
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use focus::FocusManager;
+use profiler::Profiler;
+use schedule::Schedule;
+use style::Cascade;
+
+// --- told about every frame once it's done
+type FrameCallback = Box<dyn FnMut(&FrameTiming)>;
+
+// --- how long a frame is shown when the monitor doesn't say
+const REFRESH_INTERVAL: Duration = Duration::from_micros(16_667);
+
+// --- sent to the event loop from other threads
+pub enum UserEvent {
+    StylesheetChanged,
+    Redraw,
+}
+
 pub struct App<'a> {
-    bg_color: &'a [f64; 4],
+    bg_color: Option<&'a [f64; 4]>,
     window: window::WindowContext<'a>,
     title: &'a str,
     window_size: winit::dpi::PhysicalSize<u32>,
     components: Vec<View>,
+    style: Style,
+    hovered: Option<Vec<usize>>,
+    pressed: Option<Vec<usize>>,
+    theme: ThemePreference,
+    system_theme: winit::window::Theme,
+    stylesheet: Stylesheet,
+    stylesheet_path: Option<PathBuf>,
+    // --- one for the app, follows `stylesheet_path`
+    watcher: stylesheet::Watcher,
+    focus: FocusManager,
+    modifiers: ModifiersState,
+    // --- the focused view activated with Enter/Space, waiting for the key release
+    key_pressed: Option<Vec<usize>>,
+    cursor: [f32; 2],
+    pointer_capture: Option<Vec<usize>>,
+    // --- the view the input method is enabled for, and where its caret was last reported
+    ime_target: Option<Vec<usize>>,
+    ime_area: Option<Rect>,
+    clipboard: Clipboard,
+    // --- paces the frames, and lets app code ask for one
+    schedule: Schedule,
+    redraw: RedrawHandle,
+    // --- times every frame, and who wants to hear about it
+    profiler: Profiler,
+    on_frame: Option<FrameCallback>,
 }
 
 impl Default for App<'_> {
     fn default() -> Self {
         Self {
-            bg_color: &[1., 1., 1., 1.],
+            bg_color: None,
             window: window::WindowContext::default(),
             title: "My Basic GUI",
             window_size: winit::dpi::PhysicalSize::new(800, 600),
             components: vec![],
+            style: Style::default(),
+            hovered: None,
+            pressed: None,
+            theme: ThemePreference::default(),
+            system_theme: winit::window::Theme::Light,
+            stylesheet: Stylesheet::default(),
+            stylesheet_path: None,
+            watcher: Default::default(),
+            focus: FocusManager::default(),
+            modifiers: ModifiersState::default(),
+            key_pressed: None,
+            cursor: [0., 0.],
+            pointer_capture: None,
+            ime_target: None,
+            ime_area: None,
+            clipboard: Clipboard::default(),
+            schedule: Schedule::default(),
+            redraw: RedrawHandle::default(),
+            profiler: Profiler::default(),
+            on_frame: None,
         }
     }
 }
 
 impl<'a> App<'a> {
+    // --- overrides the theme's background
     pub fn set_bg_color(&mut self, input: &'a [f64; 4]) {
-        self.bg_color = input;
+        self.bg_color = Some(input);
     }
 
     pub fn set_title(&mut self, title: &'a str) {
@@ -46,21 +165,508 @@ impl<'a> App<'a> {
         self.window_size = winit::dpi::PhysicalSize::new(width, height);
     }
 
+    // --- multisampling: 1 (off), 2, 4 (the default) or 8 samples per pixel,
+    // --- smoothing the edges of paths & transformed views
+    pub fn set_sample_count(&mut self, count: u32) {
+        self.window.set_sample_count(count);
+        self.request_redraw();
+    }
+
+    // --- what's actually used, the adapter may not do as many as asked for
+    pub fn sample_count(&self) -> u32 {
+        self.window.sample_count()
+    }
+
+    // --- vsync by default, falls back to it when the surface can't do what's asked for
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        self.window.set_present_mode(mode);
+        self.request_redraw();
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        self.window.present_mode()
+    }
+
+    // --- at most this many frames a second, while animating or not.
+    // --- `None` (the default) leaves it to the present mode
+    pub fn set_max_fps(&mut self, fps: Option<u32>) {
+        self.schedule.set_max_fps(fps);
+    }
+
+    pub fn max_fps(&self) -> Option<u32> {
+        self.schedule.max_fps()
+    }
+
+    // --- for asking for frames from other threads, e.g. once a task is done
+    pub fn redraw_handle(&self) -> RedrawHandle {
+        self.redraw.clone()
+    }
+
+    // --- a graph of the last frames' times in the top right corner, over everything
+    pub fn set_frame_overlay(&mut self, show: bool) {
+        self.profiler.set_overlay(show);
+        self.request_redraw();
+    }
+
+    pub fn frame_overlay(&self) -> bool {
+        self.profiler.overlay()
+    }
+
+    // --- what a frame should take at most, 60Hz by default. frames over it count
+    // --- against it in the stats, and show up red in the overlay
+    pub fn set_frame_budget(&mut self, budget: Duration) {
+        self.profiler.set_budget(budget);
+        self.request_redraw();
+    }
+
+    pub fn frame_budget(&self) -> Duration {
+        self.profiler.budget()
+    }
+
+    // --- times the render pass on the GPU too, when the adapter can
+    pub fn set_gpu_timing(&mut self, on: bool) {
+        self.window.set_gpu_timing(on);
+    }
+
+    pub fn gpu_timing(&self) -> bool {
+        self.window.gpu_timing()
+    }
+
+    pub fn frame_stats(&self) -> FrameStats {
+        self.profiler.stats()
+    }
+
+    // --- the last frames' timings, oldest first
+    pub fn frame_timings(&self) -> Vec<FrameTiming> {
+        self.profiler.timings().copied().collect()
+    }
+
+    // --- for reading the stats while the app runs, e.g. from a listener
+    pub fn frame_stats_handle(&self) -> StatsHandle {
+        self.profiler.handle()
+    }
+
+    // --- called with every frame's timing once it's done
+    pub fn on_frame(&mut self, f: impl FnMut(&FrameTiming) + 'static) {
+        self.on_frame = Some(Box::new(f));
+    }
+
+    // --- the style every top-level component inherits from
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = ThemePreference::Fixed(Box::new(theme));
+        self.request_redraw();
+    }
+
+    // --- switches between the two as the OS dark/light preference changes
+    pub fn follow_system_theme(&mut self, light: Theme, dark: Theme) {
+        self.theme = ThemePreference::System {
+            light: Box::new(light),
+            dark: Box::new(dark),
+        };
+        self.request_redraw();
+    }
+
+    pub fn theme(&self) -> &Theme {
+        self.theme.resolve(self.system_theme)
+    }
+
+    pub fn set_stylesheet(&mut self, stylesheet: Stylesheet) {
+        self.stylesheet = stylesheet;
+        self.stylesheet_path = None;
+        self.watcher.set_path(None);
+        self.request_redraw();
+    }
+
+    // --- the file is watched while the app runs, every save re-applies it
+    pub fn load_stylesheet(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.into();
+        self.stylesheet = Stylesheet::load(&path)?;
+
+        self.watcher.set_path(Some(path.clone()));
+        self.stylesheet_path = Some(path);
+        self.request_redraw();
+
+        Ok(())
+    }
+
+    fn reload_stylesheet(&mut self) {
+        let Some(path) = self.stylesheet_path.as_ref() else {
+            return;
+        };
+
+        match Stylesheet::load(path) {
+            Ok(stylesheet) => {
+                log::info!("Reloaded stylesheet {}", path.display());
+                self.stylesheet = stylesheet;
+                self.request_redraw();
+            }
+            // --- keep the last good one, the file is probably mid-edit
+            Err(err) => log::error!("Stylesheet {}: {}", path.display(), err),
+        }
+    }
+
+    fn root_style(&self) -> Style {
+        let theme = self.theme();
+
+        Style::new()
+            .with_foreground(theme.palette.text)
+            .with_font(theme.typography.font(theme.typography.body))
+            .merge(&self.style)
+    }
+
+    fn bg_color(&self) -> [f64; 4] {
+        match self.bg_color {
+            Some(color) => *color,
+            None => self.theme().palette.background.map(|i| i as f64),
+        }
+    }
+
+    // --- how long the monitor shows each frame
+    fn refresh_interval(&self) -> Duration {
+        let monitor = self
+            .window
+            .window
+            .as_ref()
+            .and_then(Window::current_monitor);
+        monitor
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .filter(|millihertz| *millihertz > 0)
+            .map_or(REFRESH_INTERVAL, |millihertz| {
+                Duration::from_secs_f64(1000. / millihertz as f64)
+            })
+    }
+
+    // --- frames are only drawn when something changed, this is how app code says it did
+    pub fn request_redraw(&self) {
+        if let Some(window) = self.window.window.as_ref() {
+            window.request_redraw();
+        }
+    }
+
+    // --- shared with the text elements for cut, copy & paste
+    pub fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    // --- e.g. `Clipboard::memory()` to keep headless runs off the system clipboard
+    pub fn set_clipboard(&mut self, clipboard: Clipboard) {
+        self.clipboard = clipboard;
+    }
+
     pub fn add_components(&mut self, component: impl IntoView + 'static) {
-        self.components.push(component.into_view())
+        self.components.push(component.into_view());
+        self.focus.validate(&mut self.components);
+    }
+
+    fn update_hover(&mut self, x: f32, y: f32) {
+        let root = self.root_style();
+        let cascade = Cascade {
+            theme: self.theme(),
+            stylesheet: &self.stylesheet,
+            root: &root,
+            now: Instant::now(),
+        };
+        let hit = view::hit_test(&self.components, &cascade, x, y);
+        let (path, style) = hit.unzip();
+
+        if path == self.hovered {
+            return;
+        }
+
+        if let Some(old) = self.hovered.take() {
+            view::update_along(&mut self.components, &old, |state| state.hovered = false);
+        }
+        if let Some(new) = path.as_ref() {
+            view::update_along(&mut self.components, new, |state| state.hovered = true);
+        }
+        self.hovered = path;
+
+        if let Some(window) = self.window.window.as_ref() {
+            let cursor = style.and_then(|s| s.cursor).unwrap_or_default();
+            window.set_cursor(cursor);
+            window.request_redraw();
+        }
+    }
+
+    fn dispatch(&mut self, target: Option<Vec<usize>>, event: Event) -> Outcome {
+        let Some(target) = target else {
+            return Outcome::default();
+        };
+
+        let placements = self.placements(&target);
+        let outcome = event::dispatch(
+            &mut self.components,
+            &target,
+            &placements,
+            &self.clipboard,
+            &event,
+        );
+        match outcome.pointer_capture {
+            Some(true) => self.pointer_capture = Some(target),
+            Some(false) => self.pointer_capture = None,
+            None => (),
+        }
+
+        // --- listeners & default actions may have changed anything
+        if outcome.handled {
+            self.request_redraw();
+        }
+        outcome
+    }
+
+    fn placements(&self, path: &[usize]) -> Vec<view::Placement> {
+        let root = self.root_style();
+        let cascade = Cascade {
+            theme: self.theme(),
+            stylesheet: &self.stylesheet,
+            root: &root,
+            now: Instant::now(),
+        };
+        view::placements(&self.components, &cascade, path)
+    }
+
+    // --- the input method follows the focus: enabled while the focused view takes text,
+    // --- with its candidate window kept next to the caret
+    fn update_ime(&mut self) {
+        let focused = self.focus.focused().map(<[usize]>::to_vec);
+        let area = focused.as_ref().and_then(|path| {
+            let placement = self.placements(path).pop()?;
+            let view = view::view_at(&self.components, path)?;
+            let area = view.element().ime_cursor_area(&placement)?;
+            Some(placement.transform.map_rect(&area))
+        });
+        let target = area.and(focused);
+
+        // --- whatever the old target was composing is dropped
+        if self.ime_target.is_some() && self.ime_target != target {
+            let old = self.ime_target.take();
+            self.dispatch(old, Event::Ime(Ime::Disabled));
+        }
+
+        let Some(window) = self.window.window.as_ref() else {
+            return;
+        };
+        if target.is_some() != self.ime_target.is_some() {
+            window.set_ime_allowed(target.is_some());
+        }
+        if let Some(rect) = area.filter(|rect| Some(*rect) != self.ime_area) {
+            window.set_ime_cursor_area(
+                winit::dpi::PhysicalPosition::new(rect.x, rect.y),
+                winit::dpi::PhysicalSize::new(rect.width, rect.height),
+            );
+        }
+
+        self.ime_target = target;
+        self.ime_area = area;
+    }
+
+    fn ime(&mut self, ime: winit::event::Ime) {
+        // --- winit counts bytes, everything here counts chars
+        let chars = |text: &str, byte: usize| text[..byte].chars().count();
+        let ime = match ime {
+            winit::event::Ime::Enabled => Ime::Enabled,
+            winit::event::Ime::Preedit(text, cursor) => Ime::Preedit {
+                cursor: cursor.map(|(start, end)| chars(&text, start)..chars(&text, end)),
+                text,
+            },
+            winit::event::Ime::Commit(text) => Ime::Commit(text),
+            winit::event::Ime::Disabled => Ime::Disabled,
+        };
+
+        let focused = self.focus.focused().map(<[usize]>::to_vec);
+        self.dispatch(focused, Event::Ime(ime));
+    }
+
+    // --- the captured view if there's one, otherwise whatever is under the cursor
+    fn pointer_target(&self) -> Option<Vec<usize>> {
+        self.pointer_capture
+            .clone()
+            .or_else(|| self.hovered.clone())
+    }
+
+    fn pointer_moved(&mut self, x: f32, y: f32) {
+        self.cursor = [x, y];
+        self.update_hover(x, y);
+        self.dispatch(self.pointer_target(), Event::PointerMove { x, y });
+    }
+
+    fn pointer_button(&mut self, button: MouseButton, pressed: bool) {
+        let [x, y] = self.cursor;
+        let target = self.pointer_target();
+
+        if pressed {
+            let outcome = self.dispatch(target.clone(), Event::PointerDown { x, y, button });
+            if button != MouseButton::Left {
+                return;
+            }
+
+            // --- the pressed view keeps getting the pointer until release, so drags work
+            if outcome.pointer_capture.is_none() && self.pointer_capture.is_none() {
+                self.pointer_capture = target;
+            }
+
+            // --- clicking moves the focus to the nearest focusable view, or away
+            if !outcome.default_prevented {
+                let focus = self.hovered.clone().and_then(|mut path| {
+                    while !path.is_empty() {
+                        let view = view::view_at(&self.components, &path);
+                        if view.is_some_and(|view| view.element().focusable()) {
+                            return Some(path);
+                        }
+                        path.pop();
+                    }
+                    None
+                });
+                self.focus.focus(&mut self.components, focus);
+            }
+
+            if let Some(path) = self.hovered.clone() {
+                view::update_along(&mut self.components, &path, |state| {
+                    state.active = !state.disabled
+                });
+                self.pressed = Some(path);
+            }
+        } else {
+            self.dispatch(target, Event::PointerUp { x, y, button });
+            if button != MouseButton::Left {
+                return;
+            }
+            self.pointer_capture = None;
+
+            let released = self.pressed.take();
+            if let Some(old) = released.as_ref() {
+                view::update_along(&mut self.components, old, |state| state.active = false);
+            }
+
+            // --- only a click when released over the same view it started on
+            if released.is_some() && released == self.hovered {
+                self.dispatch(released, Event::Click { x, y });
+            }
+        }
+    }
+
+    fn wheel(&mut self, delta: WheelDelta, phase: TouchPhase) {
+        let [x, y] = self.cursor;
+        let event = Event::Wheel { x, y, delta, phase };
+        self.dispatch(self.hovered.clone(), event);
+    }
+
+    fn keyboard_input(&mut self, event: KeyEvent) {
+        let focused = self.focus.focused().map(<[usize]>::to_vec);
+        let modifiers = self.modifiers;
+        let pressed = event.state == PressState::Pressed;
+
+        let dispatched = if pressed {
+            Event::KeyDown {
+                key: event.logical_key.clone(),
+                text: event.text.as_ref().map(|t| t.to_string()),
+                repeat: event.repeat,
+                modifiers,
+            }
+        } else {
+            Event::KeyUp {
+                key: event.logical_key.clone(),
+                modifiers,
+            }
+        };
+        let outcome = self.dispatch(focused.clone(), dispatched);
+        if outcome.default_prevented || outcome.default_handled {
+            return;
+        }
+
+        match event.logical_key {
+            Key::Named(NamedKey::Tab)
+                if pressed
+                    && self
+                        .focus
+                        .traverse(&mut self.components, modifiers.shift_key()) =>
+            {
+                self.request_redraw();
+            }
+            Key::Named(NamedKey::Enter | NamedKey::Space) => {
+                if pressed && !event.repeat {
+                    if let Some(path) = focused {
+                        if let Some(view) = view::view_at_mut(&mut self.components, &path) {
+                            let state = view.element_mut().state_mut();
+                            state.active = !state.disabled;
+                        }
+                        self.key_pressed = Some(path);
+                    }
+                } else if !pressed {
+                    if let Some(path) = self.key_pressed.take() {
+                        if let Some(view) = view::view_at_mut(&mut self.components, &path) {
+                            view.element_mut().state_mut().active = false;
+                        }
+                        let [x, y] = self.cursor;
+                        self.dispatch(Some(path), Event::Click { x, y });
+                    }
+                }
+                self.request_redraw();
+            }
+            _ => (),
+        }
+    }
+
+    // --- focuses the view with this id, if it's focusable
+    pub fn focus(&mut self, id: &str) -> bool {
+        let path = focus::find_by_id(&self.components, id);
+        let moved = path.is_some() && self.focus.focus(&mut self.components, path);
+        if moved {
+            self.request_redraw();
+        }
+        moved
+    }
+
+    pub fn focus_next(&mut self) -> bool {
+        let moved = self.focus.traverse(&mut self.components, false);
+        if moved {
+            self.request_redraw();
+        }
+        moved
+    }
+
+    pub fn focus_previous(&mut self) -> bool {
+        let moved = self.focus.traverse(&mut self.components, true);
+        if moved {
+            self.request_redraw();
+        }
+        moved
+    }
+
+    pub fn blur(&mut self) {
+        if self.focus.focus(&mut self.components, None) {
+            self.request_redraw();
+        }
+    }
+
+    pub fn focused_id(&self) -> Option<&str> {
+        let path = self.focus.focused()?;
+        view::view_at(&self.components, path)?.element().id()
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         env_logger::init();
 
-        let event_loop = EventLoop::new()?;
+        let event_loop = EventLoop::<UserEvent>::with_user_event().build()?;
         event_loop.set_control_flow(ControlFlow::Wait);
 
+        let proxy = event_loop.create_proxy();
+        self.watcher.start(proxy.clone());
+        self.redraw.connect(proxy);
+        load::wake_with(self.redraw.clone());
+
         event_loop.run_app(self).map_err(|err| err.into())
     }
 }
 
-impl<'a> ApplicationHandler for App<'a> {
+impl<'a> ApplicationHandler<UserEvent> for App<'a> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let window = event_loop
             .create_window(
@@ -71,25 +677,101 @@ impl<'a> ApplicationHandler for App<'a> {
             )
             .unwrap();
 
+        if let Some(theme) = window.theme() {
+            self.system_theme = theme;
+        }
+
         self.window.set_window(window);
-        self.window.set_bg_color(self.bg_color);
         self.window.init();
     }
 
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        if let StartCause::ResumeTimeReached { .. } = cause {
+            self.request_redraw();
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        let started = Instant::now();
+        match event {
+            UserEvent::StylesheetChanged => self.reload_stylesheet(),
+            UserEvent::Redraw => self.request_redraw(),
+        }
+        self.profiler.event(started.elapsed());
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        // --- everything but drawing counts towards the next frame's event handling
+        let started = Instant::now();
+        let redraw = matches!(event, WindowEvent::RedrawRequested);
+
         match event {
             WindowEvent::CloseRequested => {
                 log::info!("Close button was pressed, stopping...");
                 event_loop.exit();
             }
-            WindowEvent::RedrawRequested => match self.window.render(&self.components) {
-                Ok(_) => (),
-                Err(wgpu::SurfaceError::Lost) => self.window.resize(self.window.size.unwrap()),
-                Err(wgpu::SurfaceError::OutOfMemory) => {
-                    event_loop.exit();
+            WindowEvent::RedrawRequested => {
+                // --- too soon after the last frame, it's drawn once the cap allows
+                let now = Instant::now();
+                if let Some(due) = self.schedule.wait(now) {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(due));
+                    return;
                 }
-                Err(err) => log::error!("Render Error: {:?}", err),
-            },
+                self.schedule.frame(now);
+                self.profiler.begin(now);
+
+                self.window.set_bg_color(self.bg_color());
+                self.update_ime();
+
+                let root = self.root_style();
+                let cascade = Cascade {
+                    theme: self.theme.resolve(self.system_theme),
+                    stylesheet: &self.stylesheet,
+                    root: &root,
+                    now,
+                };
+
+                let running = view::update_motion(&mut self.components, &cascade);
+                self.profiler.lap(FramePhase::Layout);
+
+                let frame = self
+                    .window
+                    .render(&self.components, &cascade, &mut self.profiler);
+                let presented = match frame {
+                    Ok(presented) => presented,
+                    Err(wgpu::SurfaceError::Lost) => {
+                        self.window.resize(self.window.size.unwrap());
+                        false
+                    }
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        event_loop.exit();
+                        false
+                    }
+                    Err(err) => {
+                        log::error!("Render Error: {:?}", err);
+                        false
+                    }
+                };
+
+                // --- keep drawing frames while something moves, animations or video, paced by
+                // --- the cap, or else the present mode. a frame that changed nothing isn't
+                // --- presented & paces nothing, the next one waits for the display's refresh
+                let flow = match (running, self.schedule.next(now)) {
+                    (false, _) => ControlFlow::Wait,
+                    (true, Some(due)) => ControlFlow::WaitUntil(due),
+                    (true, None) if presented => {
+                        self.request_redraw();
+                        ControlFlow::Wait
+                    }
+                    (true, None) => ControlFlow::WaitUntil(now + self.refresh_interval()),
+                };
+                event_loop.set_control_flow(flow);
+
+                let timing = self.profiler.end(presented);
+                if let (Some(timing), Some(f)) = (timing, self.on_frame.as_mut()) {
+                    f(&timing);
+                }
+            }
             WindowEvent::Resized(physical_size) => {
                 self.window.resize(physical_size);
                 self.window.window.as_ref().unwrap().request_redraw();
@@ -103,8 +785,40 @@ impl<'a> ApplicationHandler for App<'a> {
                 self.window.resize(new_size);
                 self.window.window.as_ref().unwrap().request_redraw();
             }
+            WindowEvent::ThemeChanged(theme) => {
+                log::info!("System theme changed to {:?}", theme);
+                self.system_theme = theme;
+                self.request_redraw();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_moved(position.x as f32, position.y as f32);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.update_hover(f32::NEG_INFINITY, f32::NEG_INFINITY);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.pointer_button(button, state == PressState::Pressed);
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => WheelDelta::Lines(x, y),
+                    MouseScrollDelta::PixelDelta(p) => WheelDelta::Pixels(p.x as f32, p.y as f32),
+                };
+                self.wheel(delta, phase);
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                self.keyboard_input(event);
+            }
+            WindowEvent::Ime(ime) => self.ime(ime),
             // ------------------------------------------------
             _ => (),
         }
+
+        if !redraw {
+            self.profiler.event(started.elapsed());
+        }
     }
 }
@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::schedule::RedrawHandle;
+use crate::text::Bitmap;
+use crate::texture::{self, ImageError};
+
+// --- asked for a frame whenever a task is done, once the app runs
+static WAKER: Mutex<Option<RedrawHandle>> = Mutex::new(None);
+
+pub(crate) fn wake_with(redraw: RedrawHandle) {
+    *WAKER.lock().unwrap() = Some(redraw);
+}
+
+// --- work done on its own thread, e.g. reading & decoding a file, so the UI never waits on it.
+// --- clones share the result, a frame is asked for once it's there to pick it up in
+pub struct Task<T> {
+    result: Arc<OnceLock<T>>,
+}
+
+impl<T> Clone for Task<T> {
+    fn clone(&self) -> Self {
+        Self {
+            result: self.result.clone(),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Task<T> {
+    pub fn spawn(work: impl FnOnce() -> T + Send + 'static) -> Self {
+        let result = Arc::new(OnceLock::new());
+        let slot = result.clone();
+
+        std::thread::spawn(move || {
+            let _ = slot.set(work());
+            if let Some(redraw) = WAKER.lock().unwrap().as_ref() {
+                redraw.request_redraw();
+            }
+        });
+
+        Self { result }
+    }
+
+    // --- `None` until the work is done
+    pub fn get(&self) -> Option<&T> {
+        self.result.get()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.result.get().is_some()
+    }
+}
+
+// --- the whole file, e.g. a font to hand to a rasterizer
+pub fn read_file(path: impl Into<PathBuf>) -> Task<std::io::Result<Vec<u8>>> {
+    let path = path.into();
+    Task::spawn(move || std::fs::read(path))
+}
+
+pub fn decode_image(bytes: Vec<u8>) -> Task<Result<Bitmap, ImageError>> {
+    Task::spawn(move || texture::decode(&bytes))
+}
+
+pub fn open_image(path: impl AsRef<Path>) -> Task<Result<Bitmap, ImageError>> {
+    let path = path.as_ref().to_path_buf();
+    Task::spawn(move || texture::decode(&std::fs::read(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    fn finish<T: Send + Sync + 'static>(task: &Task<T>) -> &T {
+        while !task.is_done() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        task.get().unwrap()
+    }
+
+    fn png(width: u32, height: u32, rgba: [u8; 4]) -> Vec<u8> {
+        let pixels = image::RgbaImage::from_pixel(width, height, image::Rgba(rgba));
+        let mut bytes = Cursor::new(vec![]);
+        pixels
+            .write_to(&mut bytes, image::ImageFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn decode() {
+        let task = decode_image(png(2, 3, [10, 20, 30, 40]));
+        // --- clones share the one result
+        let clone = task.clone();
+
+        let bitmap = finish(&task).as_ref().unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (2, 3));
+        assert_eq!(bitmap.rgba, [10, 20, 30, 40].repeat(6));
+        assert!(clone.is_done());
+        assert_eq!(clone.get().unwrap().as_ref().unwrap(), bitmap);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let task = decode_image(b"not an image".to_vec());
+        assert!(matches!(finish(&task), Err(ImageError::Decode(_))));
+
+        let mut cut = png(4, 4, [0; 4]);
+        cut.truncate(cut.len() / 2);
+        let task = decode_image(cut);
+        assert!(matches!(finish(&task), Err(ImageError::Decode(_))));
+
+        let task = open_image("does/not/exist.png");
+        assert!(matches!(finish(&task), Err(ImageError::Io(_))));
+    }
+}
@@ -0,0 +1,895 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::geometry::{Rect, Transform};
+use crate::style::Color;
+use crate::vertex::Vertex;
+
+// --- px, how far a flattened curve may stray from the real one
+const TOLERANCE: f32 = 0.25;
+// --- a miter longer than this many half widths is cut off like a bevel
+const MITER_LIMIT: f32 = 4.;
+// --- px, the rounded box the fragment shader sees for mesh triangles
+const MESH_BOX: f32 = 1e6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verb {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo([f32; 2], [f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+// --- a shape made of lines & curves, in px. several subpaths make holes or separate pieces
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    verbs: Vec<Verb>,
+    // --- where the current subpath started & the pen is now
+    start: [f32; 2],
+    current: Option<[f32; 2]>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.verbs.push(Verb::MoveTo([x, y]));
+        self.start = [x, y];
+        self.current = Some([x, y]);
+        self
+    }
+
+    // --- without a current point these start a subpath where they'd end
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+        self.verbs.push(Verb::LineTo([x, y]));
+        self.current = Some([x, y]);
+        self
+    }
+
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(cx, cy);
+        }
+        self.verbs.push(Verb::QuadTo([cx, cy], [x, y]));
+        self.current = Some([x, y]);
+        self
+    }
+
+    pub fn cubic_to(
+        &mut self,
+        c1x: f32,
+        c1y: f32,
+        c2x: f32,
+        c2y: f32,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(c1x, c1y);
+        }
+        self.verbs
+            .push(Verb::CubicTo([c1x, c1y], [c2x, c2y], [x, y]));
+        self.current = Some([x, y]);
+        self
+    }
+
+    // --- angles in radians, clockwise on screen from the positive x axis.
+    // --- a line joins the pen to where the arc starts, like canvas' `arc`
+    pub fn arc(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        start: f32,
+        end: f32,
+        counter_clockwise: bool,
+    ) -> &mut Self {
+        let mut sweep = end - start;
+        if counter_clockwise {
+            if sweep > 0. {
+                sweep = sweep % TAU - TAU;
+            }
+            sweep = sweep.max(-TAU);
+        } else {
+            if sweep < 0. {
+                sweep = sweep % TAU + TAU;
+            }
+            sweep = sweep.min(TAU);
+        }
+
+        let point = |angle: f32| [cx + radius * angle.cos(), cy + radius * angle.sin()];
+        let [x, y] = point(start);
+        match self.current {
+            Some(_) => self.line_to(x, y),
+            None => self.move_to(x, y),
+        };
+
+        // --- a cubic per quarter turn at most stays well within the tolerance
+        let pieces = (sweep.abs() / (PI / 2.)).ceil().max(1.) as usize;
+        let step = sweep / pieces as f32;
+        let k = 4. / 3. * (step / 4.).tan() * radius;
+        for i in 0..pieces {
+            let (a0, a1) = (start + step * i as f32, start + step * (i + 1) as f32);
+            let [x0, y0] = point(a0);
+            let [x1, y1] = point(a1);
+            self.cubic_to(
+                x0 - k * a0.sin(),
+                y0 + k * a0.cos(),
+                x1 + k * a1.sin(),
+                y1 - k * a1.cos(),
+                x1,
+                y1,
+            );
+        }
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.verbs.push(Verb::Close);
+            self.current = Some(self.start);
+        }
+        self
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) -> &mut Self {
+        self.move_to(x, y)
+            .line_to(x + width, y)
+            .line_to(x + width, y + height)
+            .line_to(x, y + height)
+            .close()
+    }
+
+    pub fn circle(&mut self, cx: f32, cy: f32, radius: f32) -> &mut Self {
+        self.move_to(cx + radius, cy);
+        self.arc(cx, cy, radius, 0., TAU, false).close()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verbs.is_empty()
+    }
+
+    // --- every point mapped through it, curves stay curves
+    pub fn transform(&self, transform: &Transform) -> Self {
+        let map = |p: [f32; 2]| transform.apply(p);
+        Self {
+            verbs: self
+                .verbs
+                .iter()
+                .map(|verb| match *verb {
+                    Verb::MoveTo(p) => Verb::MoveTo(map(p)),
+                    Verb::LineTo(p) => Verb::LineTo(map(p)),
+                    Verb::QuadTo(c, p) => Verb::QuadTo(map(c), map(p)),
+                    Verb::CubicTo(c1, c2, p) => Verb::CubicTo(map(c1), map(c2), map(p)),
+                    Verb::Close => Verb::Close,
+                })
+                .collect(),
+            start: map(self.start),
+            current: self.current.map(map),
+        }
+    }
+
+    // --- the smallest rect around the flattened outline, `None` for an empty path
+    pub fn bounds(&self) -> Option<Rect> {
+        let points = self.flatten().into_iter().flat_map(|(points, _)| points);
+        let [l, t, r, b] = points.fold(
+            [
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ],
+            |[l, t, r, b], [x, y]| [l.min(x), t.min(y), r.max(x), b.max(y)],
+        );
+        (l <= r && t <= b).then(|| Rect::new(l, t, r - l, b - t))
+    }
+
+    // --- every subpath as a polyline, and whether it's closed
+    pub fn flatten(&self) -> Vec<(Vec<[f32; 2]>, bool)> {
+        let mut subpaths = Vec::new();
+        let mut points: Vec<[f32; 2]> = Vec::new();
+        let mut start = [0., 0.];
+
+        let mut finish = |points: &mut Vec<[f32; 2]>, closed: bool| {
+            points.dedup();
+            if closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            if !points.is_empty() {
+                subpaths.push((std::mem::take(points), closed));
+            }
+        };
+
+        for verb in &self.verbs {
+            let last = points.last().copied().unwrap_or(start);
+            match *verb {
+                Verb::MoveTo(p) => {
+                    // --- a pen that was only moved draws nothing
+                    match points.len() > 1 {
+                        true => finish(&mut points, false),
+                        false => points.clear(),
+                    }
+                    start = p;
+                    points.push(p);
+                }
+                Verb::LineTo(p) => points.push(p),
+                Verb::QuadTo(c, p) => {
+                    let dd = length(sub(add(last, p), scale(c, 2.)));
+                    let n = segments(0.25 * dd);
+                    points.extend((1..=n).map(|i| {
+                        let t = i as f32 / n as f32;
+                        let mt = 1. - t;
+                        add(
+                            add(scale(last, mt * mt), scale(c, 2. * mt * t)),
+                            scale(p, t * t),
+                        )
+                    }));
+                }
+                Verb::CubicTo(c1, c2, p) => {
+                    let dd = length(sub(add(last, c2), scale(c1, 2.)))
+                        .max(length(sub(add(c1, p), scale(c2, 2.))));
+                    let n = segments(0.75 * dd);
+                    points.extend((1..=n).map(|i| {
+                        let t = i as f32 / n as f32;
+                        let mt = 1. - t;
+                        add(
+                            add(scale(last, mt * mt * mt), scale(c1, 3. * mt * mt * t)),
+                            add(scale(c2, 3. * mt * t * t), scale(p, t * t * t)),
+                        )
+                    }));
+                }
+                Verb::Close => {
+                    match points.len() > 1 {
+                        true => finish(&mut points, true),
+                        false => points.clear(),
+                    }
+                    points.push(start);
+                }
+            }
+        }
+        // --- a close leaves the pen at the start, that alone isn't a subpath
+        if points.len() > 1 {
+            finish(&mut points, false);
+        }
+
+        subpaths
+    }
+}
+
+// --- how many lines a curve is cut into, from Wang's formula
+fn segments(deviation: f32) -> usize {
+    ((deviation / TOLERANCE).sqrt().ceil() as usize).clamp(1, 256)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FillRule {
+    // --- inside where the outlines around a point don't cancel out
+    #[default]
+    NonZero,
+    // --- inside where an odd number of outlines go around a point
+    EvenOdd,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    // --- goes past the end by half the width
+    Square,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    // --- lengths of the dashes & the gaps between them, taking turns. empty for a solid line
+    pub dashes: Vec<f32>,
+    // --- how far into the pattern the line starts
+    pub dash_offset: f32,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            dashes: vec![],
+            dash_offset: 0.,
+        }
+    }
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_dashes(mut self, dashes: impl Into<Vec<f32>>, offset: f32) -> Self {
+        self.dashes = dashes.into();
+        self.dash_offset = offset;
+        self
+    }
+}
+
+// --- triangles in window coordinates, all of one color
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub points: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    pub color: Color,
+}
+
+impl Mesh {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    // --- either way around, slivers are left out
+    pub fn triangle(&mut self, a: [f32; 2], b: [f32; 2], c: [f32; 2]) {
+        if cross(sub(b, a), sub(c, a)).abs() < 1e-6 {
+            return;
+        }
+
+        let first = self.points.len() as u32;
+        self.points.extend([a, b, c]);
+        self.indices.extend([first, first + 1, first + 2]);
+    }
+
+    // --- convex ones only
+    pub fn polygon(&mut self, points: &[[f32; 2]]) {
+        for pair in points.windows(2).skip(1) {
+            self.triangle(points[0], pair[0], pair[1]);
+        }
+    }
+
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        for [x, y] in self.points.iter_mut() {
+            *x += dx;
+            *y += dy;
+        }
+    }
+
+    pub fn vertices(&self, opacity: f32, size: &winit::dpi::PhysicalSize<u32>) -> Vec<Vertex> {
+        self.points
+            .iter()
+            .map(|[x, y]| Vertex {
+                position: [
+                    x / (size.width as f32 / 2.) - 1.,
+                    1. - y / (size.height as f32 / 2.),
+                    0.,
+                ],
+                // --- the middle of a box far larger than any window, so nothing's cut off
+                tex_coords: [0.5, 0.5],
+                size: [MESH_BOX; 2],
+                radius: 0.,
+                border_width: 0.,
+                border_color: [0.; 4],
+                opacity,
+                uv: [0., 0., 1., 1.],
+                tint: [1., 1., 1., 1.],
+                gradient: [0.; 4],
+                border_gradient: [0.; 4],
+                blur: 0.,
+            })
+            .collect()
+    }
+}
+
+// --- cuts the filled area into horizontal bands, each one into trapezoids between the
+// --- edges crossing it. bands are split where edges cross so their order holds within one
+pub fn fill(path: &Path, rule: FillRule, color: Color) -> Mesh {
+    let mut mesh = Mesh::new(color);
+
+    // --- top to bottom, with which way it goes around
+    let mut edges = Vec::new();
+    for (points, _) in path.flatten() {
+        // --- fills always close the subpath
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            if a[1] == b[1] {
+                continue;
+            }
+            let (top, bottom, winding) = match a[1] < b[1] {
+                true => (*a, b, 1),
+                false => (b, *a, -1),
+            };
+            edges.push(Edge {
+                top,
+                bottom,
+                winding,
+            });
+        }
+    }
+    if edges.is_empty() {
+        return mesh;
+    }
+    edges.sort_by(|a, b| a.top[1].total_cmp(&b.top[1]));
+
+    let mut ys = edges
+        .iter()
+        .flat_map(|edge| [edge.top[1], edge.bottom[1]])
+        .collect::<Vec<_>>();
+    ys.sort_by(f32::total_cmp);
+    ys.dedup();
+
+    let mut next = 0;
+    let mut active: Vec<Edge> = Vec::new();
+    for band in ys.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        active.retain(|edge| edge.bottom[1] > y0);
+        while next < edges.len() && edges[next].top[1] <= y0 {
+            active.push(edges[next]);
+            next += 1;
+        }
+        fill_band(&mut mesh, &active, y0, y1, rule);
+    }
+
+    mesh
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: [f32; 2],
+    bottom: [f32; 2],
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top[1]) / (self.bottom[1] - self.top[1]);
+        self.top[0] + (self.bottom[0] - self.top[0]) * t
+    }
+}
+
+fn fill_band(mesh: &mut Mesh, active: &[Edge], y0: f32, y1: f32, rule: FillRule) {
+    // --- too thin to hold a pixel's worth of anything
+    if y1 - y0 < 1e-4 {
+        return;
+    }
+
+    let mid = (y0 + y1) / 2.;
+    let mut crossing = active
+        .iter()
+        .filter(|edge| edge.top[1] <= y0 && edge.bottom[1] >= y1)
+        .map(|edge| (edge.x_at(mid), edge.x_at(y0), edge.x_at(y1), edge.winding))
+        .collect::<Vec<_>>();
+    crossing.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // --- neighbours swapping places means they cross, the band is split there
+    for pair in crossing.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if a.1 > b.1 + 1e-3 || a.2 > b.2 + 1e-3 {
+            // --- where the gap between them closes, interpolated along the band
+            let (top, bottom) = (b.1 - a.1, b.2 - a.2);
+            let t = top / (top - bottom);
+            if t.is_finite() && t > 0. && t < 1. {
+                let y = y0 + (y1 - y0) * t;
+                fill_band(mesh, active, y0, y, rule);
+                fill_band(mesh, active, y, y1, rule);
+                return;
+            }
+        }
+    }
+
+    let mut winding = 0;
+    let mut left = None;
+    for (_, x0, x1, edge_winding) in crossing {
+        let was_inside = inside(winding, rule);
+        winding += edge_winding;
+        match (was_inside, inside(winding, rule)) {
+            (false, true) => left = Some((x0, x1)),
+            (true, false) => {
+                if let Some((l0, l1)) = left.take() {
+                    mesh.polygon(&[[l0, y0], [x0, y0], [x1, y1], [l1, y1]]);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn inside(winding: i32, rule: FillRule) -> bool {
+    match rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+// --- each segment, join & cap is its own convex piece, so they overlap where they meet:
+// --- a translucent stroke is a little stronger at its joins
+pub fn stroke(path: &Path, stroke: &Stroke, color: Color) -> Mesh {
+    let mut mesh = Mesh::new(color);
+    let half = stroke.width / 2.;
+    if half <= 0. {
+        return mesh;
+    }
+
+    for (points, closed) in path.flatten() {
+        let pieces = match stroke.dashes.iter().any(|dash| *dash > 0.) {
+            true => dash(&points, closed, &stroke.dashes, stroke.dash_offset),
+            false => vec![(points, closed)],
+        };
+        for (points, closed) in pieces {
+            stroke_polyline(&mut mesh, &points, closed, stroke, half);
+        }
+    }
+
+    mesh
+}
+
+fn stroke_polyline(mesh: &mut Mesh, points: &[[f32; 2]], closed: bool, stroke: &Stroke, half: f32) {
+    if points.len() < 2 {
+        // --- a dot only shows with caps that go past the ends
+        if let (Some(p), LineCap::Round) = (points.first(), stroke.cap) {
+            mesh.polygon(&circle(*p, half));
+        }
+        if let (Some(p), LineCap::Square) = (points.first(), stroke.cap) {
+            let [x, y] = *p;
+            mesh.polygon(&[
+                [x - half, y - half],
+                [x + half, y - half],
+                [x + half, y + half],
+                [x - half, y + half],
+            ]);
+        }
+        return;
+    }
+
+    let count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+
+    for i in 0..count {
+        let (mut a, mut b) = segment(i);
+        let d = normalize(sub(b, a));
+        let n = [-d[1] * half, d[0] * half];
+
+        if !closed && stroke.cap == LineCap::Square {
+            if i == 0 {
+                a = sub(a, scale(d, half));
+            }
+            if i == count - 1 {
+                b = add(b, scale(d, half));
+            }
+        }
+        mesh.polygon(&[add(a, n), add(b, n), sub(b, n), sub(a, n)]);
+    }
+
+    // --- joins at every inner point, and where a closed one meets itself
+    let joins = if closed {
+        0..points.len()
+    } else {
+        1..points.len() - 1
+    };
+    for i in joins {
+        let prev = points[(i + points.len() - 1) % points.len()];
+        let (p, next) = (points[i], points[(i + 1) % points.len()]);
+        join(mesh, prev, p, next, stroke.join, half);
+    }
+
+    if !closed && stroke.cap == LineCap::Round {
+        mesh.polygon(&circle(points[0], half));
+        mesh.polygon(&circle(points[points.len() - 1], half));
+    }
+}
+
+fn join(mesh: &mut Mesh, prev: [f32; 2], p: [f32; 2], next: [f32; 2], kind: LineJoin, half: f32) {
+    let (d0, d1) = (normalize(sub(p, prev)), normalize(sub(next, p)));
+    let turn = cross(d0, d1);
+    if turn.abs() < 1e-6 && dot(d0, d1) > 0. {
+        return;
+    }
+
+    // --- the gap opens on the outside of the turn
+    let side = if turn > 0. { -1. } else { 1. };
+    let n0 = scale([-d0[1], d0[0]], side);
+    let n1 = scale([-d1[1], d1[0]], side);
+    let (a, b) = (add(p, scale(n0, half)), add(p, scale(n1, half)));
+
+    match kind {
+        LineJoin::Round => mesh.polygon(&circle(p, half)),
+        LineJoin::Miter => {
+            let sum = add(n0, n1);
+            let len2 = dot(sum, sum);
+            // --- 2 / |n0 + n1| is how much longer than half the width the miter is
+            if len2 > 1e-6 && 2. / len2.sqrt() <= MITER_LIMIT {
+                let tip = add(p, scale(sum, 2. * half / len2));
+                mesh.polygon(&[p, a, tip, b]);
+            } else {
+                mesh.triangle(p, a, b);
+            }
+        }
+        LineJoin::Bevel => mesh.triangle(p, a, b),
+    }
+}
+
+fn circle(center: [f32; 2], radius: f32) -> Vec<[f32; 2]> {
+    let step = 2. * (1. - TOLERANCE / radius.max(TOLERANCE)).acos();
+    let n = ((TAU / step.max(1e-3)).ceil() as usize).clamp(8, 128);
+    (0..n)
+        .map(|i| {
+            let angle = TAU * i as f32 / n as f32;
+            [
+                center[0] + radius * angle.cos(),
+                center[1] + radius * angle.sin(),
+            ]
+        })
+        .collect()
+}
+
+// --- the polyline cut into the dashes of the pattern, as open polylines
+fn dash(
+    points: &[[f32; 2]],
+    closed: bool,
+    dashes: &[f32],
+    offset: f32,
+) -> Vec<(Vec<[f32; 2]>, bool)> {
+    // --- an odd pattern repeats twice so dashes & gaps keep taking turns
+    let pattern = match dashes.len() % 2 {
+        0 => dashes.to_vec(),
+        _ => [dashes, dashes].concat(),
+    };
+    let total: f32 = pattern.iter().map(|dash| dash.max(0.)).sum();
+
+    let mut idx = 0;
+    let mut left = offset.rem_euclid(total);
+    while left >= pattern[idx].max(0.) {
+        left -= pattern[idx].max(0.);
+        idx = (idx + 1) % pattern.len();
+    }
+    let mut remaining = pattern[idx].max(0.) - left;
+
+    let mut pieces = Vec::new();
+    let mut current = vec![points[0]];
+    let ends = points.iter().skip(1).chain(closed.then_some(&points[0]));
+    let mut from = points[0];
+    for &to in ends {
+        let mut a = from;
+        let mut len = length(sub(to, a));
+        while len > remaining {
+            let p = add(a, scale(sub(to, a), remaining / len));
+            if idx % 2 == 0 {
+                current.push(p);
+                pieces.push((std::mem::take(&mut current), false));
+            } else {
+                current = vec![p];
+            }
+            len -= remaining;
+            a = p;
+            idx = (idx + 1) % pattern.len();
+            remaining = pattern[idx].max(0.);
+        }
+        remaining -= len;
+        if idx % 2 == 0 {
+            current.push(to);
+        }
+        from = to;
+    }
+    if idx % 2 == 0 && current.len() > 1 {
+        pieces.push((current, false));
+    }
+
+    pieces
+        .into_iter()
+        .map(|(mut points, closed)| {
+            points.dedup();
+            (points, closed)
+        })
+        .filter(|(points, _)| !points.is_empty())
+        .collect()
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale(a: [f32; 2], s: f32) -> [f32; 2] {
+    [a[0] * s, a[1] * s]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn cross(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn length(a: [f32; 2]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: [f32; 2]) -> [f32; 2] {
+    let len = length(a);
+    match len > 0. {
+        true => scale(a, 1. / len),
+        false => [0., 0.],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = [0., 0., 0., 1.];
+
+    fn triangles(mesh: &Mesh) -> impl Iterator<Item = [[f32; 2]; 3]> + '_ {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| mesh.points[tri[i] as usize]))
+    }
+
+    // --- triangles may overlap, so only meaningful for fills
+    fn area(mesh: &Mesh) -> f32 {
+        triangles(mesh)
+            .map(|[a, b, c]| cross(sub(b, a), sub(c, a)).abs() / 2.)
+            .sum()
+    }
+
+    fn covers(mesh: &Mesh, p: [f32; 2]) -> bool {
+        triangles(mesh).any(|[a, b, c]| {
+            let sides = [
+                cross(sub(b, a), sub(p, a)),
+                cross(sub(c, b), sub(p, b)),
+                cross(sub(a, c), sub(p, c)),
+            ];
+            sides.iter().all(|side| *side >= 0.) || sides.iter().all(|side| *side <= 0.)
+        })
+    }
+
+    #[test]
+    fn fill_shapes() {
+        let mut rect = Path::new();
+        rect.rect(0., 0., 10., 20.);
+        let mesh = fill(&rect, FillRule::NonZero, BLACK);
+        assert!((area(&mesh) - 200.).abs() < 1e-3);
+        assert!(covers(&mesh, [5., 15.]));
+        assert!(!covers(&mesh, [15., 5.]));
+
+        // --- flattened within the tolerance, so between it & a circle that much smaller
+        let mut circle = Path::new();
+        circle.circle(50., 50., 10.);
+        let mesh = fill(&circle, FillRule::NonZero, BLACK);
+        let real = PI * 100.;
+        let inner = PI * (10. - TOLERANCE).powi(2);
+        assert!(area(&mesh) < real && area(&mesh) > inner);
+        assert!(!covers(&mesh, [42.5, 42.5]));
+
+        assert!(fill(&Path::new(), FillRule::NonZero, BLACK).is_empty());
+    }
+
+    #[test]
+    fn fill_rules() {
+        // --- both going the same way around
+        let mut nested = Path::new();
+        nested.rect(0., 0., 30., 30.).rect(10., 10., 10., 10.);
+
+        let mesh = fill(&nested, FillRule::NonZero, BLACK);
+        assert!((area(&mesh) - 900.).abs() < 1e-2);
+        assert!(covers(&mesh, [15., 15.]));
+
+        let mesh = fill(&nested, FillRule::EvenOdd, BLACK);
+        assert!((area(&mesh) - 800.).abs() < 1e-2);
+        assert!(!covers(&mesh, [15., 15.]));
+        assert!(covers(&mesh, [5., 15.]));
+
+        // --- the other way around cancels out
+        let mut hole = Path::new();
+        hole.rect(0., 0., 30., 30.)
+            .move_to(10., 10.)
+            .line_to(10., 20.)
+            .line_to(20., 20.)
+            .line_to(20., 10.)
+            .close();
+        let mesh = fill(&hole, FillRule::NonZero, BLACK);
+        assert!((area(&mesh) - 800.).abs() < 1e-2);
+        assert!(!covers(&mesh, [15., 15.]));
+    }
+
+    #[test]
+    fn caps() {
+        let mut line = Path::new();
+        line.move_to(0., 0.).line_to(10., 0.);
+        let stroked = |cap| stroke(&line, &Stroke::new(2.).with_cap(cap), BLACK);
+
+        let butt = stroked(LineCap::Butt);
+        assert!((area(&butt) - 20.).abs() < 1e-3);
+        assert!(covers(&butt, [5., 0.9]));
+        assert!(!covers(&butt, [-0.5, 0.]));
+
+        let square = stroked(LineCap::Square);
+        assert!((area(&square) - 24.).abs() < 1e-3);
+        assert!(covers(&square, [-0.9, 0.9]));
+        assert!(covers(&square, [10.9, -0.9]));
+
+        let round = stroked(LineCap::Round);
+        assert!(covers(&round, [-0.9, 0.]));
+        assert!(covers(&round, [10.5, 0.5]));
+        assert!(!covers(&round, [-0.9, 0.9]));
+    }
+
+    #[test]
+    fn joins() {
+        // --- right then down, the outside of the corner is up & to the right
+        let mut corner = Path::new();
+        corner.move_to(0., 0.).line_to(10., 0.).line_to(10., 10.);
+        let stroked = |join| stroke(&corner, &Stroke::new(2.).with_join(join), BLACK);
+
+        let miter = stroked(LineJoin::Miter);
+        assert!(covers(&miter, [10.9, -0.9]));
+
+        let bevel = stroked(LineJoin::Bevel);
+        assert!(covers(&bevel, [10.4, -0.4]));
+        assert!(!covers(&bevel, [10.9, -0.9]));
+
+        let round = stroked(LineJoin::Round);
+        assert!(covers(&round, [10.6, -0.6]));
+        assert!(!covers(&round, [10.9, -0.9]));
+
+        // --- too sharp for a miter, it's beveled instead
+        let mut spike = Path::new();
+        spike.move_to(0., 0.).line_to(10., 0.).line_to(0., 1.);
+        let miter = stroke(&spike, &Stroke::new(2.), BLACK);
+        assert!(!covers(&miter, [14., 0.]));
+    }
+
+    #[test]
+    fn dashes() {
+        let mut line = Path::new();
+        line.move_to(0., 0.).line_to(10., 0.);
+
+        let dashed = stroke(&line, &Stroke::new(2.).with_dashes([2., 2.], 0.), BLACK);
+        assert!((area(&dashed) - 12.).abs() < 1e-3);
+        assert!(covers(&dashed, [1., 0.]));
+        assert!(!covers(&dashed, [3., 0.]));
+        assert!(covers(&dashed, [5., 0.]));
+
+        // --- the offset shifts the pattern along
+        let shifted = stroke(&line, &Stroke::new(2.).with_dashes([2., 2.], 2.), BLACK);
+        assert!(!covers(&shifted, [1., 0.]));
+        assert!(covers(&shifted, [3., 0.]));
+    }
+}
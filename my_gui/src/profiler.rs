@@ -0,0 +1,412 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::geometry::Rect;
+use crate::style::{Color, Font};
+use crate::text::{self, TextCache};
+use crate::texture::Texture;
+
+// --- how many frames are kept for the stats & the overlay's graph
+const HISTORY: usize = 120;
+
+// --- one frame a refresh at 60Hz, until told otherwise
+const DEFAULT_BUDGET: Duration = Duration::from_micros(16_667);
+
+// --- the overlay: each frame is a bar this wide, the graph goes up to twice the budget
+const BAR_WIDTH: f32 = 2.;
+const GRAPH_HEIGHT: f32 = 64.;
+const MARGIN: f32 = 8.;
+const PADDING: f32 = 6.;
+const LABEL_SIZE: f32 = 12.;
+
+const PANEL_COLOR: Color = [0., 0., 0., 0.7];
+const BUDGET_COLOR: Color = [1., 1., 1., 0.5];
+const UNDER_COLOR: Color = [0.3, 0.85, 0.4, 1.];
+const OVER_COLOR: Color = [0.95, 0.3, 0.25, 1.];
+const LABEL_COLOR: Color = [1., 1., 1., 1.];
+
+// --- where a frame's time went on the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    // --- input & window events handled since the frame before
+    Events,
+    // --- animations, styles & layout
+    Layout,
+    // --- the draw list, and what changed in it
+    Paint,
+    // --- textures, buffers & pipelines
+    Upload,
+    // --- waiting for the surface, encoding, submitting & presenting
+    Submit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTiming {
+    pub start: Instant,
+    pub events: Duration,
+    pub layout: Duration,
+    pub paint: Duration,
+    pub upload: Duration,
+    pub submit: Duration,
+    // --- the render pass on the GPU, when timestamp queries are on. it's read back a frame
+    // --- or more later, so it's the latest one measured rather than this frame's
+    pub gpu: Option<Duration>,
+    // --- false when nothing changed and nothing was drawn
+    pub presented: bool,
+}
+
+impl FrameTiming {
+    fn new(start: Instant) -> Self {
+        Self {
+            start,
+            events: Duration::ZERO,
+            layout: Duration::ZERO,
+            paint: Duration::ZERO,
+            upload: Duration::ZERO,
+            submit: Duration::ZERO,
+            gpu: None,
+            presented: false,
+        }
+    }
+
+    pub fn phase(&self, phase: FramePhase) -> Duration {
+        match phase {
+            FramePhase::Events => self.events,
+            FramePhase::Layout => self.layout,
+            FramePhase::Paint => self.paint,
+            FramePhase::Upload => self.upload,
+            FramePhase::Submit => self.submit,
+        }
+    }
+
+    fn phase_mut(&mut self, phase: FramePhase) -> &mut Duration {
+        match phase {
+            FramePhase::Events => &mut self.events,
+            FramePhase::Layout => &mut self.layout,
+            FramePhase::Paint => &mut self.paint,
+            FramePhase::Upload => &mut self.upload,
+            FramePhase::Submit => &mut self.submit,
+        }
+    }
+
+    // --- all of the CPU's time, from the first event handled to the frame presented
+    pub fn total(&self) -> Duration {
+        self.events + self.layout + self.paint + self.upload + self.submit
+    }
+}
+
+// --- a summary of the frames kept, all of them zero before the first one
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    pub frames: usize,
+    pub average: Duration,
+    // --- 95% of the frames took this long or less
+    pub p95: Duration,
+    pub worst: Duration,
+    // --- frames that took longer than the budget
+    pub over_budget: usize,
+    pub budget: Duration,
+    pub gpu: Option<Duration>,
+    // --- frames drawn per second, from when they started
+    pub fps: f32,
+}
+
+// --- times every frame's phases, keeps the last few for the stats & the overlay
+#[derive(Debug)]
+pub struct Profiler {
+    history: VecDeque<FrameTiming>,
+    current: Option<FrameTiming>,
+    // --- where the phase being timed started
+    mark: Instant,
+    // --- handling events between frames, counted in the next one
+    events: Duration,
+    gpu: Option<Duration>,
+    budget: Duration,
+    overlay: bool,
+    // --- the latest stats, shared with whoever asked for a handle
+    shared: Arc<Mutex<FrameStats>>,
+    label: TextCache,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(HISTORY),
+            current: None,
+            mark: Instant::now(),
+            events: Duration::ZERO,
+            gpu: None,
+            budget: DEFAULT_BUDGET,
+            overlay: false,
+            shared: Arc::default(),
+            label: TextCache::default(),
+        }
+    }
+}
+
+impl Profiler {
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    pub fn overlay(&self) -> bool {
+        self.overlay
+    }
+
+    // --- an event handled outside of any frame
+    pub fn event(&mut self, took: Duration) {
+        self.events += took;
+    }
+
+    pub fn begin(&mut self, now: Instant) {
+        let mut frame = FrameTiming::new(now);
+        frame.events = std::mem::take(&mut self.events);
+        self.current = Some(frame);
+        self.mark = now;
+    }
+
+    // --- the time since the last mark goes to `phase`
+    pub fn lap(&mut self, phase: FramePhase) {
+        let now = Instant::now();
+        if let Some(frame) = self.current.as_mut() {
+            *frame.phase_mut(phase) += now.saturating_duration_since(self.mark);
+        }
+        self.mark = now;
+    }
+
+    pub fn gpu(&mut self, took: Duration) {
+        self.gpu = Some(took);
+    }
+
+    // --- the frame is done, `None` when none was begun
+    pub fn end(&mut self, presented: bool) -> Option<FrameTiming> {
+        let mut frame = self.current.take()?;
+        frame.presented = presented;
+        frame.gpu = self.gpu;
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+        *self.shared.lock().unwrap() = self.stats();
+
+        Some(frame)
+    }
+
+    // --- oldest first
+    pub fn timings(&self) -> impl Iterator<Item = &FrameTiming> {
+        self.history.iter()
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        let frames = self.history.len();
+        if frames == 0 {
+            return FrameStats {
+                budget: self.budget,
+                ..Default::default()
+            };
+        }
+
+        let mut totals = self
+            .history
+            .iter()
+            .map(FrameTiming::total)
+            .collect::<Vec<_>>();
+        totals.sort();
+        let sum = totals.iter().sum::<Duration>();
+        let p95 = totals[((frames - 1) as f32 * 0.95).round() as usize];
+
+        let first = self.history.front().unwrap().start;
+        let last = self.history.back().unwrap().start;
+        let span = last.saturating_duration_since(first).as_secs_f32();
+
+        FrameStats {
+            frames,
+            average: sum / frames as u32,
+            p95,
+            worst: totals[frames - 1],
+            over_budget: totals.iter().filter(|total| **total > self.budget).count(),
+            budget: self.budget,
+            gpu: self.gpu,
+            fps: match span > 0. {
+                true => (frames - 1) as f32 / span,
+                false => 0.,
+            },
+        }
+    }
+
+    pub fn handle(&self) -> StatsHandle {
+        StatsHandle {
+            stats: self.shared.clone(),
+        }
+    }
+
+    // --- the overlay in the window's top right corner, `None` when it's off
+    pub fn overlay_shapes(&self, window: Rect) -> Option<Overlay> {
+        if !self.overlay {
+            return None;
+        }
+
+        let font = Font {
+            size: LABEL_SIZE,
+            ..Default::default()
+        };
+        let stats = self.stats();
+        let ms = |duration: Duration| duration.as_secs_f32() * 1000.;
+        let mut label = format!("avg {:.1} max {:.1} ms", ms(stats.average), ms(stats.worst));
+        if let Some(gpu) = stats.gpu {
+            label.push_str(&format!(" gpu {:.1}", ms(gpu)));
+        }
+
+        let graph_width = HISTORY as f32 * BAR_WIDTH;
+        let label_height = text::line_height(&font);
+        let width = graph_width.max(text::measure(&label, &font)) + PADDING * 2.;
+        let height = label_height + GRAPH_HEIGHT + PADDING * 3.;
+        let panel = Rect::new(window.right() - width - MARGIN, MARGIN, width, height);
+
+        let mut shapes = vec![(panel, PANEL_COLOR)];
+
+        // --- bars grow up from the bottom, the budget sits halfway
+        let graph = Rect::new(
+            panel.x + PADDING,
+            panel.y + PADDING * 2. + label_height,
+            graph_width,
+            GRAPH_HEIGHT,
+        );
+        let scale = GRAPH_HEIGHT / (self.budget.as_secs_f32() * 2.).max(f32::EPSILON);
+        let newest = HISTORY - self.history.len();
+        for (idx, frame) in self.history.iter().enumerate() {
+            let total = frame.total();
+            let bar = (total.as_secs_f32() * scale).clamp(1., GRAPH_HEIGHT);
+            let x = graph.x + (newest + idx) as f32 * BAR_WIDTH;
+            let color = match total > self.budget {
+                true => OVER_COLOR,
+                false => UNDER_COLOR,
+            };
+            shapes.push((Rect::new(x, graph.bottom() - bar, BAR_WIDTH, bar), color));
+        }
+        let budget = graph.bottom() - GRAPH_HEIGHT / 2.;
+        shapes.push((Rect::new(graph.x, budget, graph_width, 1.), BUDGET_COLOR));
+
+        let label_width = text::measure(&label, &font).ceil();
+        let texture = self
+            .label
+            .texture(&label, &font, LABEL_COLOR, 0., label_width as u32);
+        let rect = Rect::new(
+            panel.x + PADDING,
+            panel.y + PADDING,
+            label_width,
+            texture.height() as f32,
+        );
+
+        Some(Overlay {
+            shapes,
+            label: (rect, texture),
+        })
+    }
+}
+
+// --- the overlay's panel, bars & budget line as solid rects, painted in order, then its label
+pub struct Overlay {
+    pub shapes: Vec<(Rect, Color)>,
+    pub label: (Rect, Texture),
+}
+
+// --- the latest frame stats from anywhere, e.g. a listener or another thread
+#[derive(Debug, Clone)]
+pub struct StatsHandle {
+    stats: Arc<Mutex<FrameStats>>,
+}
+
+impl StatsHandle {
+    pub fn get(&self) -> FrameStats {
+        *self.stats.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    // --- `took` all spent on layout
+    fn timing(start: Instant, took: Duration) -> FrameTiming {
+        FrameTiming {
+            layout: took,
+            ..FrameTiming::new(start)
+        }
+    }
+
+    #[test]
+    fn no_frames() {
+        let mut profiler = Profiler::default();
+        profiler.set_budget(ms(8));
+        assert_eq!(
+            profiler.stats(),
+            FrameStats {
+                budget: ms(8),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn aggregates() {
+        let mut profiler = Profiler::default();
+        let start = Instant::now();
+
+        // --- 1ms to 20ms, started 10ms apart
+        for idx in 0..20 {
+            let frame = timing(start + ms(idx * 10), ms(idx + 1));
+            profiler.history.push_back(frame);
+        }
+
+        let stats = profiler.stats();
+        assert_eq!(stats.frames, 20);
+        assert_eq!(stats.average, Duration::from_micros(10_500));
+        assert_eq!(stats.p95, ms(19));
+        assert_eq!(stats.worst, ms(20));
+        // --- 17ms to 20ms go past the 60Hz budget
+        assert_eq!(stats.over_budget, 4);
+        assert!((stats.fps - 100.).abs() < 0.01);
+        assert_eq!(stats.gpu, None);
+    }
+
+    #[test]
+    fn frames() {
+        let mut profiler = Profiler::default();
+        let handle = profiler.handle();
+        assert_eq!(profiler.end(true), None);
+
+        // --- events between frames count towards the next one
+        profiler.event(ms(3));
+        profiler.begin(Instant::now());
+        profiler.gpu(ms(2));
+        let frame = profiler.end(false).unwrap();
+        assert_eq!(frame.events, ms(3));
+        assert_eq!(frame.gpu, Some(ms(2)));
+        assert!(!frame.presented);
+        assert_eq!(handle.get(), profiler.stats());
+
+        // --- only the latest are kept
+        for _ in 0..HISTORY {
+            profiler.begin(Instant::now());
+            profiler.end(true);
+        }
+        assert_eq!(profiler.stats().frames, HISTORY);
+        assert!(profiler.timings().all(|frame| frame.presented));
+        assert_eq!(handle.get().frames, HISTORY);
+    }
+}
@@ -0,0 +1,460 @@
+use std::fmt;
+use std::ops::Range;
+
+// --- bytes, chunks are split above the max and merged with a neighbour below the min
+const MAX_CHUNK: usize = 1024;
+const MIN_CHUNK: usize = 256;
+
+// --- text kept in chunks of bounded size, each knowing how many chars & line breaks it holds,
+// --- so an edit only rewrites one chunk and lookups skip over whole chunks.
+// --- positions count chars, lines are separated by '\n'
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+    chunks: Vec<Chunk>,
+    chars: usize,
+    breaks: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Chunk {
+    text: String,
+    chars: usize,
+    breaks: usize,
+}
+
+impl Chunk {
+    fn new(text: String) -> Self {
+        let chars = text.chars().count();
+        let breaks = text.matches('\n').count();
+
+        Self {
+            text,
+            chars,
+            breaks,
+        }
+    }
+}
+
+// --- byte offset of the `idx`-th char
+fn byte_of(text: &str, idx: usize) -> usize {
+    text.char_indices()
+        .nth(idx)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+// --- cuts `text` in pieces of about half the max size, on char boundaries
+fn split(text: &str) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let mut end = rest.len().min(MAX_CHUNK / 2);
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        chunks.push(Chunk::new(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+
+    chunks
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.chars
+    }
+
+    // --- an empty rope still has one (empty) line
+    pub fn len_lines(&self) -> usize {
+        self.breaks + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars == 0
+    }
+
+    // --- the chunk holding `idx` and the offset in it, the very end belongs to the last chunk
+    fn locate(&self, idx: usize) -> (usize, usize) {
+        let mut start = 0;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if idx < start + chunk.chars || i == self.chunks.len() - 1 {
+                return (i, idx - start);
+            }
+            start += chunk.chars;
+        }
+        (0, 0)
+    }
+
+    pub fn insert(&mut self, idx: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let idx = idx.min(self.chars);
+        if self.chunks.is_empty() {
+            self.chunks.push(Chunk::default());
+        }
+
+        let (i, offset) = self.locate(idx);
+        let chunk = &mut self.chunks[i];
+        chunk.text.insert_str(byte_of(&chunk.text, offset), text);
+
+        self.chars += text.chars().count();
+        self.breaks += text.matches('\n').count();
+
+        let text = std::mem::take(&mut chunk.text);
+        if text.len() > MAX_CHUNK {
+            self.chunks.splice(i..=i, split(&text));
+        } else {
+            *chunk = Chunk::new(text);
+        }
+    }
+
+    pub fn remove(&mut self, range: Range<usize>) {
+        let range = range.start.min(self.chars)..range.end.min(self.chars);
+        if range.is_empty() {
+            return;
+        }
+
+        let mut start = 0;
+        for chunk in self.chunks.iter_mut() {
+            let end = start + chunk.chars;
+            let (from, to) = (range.start.max(start), range.end.min(end));
+
+            if from < to {
+                let bytes = byte_of(&chunk.text, from - start)..byte_of(&chunk.text, to - start);
+                self.breaks -= chunk.text[bytes.clone()].matches('\n').count();
+                self.chars -= to - from;
+
+                chunk.text.replace_range(bytes, "");
+                *chunk = Chunk::new(std::mem::take(&mut chunk.text));
+            }
+
+            if end >= range.end {
+                break;
+            }
+            start = end;
+        }
+
+        self.chunks.retain(|chunk| !chunk.text.is_empty());
+        self.merge();
+    }
+
+    // --- keeps lots of tiny chunks from piling up after deletions
+    fn merge(&mut self) {
+        let mut i = 0;
+        while i + 1 < self.chunks.len() {
+            let (a, b) = (&self.chunks[i], &self.chunks[i + 1]);
+            let small = a.text.len() < MIN_CHUNK || b.text.len() < MIN_CHUNK;
+
+            if small && a.text.len() + b.text.len() <= MAX_CHUNK {
+                let next = self.chunks.remove(i + 1);
+                let chunk = &mut self.chunks[i];
+                chunk.text.push_str(&next.text);
+                chunk.chars += next.chars;
+                chunk.breaks += next.breaks;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> String {
+        let mut out = String::new();
+        let mut start = 0;
+
+        for chunk in self.chunks.iter() {
+            let end = start + chunk.chars;
+            let (from, to) = (range.start.max(start), range.end.min(end));
+
+            if from < to {
+                let bytes = byte_of(&chunk.text, from - start)..byte_of(&chunk.text, to - start);
+                out.push_str(&chunk.text[bytes]);
+            }
+
+            if end >= range.end {
+                break;
+            }
+            start = end;
+        }
+
+        out
+    }
+
+    pub fn char(&self, idx: usize) -> Option<char> {
+        if idx >= self.chars {
+            return None;
+        }
+        let (i, offset) = self.locate(idx);
+        self.chunks[i].text.chars().nth(offset)
+    }
+
+    // --- first char of the line, the end of the text past the last line
+    pub fn line_to_char(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+
+        let (mut chars, mut breaks) = (0, 0);
+        for chunk in self.chunks.iter() {
+            if breaks + chunk.breaks >= line {
+                for (n, ch) in chunk.text.chars().enumerate() {
+                    if ch == '\n' {
+                        breaks += 1;
+                        if breaks == line {
+                            return chars + n + 1;
+                        }
+                    }
+                }
+            }
+            chars += chunk.chars;
+            breaks += chunk.breaks;
+        }
+
+        self.chars
+    }
+
+    pub fn char_to_line(&self, idx: usize) -> usize {
+        let idx = idx.min(self.chars);
+        let (mut chars, mut breaks) = (0, 0);
+
+        for chunk in self.chunks.iter() {
+            if idx <= chars + chunk.chars {
+                let prefix = &chunk.text[..byte_of(&chunk.text, idx - chars)];
+                return breaks + prefix.matches('\n').count();
+            }
+            chars += chunk.chars;
+            breaks += chunk.breaks;
+        }
+
+        breaks
+    }
+
+    // --- chars of the line, without its line break
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_to_char(line);
+        let end = match line + 1 < self.len_lines() {
+            true => self.line_to_char(line + 1) - 1,
+            false => self.chars,
+        };
+        start..end
+    }
+
+    pub fn line(&self, line: usize) -> String {
+        self.slice(self.line_range(line))
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks.iter() {
+            f.write_str(&chunk.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Self {
+            chunks: split(text),
+            chars: text.chars().count(),
+            breaks: text.matches('\n').count(),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- the rope against the same edits on a plain string, chunk bookkeeping included
+    fn check(rope: &Rope, text: &str) {
+        assert_eq!(rope.to_string(), text);
+        assert_eq!(rope.len_chars(), text.chars().count());
+        assert_eq!(rope.len_lines(), text.split('\n').count());
+
+        for chunk in rope.chunks.iter() {
+            assert!(!chunk.text.is_empty());
+            assert!(chunk.text.len() <= MAX_CHUNK);
+            assert_eq!(chunk.chars, chunk.text.chars().count());
+            assert_eq!(chunk.breaks, chunk.text.matches('\n').count());
+        }
+
+        let mut start = 0;
+        for (line, expected) in text.split('\n').enumerate() {
+            let len = expected.chars().count();
+            assert_eq!(rope.line_to_char(line), start, "line {line}");
+            assert_eq!(rope.line_range(line), start..start + len, "line {line}");
+            assert_eq!(rope.line(line), expected, "line {line}");
+            assert_eq!(rope.char_to_line(start), line);
+            assert_eq!(rope.char_to_line(start + len), line);
+            start += len + 1;
+        }
+    }
+
+    // --- long enough to span a few chunks, with multi-byte chars across their edges
+    fn sample(lines: usize) -> String {
+        (0..lines)
+            .map(|n| format!("line {n} ünïcödé → ✓ {}", "x".repeat(n % 40)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn char_range(text: &str, range: Range<usize>) -> Range<usize> {
+        byte_of(text, range.start)..byte_of(text, range.end)
+    }
+
+    #[test]
+    fn empty() {
+        let rope = Rope::new();
+        assert!(rope.is_empty());
+        assert_eq!(rope.len_lines(), 1);
+        assert_eq!(rope.line_range(0), 0..0);
+        assert_eq!(rope.line(0), "");
+        assert_eq!(rope.char(0), None);
+        assert_eq!(rope.char_to_line(10), 0);
+        assert_eq!(rope.line_to_char(3), 0);
+    }
+
+    #[test]
+    fn from_text() {
+        let text = sample(200);
+        let rope = Rope::from(text.as_str());
+        assert!(rope.chunks.len() > 4);
+        check(&rope, &text);
+    }
+
+    #[test]
+    fn lookups() {
+        let rope = Rope::from("ab\n\ncdé\n");
+        assert_eq!(rope.len_lines(), 4);
+        assert_eq!(rope.char(5), Some('d'));
+        assert_eq!(rope.char(6), Some('é'));
+        assert_eq!(rope.char(8), None);
+        assert_eq!(rope.line_to_char(1), 3);
+        assert_eq!(rope.line_to_char(2), 4);
+        assert_eq!(rope.line_to_char(3), 8);
+        // --- past the last line is the end of the text
+        assert_eq!(rope.line_to_char(9), 8);
+        assert_eq!(rope.char_to_line(2), 0);
+        assert_eq!(rope.char_to_line(3), 1);
+        assert_eq!(rope.char_to_line(100), 3);
+        assert_eq!(rope.line_range(2), 4..7);
+        assert_eq!(rope.line(3), "");
+        assert_eq!(rope.slice(1..6), "b\n\ncd");
+    }
+
+    #[test]
+    fn insert_splits_chunks() {
+        let mut text = String::new();
+        let mut rope = Rope::new();
+
+        for n in 0..300 {
+            let piece = format!("{n}: ñ✓\n");
+            // --- keep hitting the middle, the same chunk grows until it splits
+            let idx = rope.len_chars() / 2;
+            rope.insert(idx, &piece);
+            let at = byte_of(&text, idx);
+            text.insert_str(at, &piece);
+        }
+
+        assert!(rope.chunks.len() > 1);
+        check(&rope, &text);
+    }
+
+    #[test]
+    fn insert_clamps_to_the_end() {
+        let mut rope = Rope::from("ab");
+        rope.insert(10, "c");
+        rope.insert(1, "");
+        check(&rope, "abc");
+    }
+
+    #[test]
+    fn remove_across_chunks() {
+        let mut text = sample(150);
+        let mut rope = Rope::from(text.as_str());
+        let chunks = rope.chunks.len();
+
+        // --- from inside one chunk to inside another, a few chunks further
+        let range = 300..2500;
+        rope.remove(range.clone());
+        text.replace_range(char_range(&text, range), "");
+        assert!(rope.chunks.len() < chunks);
+        check(&rope, &text);
+
+        // --- out of range is clamped, empty does nothing
+        let len = rope.len_chars();
+        rope.remove(len - 10..len + 10);
+        rope.remove(5..5);
+        text.replace_range(char_range(&text, len - 10..len), "");
+        check(&rope, &text);
+
+        rope.remove(0..rope.len_chars());
+        assert!(rope.chunks.is_empty());
+        check(&rope, "");
+    }
+
+    #[test]
+    fn small_chunks_are_merged() {
+        let text = "ab\n".repeat(40);
+        let mut rope = Rope {
+            chunks: vec![Chunk::new(text.clone()); 8],
+            chars: text.len() * 8,
+            breaks: 40 * 8,
+        };
+
+        rope.remove(0..1);
+        assert_eq!(rope.chunks.len(), 1);
+        check(&rope, &text.repeat(8)[1..]);
+    }
+
+    #[test]
+    fn random_edits() {
+        let mut text = sample(60);
+        let mut rope = Rope::from(text.as_str());
+
+        // --- a small LCG, deterministic without pulling in a crate
+        let mut seed = 0x2545_f491_u64;
+        let mut next = |max: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % max.max(1)
+        };
+
+        let long = "z".repeat(700);
+        for round in 0..400 {
+            let len = text.chars().count();
+            match round % 3 {
+                0 | 1 => {
+                    let idx = next(len + 1);
+                    let piece = ["é\n", "abc", "✓✓✓\n\n", &long][next(4)];
+                    rope.insert(idx, piece);
+                    let at = byte_of(&text, idx);
+                    text.insert_str(at, piece);
+                }
+                _ => {
+                    let start = next(len + 1);
+                    let end = (start + next(900)).min(len);
+                    rope.remove(start..end);
+                    text.replace_range(char_range(&text, start..end), "");
+                }
+            }
+            assert_eq!(rope.to_string(), text, "round {round}");
+        }
+
+        check(&rope, &text);
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+// --- asks for a frame from anywhere, e.g. a thread that just finished loading something,
+// --- every `Task` does when it's done. clones share the event loop, asking before the app
+// --- runs does nothing
+#[derive(Clone, Default)]
+pub struct RedrawHandle {
+    proxy: Arc<OnceLock<EventLoopProxy<UserEvent>>>,
+}
+
+impl RedrawHandle {
+    pub(crate) fn connect(&self, proxy: EventLoopProxy<UserEvent>) {
+        let _ = self.proxy.set(proxy);
+    }
+
+    // --- false once the event loop is gone, or before it started
+    pub fn request_redraw(&self) -> bool {
+        self.proxy
+            .get()
+            .is_some_and(|proxy| proxy.send_event(UserEvent::Redraw).is_ok())
+    }
+}
+
+// --- when frames are drawn: as asked for, but never closer together than the cap allows
+#[derive(Debug, Default)]
+pub struct Schedule {
+    max_fps: Option<u32>,
+    last_frame: Option<Instant>,
+}
+
+impl Schedule {
+    // --- `None` leaves it to the present mode, e.g. the display's refresh rate with vsync
+    pub fn set_max_fps(&mut self, fps: Option<u32>) {
+        self.max_fps = fps.filter(|fps| *fps > 0);
+    }
+
+    pub fn max_fps(&self) -> Option<u32> {
+        self.max_fps
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        self.max_fps
+            .map(|fps| Duration::from_secs_f64(1. / fps as f64))
+    }
+
+    // --- when the next frame may be drawn, `None` when it may be drawn right away
+    pub fn wait(&self, now: Instant) -> Option<Instant> {
+        let due = self.last_frame? + self.interval()?;
+        (now < due).then_some(due)
+    }
+
+    // --- a frame is being drawn, the next one is timed from here
+    pub fn frame(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+
+    // --- when the frame after this one is due while something keeps moving,
+    // --- `None` to draw it as soon as the present mode lets us
+    pub fn next(&self, now: Instant) -> Option<Instant> {
+        self.interval().map(|interval| now + interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncapped() {
+        let mut schedule = Schedule::default();
+        let now = Instant::now();
+
+        assert_eq!(schedule.wait(now), None);
+        schedule.frame(now);
+        assert_eq!(schedule.wait(now), None);
+        assert_eq!(schedule.next(now), None);
+
+        // --- 0 fps means no cap rather than no frames
+        schedule.set_max_fps(Some(0));
+        assert_eq!(schedule.max_fps(), None);
+        assert_eq!(schedule.wait(now), None);
+    }
+
+    #[test]
+    fn capped() {
+        let mut schedule = Schedule::default();
+        schedule.set_max_fps(Some(50));
+        let interval = Duration::from_millis(20);
+        let start = Instant::now();
+
+        // --- nothing drawn yet, so nothing to wait for
+        assert_eq!(schedule.wait(start), None);
+
+        schedule.frame(start);
+        assert_eq!(schedule.wait(start), Some(start + interval));
+        assert_eq!(
+            schedule.wait(start + Duration::from_millis(5)),
+            Some(start + interval)
+        );
+        assert_eq!(schedule.wait(start + interval), None);
+        assert_eq!(schedule.next(start), Some(start + interval));
+
+        // --- timed from the frame actually drawn, not from when it was due
+        let late = start + Duration::from_millis(33);
+        schedule.frame(late);
+        assert_eq!(schedule.wait(late), Some(late + interval));
+
+        schedule.set_max_fps(None);
+        assert_eq!(schedule.wait(late), None);
+    }
+}
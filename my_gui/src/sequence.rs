@@ -0,0 +1,432 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::video::{timestamp, FrameSource, PixelFormat, VideoError, VideoFrame};
+
+const Y4M_MAGIC: &str = "YUV4MPEG2";
+const FRAME_MAGIC: &str = "FRAME";
+// --- a header line longer than this isn't one
+const MAX_LINE: usize = 4096;
+
+// --- uncompressed 4:2:0 or monochrome frames in a YUV4MPEG2 file, each one read from disk
+// --- when it's asked for. where every frame starts is found when it's opened
+pub struct Y4mReader<R> {
+    reader: R,
+    width: u32,
+    height: u32,
+    frame_rate: f64,
+    // --- monochrome frames get neutral chroma planes added
+    mono: bool,
+    offsets: Vec<u64>,
+}
+
+impl Y4mReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VideoError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead + Seek> Y4mReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, VideoError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = read_line(&mut reader)?.ok_or_else(|| format_error("empty file"))?;
+
+        let mut params = header.split(' ');
+        if params.next() != Some(Y4M_MAGIC) {
+            return Err(format_error("not a YUV4MPEG2 file"));
+        }
+
+        let (mut width, mut height, mut frame_rate) = (None, None, None);
+        let mut mono = false;
+        for param in params.filter(|param| param.is_char_boundary(1)) {
+            let (tag, value) = param.split_at(1);
+            match tag {
+                "W" => width = value.parse::<u32>().ok(),
+                "H" => height = value.parse::<u32>().ok(),
+                "F" => frame_rate = parse_ratio(value),
+                "C" => {
+                    mono = match value {
+                        "420" | "420jpeg" | "420paldv" | "420mpeg2" => false,
+                        "mono" => true,
+                        other => return Err(format_error(&format!("color space `{other}`"))),
+                    }
+                }
+                // --- interlacing, aspect ratio & extensions don't change how it's read
+                _ => (),
+            }
+        }
+        let (Some(width), Some(height)) = (width, height) else {
+            return Err(format_error("no frame size"));
+        };
+        let frame_rate = frame_rate.ok_or_else(|| format_error("no frame rate"))?;
+
+        let luma = width as u64 * height as u64;
+        let frame_size = match mono {
+            true => luma,
+            false => PixelFormat::I420.frame_size(width, height) as u64,
+        };
+
+        // --- only the frame headers are read, the data in between is skipped over
+        let start = reader.stream_position()?;
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut offsets = Vec::new();
+        while let Some(line) = read_line(&mut reader)? {
+            if !line.starts_with(FRAME_MAGIC) {
+                return Err(format_error("missing frame header"));
+            }
+            let offset = reader.stream_position()?;
+            if offset + frame_size > len {
+                // --- a frame cut short at the end of the file is left out
+                break;
+            }
+            reader.seek(SeekFrom::Start(offset + frame_size))?;
+            offsets.push(offset);
+        }
+
+        Ok(Self {
+            reader,
+            width,
+            height,
+            frame_rate,
+            mono,
+            offsets,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl<R: BufRead + Seek> FrameSource for Y4mReader<R> {
+    fn frame_rate(&self) -> f64 {
+        self.frame_rate
+    }
+
+    fn frame_count(&self) -> Option<u64> {
+        Some(self.offsets.len() as u64)
+    }
+
+    fn frame(&mut self, index: u64) -> Result<VideoFrame, VideoError> {
+        let offset = *self
+            .offsets
+            .get(index as usize)
+            .ok_or(VideoError::OutOfRange(index))?;
+        let size = PixelFormat::I420.frame_size(self.width, self.height);
+        let luma = self.width as usize * self.height as usize;
+
+        let mut data = vec![128; size];
+        let read = if self.mono {
+            &mut data[..luma]
+        } else {
+            &mut data[..]
+        };
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(read)?;
+
+        VideoFrame::new(
+            PixelFormat::I420,
+            self.width,
+            self.height,
+            timestamp(index, self.frame_rate),
+            data,
+        )
+    }
+}
+
+// --- frames of one format & size back to back with nothing in between, e.g. what
+// --- `ffmpeg -f rawvideo -pix_fmt rgba` writes
+pub struct RawReader<R> {
+    reader: R,
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    frame_rate: f64,
+    count: u64,
+}
+
+impl RawReader<BufReader<File>> {
+    pub fn open(
+        path: impl AsRef<Path>,
+        format: PixelFormat,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+    ) -> Result<Self, VideoError> {
+        Self::new(
+            BufReader::new(File::open(path)?),
+            format,
+            width,
+            height,
+            frame_rate,
+        )
+    }
+}
+
+impl<R: Read + Seek> RawReader<R> {
+    pub fn new(
+        mut reader: R,
+        format: PixelFormat,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+    ) -> Result<Self, VideoError> {
+        let frame_size = format.frame_size(width, height) as u64;
+        if frame_size == 0 {
+            return Err(format_error("frames have no pixels"));
+        }
+
+        // --- a frame cut short at the end of the file is left out
+        let len = reader.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            reader,
+            format,
+            width,
+            height,
+            frame_rate,
+            count: len / frame_size,
+        })
+    }
+}
+
+impl<R: Read + Seek> FrameSource for RawReader<R> {
+    fn frame_rate(&self) -> f64 {
+        self.frame_rate
+    }
+
+    fn frame_count(&self) -> Option<u64> {
+        Some(self.count)
+    }
+
+    fn frame(&mut self, index: u64) -> Result<VideoFrame, VideoError> {
+        if index >= self.count {
+            return Err(VideoError::OutOfRange(index));
+        }
+        let size = self.format.frame_size(self.width, self.height);
+
+        let mut data = vec![0; size];
+        self.reader.seek(SeekFrom::Start(index * size as u64))?;
+        self.reader.read_exact(&mut data)?;
+
+        VideoFrame::new(
+            self.format,
+            self.width,
+            self.height,
+            timestamp(index, self.frame_rate),
+            data,
+        )
+    }
+}
+
+fn format_error(message: &str) -> VideoError {
+    VideoError::Format(message.to_string())
+}
+
+// --- `num:den`, as Y4M writes frame rates
+fn parse_ratio(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once(':')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (num > 0. && den > 0.).then(|| num / den)
+}
+
+// --- without the newline, `None` at the end of the file
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>, VideoError> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE as u64)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        return Err(format_error("header line too long or cut short"));
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| format_error("header isn't text"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use super::*;
+
+    // --- 4x2 frames: 8 bytes of luma, a 2x1 U & a 2x1 V plane
+    const WIDTH: u32 = 4;
+    const HEIGHT: u32 = 2;
+
+    fn y4m(header: &str, frames: &[&[u8]]) -> Cursor<Vec<u8>> {
+        let mut file = format!("{header}\n").into_bytes();
+        for frame in frames {
+            file.extend_from_slice(b"FRAME\n");
+            file.extend_from_slice(frame);
+        }
+        Cursor::new(file)
+    }
+
+    fn format_message(reader: Result<Y4mReader<Cursor<Vec<u8>>>, VideoError>) -> String {
+        match reader {
+            Err(VideoError::Format(message)) => message,
+            Err(err) => panic!("expected a format error, got {err}"),
+            Ok(_) => panic!("expected a format error"),
+        }
+    }
+
+    #[test]
+    fn y4m_header() {
+        let frame = [7; 12];
+        let reader = Y4mReader::new(y4m(
+            "YUV4MPEG2 W4 H2 F30000:1001 Ip A1:1 C420jpeg XYSCSS=420JPEG",
+            &[&frame],
+        ))
+        .unwrap();
+        assert_eq!((reader.width(), reader.height()), (WIDTH, HEIGHT));
+        assert!((reader.frame_rate() - 29.97).abs() < 0.001);
+        assert_eq!(reader.frame_count(), Some(1));
+        assert!(!reader.mono);
+    }
+
+    #[test]
+    fn y4m_frames() {
+        let frames = [[1; 12], [2; 12], [3; 12]];
+        let mut reader = Y4mReader::new(y4m(
+            "YUV4MPEG2 W4 H2 F25:1",
+            &[&frames[0], &frames[1], &frames[2]],
+        ))
+        .unwrap();
+        assert_eq!(reader.frame_count(), Some(3));
+        assert_eq!(reader.duration(), Some(Duration::from_millis(120)));
+
+        // --- read out of order, each one seeks to its own data
+        let frame = reader.frame(2).unwrap();
+        assert_eq!(frame.format(), PixelFormat::I420);
+        assert_eq!(frame.data(), &frames[2]);
+        assert_eq!(frame.timestamp(), Duration::from_millis(80));
+        assert_eq!(reader.frame(0).unwrap().data(), &frames[0]);
+        assert!(matches!(reader.frame(3), Err(VideoError::OutOfRange(3))));
+    }
+
+    #[test]
+    fn y4m_frame_parameters() {
+        // --- a frame header may carry parameters of its own
+        let mut file = b"YUV4MPEG2 W4 H2 F1:1\nFRAME Ixyz\n".to_vec();
+        file.extend_from_slice(&[5; 12]);
+        let mut reader = Y4mReader::new(Cursor::new(file)).unwrap();
+        assert_eq!(reader.frame(0).unwrap().data(), &[5; 12]);
+    }
+
+    #[test]
+    fn y4m_mono() {
+        let luma = [9, 10, 11, 12, 13, 14, 15, 16];
+        let mut reader =
+            Y4mReader::new(y4m("YUV4MPEG2 W4 H2 F1:1 Cmono", &[&luma, &luma])).unwrap();
+        assert_eq!(reader.frame_count(), Some(2));
+
+        let frame = reader.frame(1).unwrap();
+        assert_eq!(frame.format(), PixelFormat::I420);
+        // --- the chroma planes are neutral gray
+        assert_eq!(&frame.data()[..8], &luma);
+        assert_eq!(&frame.data()[8..], &[128; 4]);
+    }
+
+    #[test]
+    fn y4m_truncated_frame() {
+        let mut reader = Y4mReader::new(y4m("YUV4MPEG2 W4 H2 F1:1", &[&[1; 12], &[2; 5]])).unwrap();
+        assert_eq!(reader.frame_count(), Some(1));
+        assert!(matches!(reader.frame(1), Err(VideoError::OutOfRange(1))));
+
+        // --- no data at all after the last frame header
+        let reader = Y4mReader::new(y4m("YUV4MPEG2 W4 H2 F1:1", &[&[1; 12], &[]])).unwrap();
+        assert_eq!(reader.frame_count(), Some(1));
+    }
+
+    #[test]
+    fn y4m_invalid() {
+        for (file, message) in [
+            (Cursor::new(vec![]), "empty file"),
+            (y4m("RIFF W4 H2 F1:1", &[]), "not a YUV4MPEG2 file"),
+            (y4m("YUV4MPEG2 W4 F1:1", &[]), "no frame size"),
+            (y4m("YUV4MPEG2 W4 H2", &[]), "no frame rate"),
+            (y4m("YUV4MPEG2 W4 H2 F1:0", &[]), "no frame rate"),
+            (y4m("YUV4MPEG2 W4 H2 F1:1 C444", &[]), "color space `444`"),
+            (
+                Cursor::new(b"YUV4MPEG2 W4 H2 F1:1".to_vec()),
+                "header line too long or cut short",
+            ),
+            (
+                Cursor::new(b"YUV4MPEG2 W4 H2 F1:1\nFRAMEX".to_vec()),
+                "header line too long or cut short",
+            ),
+            (
+                Cursor::new(b"YUV4MPEG2 W4 H2 F1:1\nJUNK\n".to_vec()),
+                "missing frame header",
+            ),
+        ] {
+            assert_eq!(format_message(Y4mReader::new(file)), message);
+        }
+
+        let long = format!("YUV4MPEG2 W4 H2 F1:1 X{}", "a".repeat(MAX_LINE));
+        assert_eq!(
+            format_message(Y4mReader::new(y4m(&long, &[]))),
+            "header line too long or cut short"
+        );
+    }
+
+    #[test]
+    fn raw_frame_count() {
+        let size = PixelFormat::Rgba.frame_size(WIDTH, HEIGHT);
+        assert_eq!(size, 32);
+
+        // --- the half frame at the end is left out
+        let data = (0..size * 5 / 2).map(|n| n as u8).collect::<Vec<_>>();
+        let mut reader = RawReader::new(
+            Cursor::new(data.clone()),
+            PixelFormat::Rgba,
+            WIDTH,
+            HEIGHT,
+            10.,
+        )
+        .unwrap();
+        assert_eq!(reader.frame_count(), Some(2));
+        assert_eq!(reader.duration(), Some(Duration::from_millis(200)));
+
+        let frame = reader.frame(1).unwrap();
+        assert_eq!(frame.data(), &data[size..size * 2]);
+        assert_eq!(frame.timestamp(), Duration::from_millis(100));
+        assert!(matches!(reader.frame(2), Err(VideoError::OutOfRange(2))));
+
+        let nv12 = PixelFormat::Nv12.frame_size(WIDTH, HEIGHT);
+        let reader = RawReader::new(
+            Cursor::new(vec![0; nv12 * 3]),
+            PixelFormat::Nv12,
+            WIDTH,
+            HEIGHT,
+            10.,
+        )
+        .unwrap();
+        assert_eq!(reader.frame_count(), Some(3));
+
+        let reader =
+            RawReader::new(Cursor::new(vec![]), PixelFormat::I420, WIDTH, HEIGHT, 10.).unwrap();
+        assert_eq!(reader.frame_count(), Some(0));
+    }
+
+    #[test]
+    fn raw_without_pixels() {
+        let reader = RawReader::new(Cursor::new(vec![0; 4]), PixelFormat::Rgba, 0, 4, 10.);
+        assert!(
+            matches!(reader, Err(VideoError::Format(message)) if message == "frames have no pixels")
+        );
+    }
+}
@@ -0,0 +1,542 @@
+use std::time::Instant;
+
+use winit::window::CursorIcon;
+
+use crate::animation::Transition;
+use crate::geometry::Transform;
+use crate::stylesheet::Stylesheet;
+use crate::theme::Theme;
+
+pub type Color = [f32; 4];
+
+pub const TRANSPARENT: Color = [0., 0., 0., 0.];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Edges {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Edges {
+    pub fn all(value: f32) -> Self {
+        Self {
+            top: value,
+            right: value,
+            bottom: value,
+            left: value,
+        }
+    }
+
+    pub fn symmetric(vertical: f32, horizontal: f32) -> Self {
+        Self {
+            top: vertical,
+            right: horizontal,
+            bottom: vertical,
+            left: horizontal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Border {
+    pub width: f32,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    pub family: String,
+    pub size: f32,
+    pub weight: u16,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            family: "sans-serif".to_string(),
+            size: 14.,
+            weight: 400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Shadow {
+    pub offset: [f32; 2],
+    pub blur: f32,
+    pub color: Color,
+}
+
+// --- where along a gradient each point of the box is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    // --- radians, clockwise from pointing up, like CSS' `linear-gradient(<angle>, ..)`
+    Linear { angle: f32 },
+    // --- out from the center to the farthest corner, center in 0..1 of the box.
+    // --- an ellipse in proportion to the box, or a circle
+    Radial { center: [f32; 2], circle: bool },
+    // --- around the center, starting at `angle` radians clockwise from up
+    Conic { center: [f32; 2], angle: f32 },
+}
+
+// --- colors at offsets in 0..1 along the gradient, blended in between.
+// --- an offset smaller than the one before it is moved up to it, like in CSS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    pub fn linear(angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Linear { angle },
+            stops: vec![],
+        }
+    }
+
+    pub fn radial(center: [f32; 2]) -> Self {
+        Self {
+            kind: GradientKind::Radial {
+                center,
+                circle: false,
+            },
+            stops: vec![],
+        }
+    }
+
+    pub fn circle(center: [f32; 2]) -> Self {
+        Self {
+            kind: GradientKind::Radial {
+                center,
+                circle: true,
+            },
+            stops: vec![],
+        }
+    }
+
+    pub fn conic(center: [f32; 2], angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Conic { center, angle },
+            stops: vec![],
+        }
+    }
+
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        self.stops.push((offset, color));
+        self
+    }
+
+    // --- blended with premultiplied alpha, so fading to transparent doesn't go gray
+    pub fn color_at(&self, t: f32) -> Color {
+        let mut last: Option<(f32, Color)> = None;
+        for &(offset, color) in &self.stops {
+            let offset = last.map_or(offset, |(previous, _)| offset.max(previous));
+            match last {
+                None if t <= offset => return color,
+                Some((previous, before)) if t <= offset => {
+                    let f = match offset > previous {
+                        true => (t - previous) / (offset - previous),
+                        false => 1.,
+                    };
+                    let alpha = before[3] + (color[3] - before[3]) * f;
+                    if alpha <= 0. {
+                        return [color[0], color[1], color[2], 0.];
+                    }
+                    let channel = |i: usize| {
+                        (before[i] * before[3] + (color[i] * color[3] - before[i] * before[3]) * f)
+                            / alpha
+                    };
+                    return [channel(0), channel(1), channel(2), alpha];
+                }
+                _ => last = Some((offset, color)),
+            }
+        }
+        last.map_or(TRANSPARENT, |(_, color)| color)
+    }
+
+    // --- what the fragment shader needs to place it: the kind, then its parameters
+    pub(crate) fn params(&self) -> [f32; 4] {
+        match self.kind {
+            GradientKind::Linear { angle } => [1., angle, 0., 0.],
+            GradientKind::Radial { center, circle } => {
+                [2., center[0], center[1], if circle { 1. } else { 0. }]
+            }
+            GradientKind::Conic { center, angle } => [3., center[0], center[1], angle],
+        }
+    }
+}
+
+// --- whether children drawn outside of the padding box show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    #[default]
+    Visible,
+    // --- cut to the padding box, following the rounded corners
+    Hidden,
+}
+
+// --- every property is optional so styles can be layered on top of each other,
+// --- a `None` means "not set here", letting the value below (or the parent) show through
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Style {
+    pub background: Option<Color>,
+    pub foreground: Option<Color>,
+    pub border: Option<Border>,
+    // --- painted instead of the background color, the border color & the text color
+    pub background_gradient: Option<Gradient>,
+    pub border_gradient: Option<Gradient>,
+    pub text_gradient: Option<Gradient>,
+    pub radius: Option<f32>,
+    pub padding: Option<Edges>,
+    pub margin: Option<Edges>,
+    pub font: Option<Font>,
+    pub shadow: Option<Shadow>,
+    pub opacity: Option<f32>,
+    pub cursor: Option<CursorIcon>,
+    pub overflow: Option<Overflow>,
+    // --- stacking among siblings, higher is painted later & hit first, ties keep tree order
+    pub z_index: Option<i32>,
+    // --- lifted out of the tree into the overlay layer, above everything else and
+    // --- not cut by its ancestors' clips, for popups, tooltips & dialogs
+    pub overlay: Option<bool>,
+    // --- moves, turns, scales or skews the view & everything in it when painted & hit,
+    // --- without changing the layout. in px, around the origin given in 0..1 of the
+    // --- border box, its center when not set
+    pub transform: Option<Transform>,
+    pub transform_origin: Option<[f32; 2]>,
+    pub transitions: Option<Vec<Transition>>,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub fn with_foreground(mut self, color: Color) -> Self {
+        self.foreground = Some(color);
+        self
+    }
+
+    pub fn with_border(mut self, width: f32, color: Color) -> Self {
+        self.border = Some(Border { width, color });
+        self
+    }
+
+    pub fn with_background_gradient(mut self, gradient: Gradient) -> Self {
+        self.background_gradient = Some(gradient);
+        self
+    }
+
+    pub fn with_border_gradient(mut self, gradient: Gradient) -> Self {
+        self.border_gradient = Some(gradient);
+        self
+    }
+
+    pub fn with_text_gradient(mut self, gradient: Gradient) -> Self {
+        self.text_gradient = Some(gradient);
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Edges) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn with_margin(mut self, margin: Edges) -> Self {
+        self.margin = Some(margin);
+        self
+    }
+
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity.clamp(0., 1.));
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = Some(overflow);
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = Some(z_index);
+        self
+    }
+
+    pub fn with_overlay(mut self, overlay: bool) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn with_transform_origin(mut self, origin: [f32; 2]) -> Self {
+        self.transform_origin = Some(origin);
+        self
+    }
+
+    pub fn with_transition(mut self, transition: Transition) -> Self {
+        self.transitions
+            .get_or_insert_with(Vec::new)
+            .push(transition);
+        self
+    }
+
+    // --- composition: every property set on `other` wins over the one on `self`
+    pub fn merge(&self, other: &Style) -> Style {
+        Style {
+            background: other.background.or(self.background),
+            foreground: other.foreground.or(self.foreground),
+            border: other.border.or(self.border),
+            background_gradient: other
+                .background_gradient
+                .clone()
+                .or_else(|| self.background_gradient.clone()),
+            border_gradient: other
+                .border_gradient
+                .clone()
+                .or_else(|| self.border_gradient.clone()),
+            text_gradient: other
+                .text_gradient
+                .clone()
+                .or_else(|| self.text_gradient.clone()),
+            radius: other.radius.or(self.radius),
+            padding: other.padding.or(self.padding),
+            margin: other.margin.or(self.margin),
+            font: other.font.clone().or_else(|| self.font.clone()),
+            shadow: other.shadow.or(self.shadow),
+            opacity: other.opacity.or(self.opacity),
+            cursor: other.cursor.or(self.cursor),
+            overflow: other.overflow.or(self.overflow),
+            z_index: other.z_index.or(self.z_index),
+            overlay: other.overlay.or(self.overlay),
+            transform: other.transform.or(self.transform),
+            transform_origin: other.transform_origin.or(self.transform_origin),
+            transitions: other
+                .transitions
+                .clone()
+                .or_else(|| self.transitions.clone()),
+        }
+    }
+
+    // --- inheritance: only the text-ish properties flow down the view tree,
+    // --- opacity is multiplied instead so a faded parent fades its children too
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            foreground: self.foreground.or(parent.foreground),
+            // --- a text color set here wins over a gradient from above
+            text_gradient: match self.foreground {
+                Some(_) => self.text_gradient.clone(),
+                None => self
+                    .text_gradient
+                    .clone()
+                    .or_else(|| parent.text_gradient.clone()),
+            },
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            cursor: self.cursor.or(parent.cursor),
+            opacity: match (self.opacity, parent.opacity) {
+                (Some(own), Some(inherited)) => Some(own * inherited),
+                (own, inherited) => own.or(inherited),
+            },
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementState {
+    pub hovered: bool,
+    pub active: bool,
+    pub focused: bool,
+    pub disabled: bool,
+}
+
+// --- the equivalent of `:hover`, `:active`, `:focus` & `:disabled`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Styles {
+    pub base: Style,
+    pub hover: Option<Style>,
+    pub active: Option<Style>,
+    pub focus: Option<Style>,
+    pub disabled: Option<Style>,
+}
+
+impl From<Style> for Styles {
+    fn from(base: Style) -> Self {
+        Self {
+            base,
+            ..Default::default()
+        }
+    }
+}
+
+impl Styles {
+    pub fn with_hover(mut self, style: Style) -> Self {
+        self.hover = Some(style);
+        self
+    }
+
+    pub fn with_active(mut self, style: Style) -> Self {
+        self.active = Some(style);
+        self
+    }
+
+    pub fn with_focus(mut self, style: Style) -> Self {
+        self.focus = Some(style);
+        self
+    }
+
+    pub fn with_disabled(mut self, style: Style) -> Self {
+        self.disabled = Some(style);
+        self
+    }
+
+    // --- layers `other` on top of `self`, variant by variant
+    pub fn merge(&self, other: &Styles) -> Styles {
+        fn layer(below: &Option<Style>, above: &Option<Style>) -> Option<Style> {
+            match (below, above) {
+                (Some(below), Some(above)) => Some(below.merge(above)),
+                (below, above) => above.clone().or_else(|| below.clone()),
+            }
+        }
+
+        Styles {
+            base: self.base.merge(&other.base),
+            hover: layer(&self.hover, &other.hover),
+            active: layer(&self.active, &other.active),
+            focus: layer(&self.focus, &other.focus),
+            disabled: layer(&self.disabled, &other.disabled),
+        }
+    }
+
+    // --- later variants win: base < hover < focus < active < disabled
+    pub fn resolve(&self, state: ElementState) -> Style {
+        let variants = [
+            (state.hovered, &self.hover),
+            (state.focused, &self.focus),
+            (state.active, &self.active),
+            (state.disabled, &self.disabled),
+        ];
+
+        variants
+            .into_iter()
+            .filter_map(|(on, style)| style.as_ref().filter(|_| on))
+            .fold(self.base.clone(), |acc, style| acc.merge(style))
+    }
+}
+
+// --- everything a view's final style is computed from,
+// --- `root` is what the top-level views inherit from
+pub struct Cascade<'a> {
+    pub theme: &'a Theme,
+    pub stylesheet: &'a Stylesheet,
+    pub root: &'a Style,
+    pub now: Instant,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = [1., 0., 0., 1.];
+    const BLUE: Color = [0., 0., 1., 1.];
+
+    fn close(a: Color, b: Color) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn without_stops() {
+        assert_eq!(Gradient::linear(0.).color_at(0.5), TRANSPARENT);
+        let single = Gradient::linear(0.).with_stop(0.3, RED);
+        assert_eq!(single.color_at(0.), RED);
+        assert_eq!(single.color_at(1.), RED);
+    }
+
+    #[test]
+    fn blends_between_stops() {
+        let gradient = Gradient::linear(0.)
+            .with_stop(0.25, RED)
+            .with_stop(0.75, BLUE);
+        // --- the ends keep the color of the closest stop
+        assert_eq!(gradient.color_at(-1.), RED);
+        assert_eq!(gradient.color_at(0.25), RED);
+        assert_eq!(gradient.color_at(0.75), BLUE);
+        assert_eq!(gradient.color_at(2.), BLUE);
+        assert!(close(gradient.color_at(0.5), [0.5, 0., 0.5, 1.]));
+        assert!(close(gradient.color_at(0.375), [0.75, 0., 0.25, 1.]));
+    }
+
+    #[test]
+    fn premultiplied() {
+        // --- fading out keeps the color instead of going through gray
+        let gradient = Gradient::linear(0.)
+            .with_stop(0., RED)
+            .with_stop(1., TRANSPARENT);
+        assert!(close(gradient.color_at(0.5), [1., 0., 0., 0.5]));
+        assert!(close(gradient.color_at(1.), TRANSPARENT));
+
+        let clear = Gradient::linear(0.)
+            .with_stop(0., [1., 0., 0., 0.])
+            .with_stop(1., [0., 0., 1., 0.]);
+        assert_eq!(clear.color_at(0.5), [0., 0., 1., 0.]);
+    }
+
+    #[test]
+    fn offsets_never_go_backwards() {
+        // --- the blue stop is moved up to the red one, making a hard edge
+        let gradient = Gradient::linear(0.)
+            .with_stop(0.5, RED)
+            .with_stop(0.2, BLUE);
+        assert_eq!(gradient.color_at(0.3), RED);
+        assert_eq!(gradient.color_at(0.5), RED);
+        assert_eq!(gradient.color_at(0.51), BLUE);
+
+        let hard = Gradient::linear(0.)
+            .with_stop(0., RED)
+            .with_stop(0.5, RED)
+            .with_stop(0.5, BLUE)
+            .with_stop(1., BLUE);
+        assert_eq!(hard.color_at(0.49), RED);
+        assert_eq!(hard.color_at(0.5), RED);
+        assert_eq!(hard.color_at(0.51), BLUE);
+    }
+
+    #[test]
+    fn params() {
+        assert_eq!(Gradient::linear(1.5).params(), [1., 1.5, 0., 0.]);
+        assert_eq!(Gradient::radial([0.2, 0.4]).params(), [2., 0.2, 0.4, 0.]);
+        assert_eq!(Gradient::circle([0.2, 0.4]).params(), [2., 0.2, 0.4, 1.]);
+        assert_eq!(Gradient::conic([0.5, 0.5], 3.).params(), [3., 0.5, 0.5, 3.]);
+    }
+}
@@ -0,0 +1,1293 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use winit::event_loop::EventLoopProxy;
+use winit::window::CursorIcon;
+
+use crate::animation::{Easing, Property, Transition};
+use crate::color;
+use crate::geometry::Transform;
+use crate::style::{Border, Color, Edges, Font, Gradient, Overflow, Shadow, Style, Styles};
+use crate::UserEvent;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pseudo {
+    Hover,
+    Active,
+    Focus,
+    Disabled,
+}
+
+// --- a compound selector like `button.primary#submit:hover`, `*` leaves `ty` empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selector {
+    pub ty: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub pseudo: Option<Pseudo>,
+}
+
+impl Selector {
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("empty selector".to_string());
+        }
+        if input.contains(char::is_whitespace) {
+            return Err(format!("combinators are not supported: `{input}`"));
+        }
+
+        let (compound, pseudo) = match input.split_once(':') {
+            Some((compound, pseudo)) => (compound, Some(Self::parse_pseudo(pseudo)?)),
+            None => (input, None),
+        };
+
+        let mut selector = Selector {
+            pseudo,
+            ..Default::default()
+        };
+        // --- a bare `:hover` is `*:hover`, like in CSS
+        if compound.is_empty() {
+            return Ok(selector);
+        }
+
+        // --- split right before every `.` & `#`, keeping the sigil with its name
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (idx, ch) in compound.char_indices().skip(1) {
+            if ch == '.' || ch == '#' {
+                parts.push(&compound[start..idx]);
+                start = idx;
+            }
+        }
+        parts.push(&compound[start..]);
+
+        for (idx, part) in parts.into_iter().enumerate() {
+            if let Some(class) = part.strip_prefix('.') {
+                selector.classes.push(Self::ident(class)?);
+            } else if let Some(id) = part.strip_prefix('#') {
+                selector.id = Some(Self::ident(id)?);
+            } else if idx == 0 && part == "*" {
+                // --- universal, matches every type
+            } else if idx == 0 {
+                selector.ty = Some(Self::ident(part)?);
+            } else {
+                return Err(format!("invalid selector `{input}`"));
+            }
+        }
+
+        Ok(selector)
+    }
+
+    fn parse_pseudo(input: &str) -> Result<Pseudo, String> {
+        match input {
+            "hover" => Ok(Pseudo::Hover),
+            "active" => Ok(Pseudo::Active),
+            "focus" => Ok(Pseudo::Focus),
+            "disabled" => Ok(Pseudo::Disabled),
+            other => Err(format!("unknown pseudo-class `:{other}`")),
+        }
+    }
+
+    fn ident(input: &str) -> Result<String, String> {
+        let valid = !input.is_empty()
+            && input
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+
+        if valid {
+            Ok(input.to_string())
+        } else {
+            Err(format!("invalid identifier `{input}`"))
+        }
+    }
+
+    // --- (ids, classes, types), compared lexicographically like CSS does
+    pub fn specificity(&self) -> (usize, usize, usize) {
+        (
+            self.id.is_some() as usize,
+            self.classes.len() + self.pseudo.is_some() as usize,
+            self.ty.is_some() as usize,
+        )
+    }
+
+    pub fn matches(&self, ty: &str, id: Option<&str>, classes: &[String]) -> bool {
+        self.ty.as_deref().is_none_or(|t| t == ty)
+            && self.id.as_deref().is_none_or(|i| Some(i) == id)
+            && self.classes.iter().all(|c| classes.contains(c))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub selector: Selector,
+    pub style: Style,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stylesheet {
+    pub rules: Vec<Rule>,
+}
+
+impl std::str::FromStr for Stylesheet {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+impl Stylesheet {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&source)?)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let source = strip_comments(input);
+        let line_at = |offset: usize| source[..offset].matches('\n').count() + 1;
+
+        let mut rules = Vec::new();
+        let mut rest = 0;
+
+        while let Some(open) = source[rest..].find('{').map(|i| i + rest) {
+            let close = source[open..]
+                .find('}')
+                .map(|i| i + open)
+                .ok_or_else(|| ParseError {
+                    line: line_at(open),
+                    message: "unclosed `{`".to_string(),
+                })?;
+
+            let error = |message: String| ParseError {
+                line: line_at(open),
+                message,
+            };
+
+            let selectors = source[rest..open]
+                .split(',')
+                .map(Selector::parse)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(error)?;
+
+            let style = parse_declarations(&source[open + 1..close]).map_err(|(at, message)| {
+                ParseError {
+                    line: line_at(open + 1 + at),
+                    message,
+                }
+            })?;
+
+            rules.extend(selectors.into_iter().map(|selector| Rule {
+                selector,
+                style: style.clone(),
+            }));
+
+            rest = close + 1;
+        }
+
+        if !source[rest..].trim().is_empty() {
+            return Err(ParseError {
+                line: line_at(rest + (source[rest..].len() - source[rest..].trim_start().len())),
+                message: "expected `{` after selector".to_string(),
+            });
+        }
+
+        Ok(Self { rules })
+    }
+
+    // --- every matching rule, by specificity then by source order, sorted into its variant
+    pub fn styles_for(&self, ty: &str, id: Option<&str>, classes: &[String]) -> Styles {
+        let mut matched = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.selector.matches(ty, id, classes))
+            .collect::<Vec<_>>();
+        matched.sort_by_key(|(order, rule)| (rule.selector.specificity(), *order));
+
+        let mut styles = Styles::default();
+        for (_, rule) in matched {
+            let slot = match rule.selector.pseudo {
+                None => {
+                    styles.base = styles.base.merge(&rule.style);
+                    continue;
+                }
+                Some(Pseudo::Hover) => &mut styles.hover,
+                Some(Pseudo::Active) => &mut styles.active,
+                Some(Pseudo::Focus) => &mut styles.focus,
+                Some(Pseudo::Disabled) => &mut styles.disabled,
+            };
+            *slot = Some(slot.take().unwrap_or_default().merge(&rule.style));
+        }
+
+        styles
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// --- the file & its modification time when last seen
+type Target = (PathBuf, Option<SystemTime>);
+
+// --- polls the modification time of the file the app loaded last, good enough for a
+// --- stylesheet and keeps us free of platform specific watcher dependencies. there's one
+// --- polling thread per app, it idles while pointed at nothing and exits once the watcher
+// --- or the event loop is gone
+#[derive(Debug, Default)]
+pub struct Watcher {
+    target: Arc<Mutex<Option<Target>>>,
+    started: bool,
+}
+
+impl Watcher {
+    // --- `None` stops watching, a change before this call isn't reported
+    pub fn set_path(&self, path: Option<PathBuf>) {
+        *self.target.lock().unwrap() = path.map(|path| {
+            let last = modified(&path);
+            (path, last)
+        });
+    }
+
+    // --- does nothing once started
+    pub fn start(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+
+        let target = Arc::downgrade(&self.target);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let Some(target) = target.upgrade() else {
+                break;
+            };
+            let changed = match target.lock().unwrap().as_mut() {
+                Some((path, last)) => {
+                    let now = modified(path);
+                    let changed = now != *last;
+                    *last = now;
+                    changed
+                }
+                None => false,
+            };
+
+            if changed && proxy.send_event(UserEvent::StylesheetChanged).is_err() {
+                // --- event loop is gone
+                break;
+            }
+        });
+    }
+}
+
+// --- replaces comments with spaces, newlines are kept so line numbers stay correct
+fn strip_comments(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("*/")
+            .map(|i| start + i + 2)
+            .unwrap_or(rest.len());
+        out.extend(
+            rest[start..end]
+                .chars()
+                .map(|ch| if ch == '\n' { '\n' } else { ' ' }),
+        );
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+
+    out
+}
+
+// --- on error, also returns the offset of the faulty declaration within the block
+fn parse_declarations(block: &str) -> Result<Style, (usize, String)> {
+    let mut style = Style::default();
+    let mut font: Option<Font> = None;
+    let mut offset = 0;
+
+    for declaration in block.split(';') {
+        let at = offset + (declaration.len() - declaration.trim_start().len());
+        offset += declaration.len() + 1;
+
+        let declaration = declaration.trim();
+        if declaration.is_empty() {
+            continue;
+        }
+
+        parse_declaration(declaration, &mut style, &mut font).map_err(|err| (at, err))?;
+    }
+
+    style.font = font;
+    Ok(style)
+}
+
+fn parse_declaration(
+    declaration: &str,
+    style: &mut Style,
+    font: &mut Option<Font>,
+) -> Result<(), String> {
+    let (name, value) = declaration
+        .split_once(':')
+        .ok_or_else(|| format!("expected `property: value`, found `{declaration}`"))?;
+    let (name, value) = (name.trim(), value.trim());
+
+    match name {
+        "background" if is_gradient(value) => {
+            style.background_gradient = Some(parse_gradient(value)?)
+        }
+        "background" | "background-color" => style.background = Some(parse_color(value)?),
+        "background-image" => style.background_gradient = parse_image(value)?,
+        "color" if is_gradient(value) => style.text_gradient = Some(parse_gradient(value)?),
+        "color" => style.foreground = Some(parse_color(value)?),
+        "border" => style.border = parse_border(value)?,
+        "border-image" => style.border_gradient = parse_image(value)?,
+        "border-radius" => style.radius = Some(parse_length(value)?),
+        "padding" => style.padding = Some(parse_edges(value)?),
+        "margin" => style.margin = Some(parse_edges(value)?),
+        "font-family" => font.get_or_insert_with(Font::default).family = parse_string(value),
+        "font-size" => font.get_or_insert_with(Font::default).size = parse_length(value)?,
+        "font-weight" => font.get_or_insert_with(Font::default).weight = parse_weight(value)?,
+        "box-shadow" => style.shadow = parse_shadow(value)?,
+        "transition" => style.transitions = Some(parse_transitions(value)?),
+        "opacity" => style.opacity = Some(parse_number(value)?.clamp(0., 1.)),
+        "cursor" => {
+            let cursor = value
+                .parse::<CursorIcon>()
+                .map_err(|_| format!("unknown cursor `{value}`"))?;
+            style.cursor = Some(cursor);
+        }
+        "overflow" => style.overflow = Some(parse_overflow(value)?),
+        "transform" => style.transform = parse_transform(value)?,
+        "transform-origin" => style.transform_origin = Some(parse_position(value)?),
+        "z-index" => {
+            let z_index = value
+                .parse::<i32>()
+                .map_err(|_| format!("invalid z-index `{value}`"))?;
+            style.z_index = Some(z_index);
+        }
+        "overlay" => {
+            let overlay = value
+                .parse::<bool>()
+                .map_err(|_| format!("expected `true` or `false`, found `{value}`"))?;
+            style.overlay = Some(overlay);
+        }
+        other => return Err(format!("unknown property `{other}`")),
+    }
+
+    Ok(())
+}
+
+fn parse_overflow(value: &str) -> Result<Overflow, String> {
+    match value {
+        "visible" => Ok(Overflow::Visible),
+        "hidden" | "clip" => Ok(Overflow::Hidden),
+        _ => Err(format!("invalid overflow `{value}`")),
+    }
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .map_err(|_| format!("expected a number, found `{value}`"))
+}
+
+fn parse_length(value: &str) -> Result<f32, String> {
+    parse_number(value.strip_suffix("px").unwrap_or(value))
+}
+
+// --- 1 to 4 values, same order as CSS: top right bottom left
+fn parse_edges(value: &str) -> Result<Edges, String> {
+    let values = value
+        .split_whitespace()
+        .map(parse_length)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match values[..] {
+        [all] => Ok(Edges::all(all)),
+        [vertical, horizontal] => Ok(Edges::symmetric(vertical, horizontal)),
+        [top, horizontal, bottom] => Ok(Edges {
+            top,
+            right: horizontal,
+            bottom,
+            left: horizontal,
+        }),
+        [top, right, bottom, left] => Ok(Edges {
+            top,
+            right,
+            bottom,
+            left,
+        }),
+        _ => Err(format!("expected 1 to 4 lengths, found `{value}`")),
+    }
+}
+
+fn parse_string(value: &str) -> String {
+    value.trim_matches(|ch| ch == '"' || ch == '\'').to_string()
+}
+
+fn parse_weight(value: &str) -> Result<u16, String> {
+    match value {
+        "normal" => Ok(400),
+        "bold" => Ok(700),
+        number => number
+            .parse::<u16>()
+            .map_err(|_| format!("invalid font-weight `{value}`")),
+    }
+}
+
+// --- `none`, or `<width> <color>`
+fn parse_border(value: &str) -> Result<Option<Border>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+
+    let (width, color) = value
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("expected `<width> <color>`, found `{value}`"))?;
+
+    Ok(Some(Border {
+        width: parse_length(width)?,
+        color: parse_color(color.trim())?,
+    }))
+}
+
+// --- `none`, or `<x> <y> <blur> <color>`
+fn parse_shadow(value: &str) -> Result<Option<Shadow>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+
+    let mut parts = value.splitn(4, char::is_whitespace);
+    let mut next = || {
+        parts
+            .next()
+            .ok_or_else(|| format!("expected `<x> <y> <blur> <color>`, found `{value}`"))
+    };
+
+    Ok(Some(Shadow {
+        offset: [parse_length(next()?)?, parse_length(next()?)?],
+        blur: parse_length(next()?)?,
+        color: parse_color(next()?.trim())?,
+    }))
+}
+
+// --- splits on commas that aren't inside parentheses, e.g. in `cubic-bezier(...)`
+fn split_top_level(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+
+    for (idx, ch) in value.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(value[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(value[start..].trim());
+
+    parts
+}
+
+fn is_gradient(value: &str) -> bool {
+    ["linear-gradient(", "radial-gradient(", "conic-gradient("]
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+}
+
+// --- `none`, or a gradient
+fn parse_image(value: &str) -> Result<Option<Gradient>, String> {
+    match value {
+        "none" => Ok(None),
+        _ => parse_gradient(value).map(Some),
+    }
+}
+
+// --- `linear-gradient([<angle> | to <side>,] <stops>)`,
+// --- `radial-gradient([circle | ellipse] [at <position>,] <stops>)`
+// --- & `conic-gradient([from <angle>] [at <position>,] <stops>)`
+fn parse_gradient(value: &str) -> Result<Gradient, String> {
+    let invalid = || format!("invalid gradient `{value}`");
+
+    let (name, args) = value.split_once('(').ok_or_else(invalid)?;
+    let args = split_top_level(args.strip_suffix(')').ok_or_else(invalid)?);
+    let (first, stops) = args.split_first().ok_or_else(invalid)?;
+
+    // --- the first argument is either the shape of the gradient or already a stop
+    let (mut gradient, stops) = match name {
+        "linear-gradient" => match parse_direction(first) {
+            Ok(angle) => (Gradient::linear(angle), stops),
+            Err(_) => (Gradient::linear(std::f32::consts::PI), &args[..]),
+        },
+        "radial-gradient" => match parse_radial(first) {
+            Ok(gradient) => (gradient, stops),
+            Err(_) => (Gradient::radial([0.5, 0.5]), &args[..]),
+        },
+        "conic-gradient" => match parse_conic(first) {
+            Ok(gradient) => (gradient, stops),
+            Err(_) => (Gradient::conic([0.5, 0.5], 0.), &args[..]),
+        },
+        _ => return Err(invalid()),
+    };
+    if stops.len() < 2 {
+        return Err(format!(
+            "a gradient needs at least 2 colors, found `{value}`"
+        ));
+    }
+
+    let stops = stops
+        .iter()
+        .map(|stop| parse_stop(stop))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut offsets = stops.iter().map(|stop| stop.0).collect::<Vec<_>>();
+
+    // --- the ends default to 0 & 1, stops without an offset are spread evenly
+    // --- between the ones around them that have one
+    let last = offsets.len() - 1;
+    offsets[0] = offsets[0].or(Some(0.));
+    offsets[last] = offsets[last].or(Some(1.));
+    for idx in 1..last {
+        if offsets[idx].is_none() {
+            let before = offsets[idx - 1].unwrap_or(0.);
+            let (next, after) = (idx + 1..=last)
+                .find_map(|n| offsets[n].map(|offset| (n, offset)))
+                .unwrap_or((last, 1.));
+            offsets[idx] = Some(before + (after - before) / (next - idx + 1) as f32);
+        }
+    }
+
+    for (offset, (_, color)) in offsets.into_iter().zip(stops) {
+        gradient = gradient.with_stop(offset.unwrap_or(1.), color);
+    }
+
+    Ok(gradient)
+}
+
+// --- `<color> [<percentage>]`
+fn parse_stop(value: &str) -> Result<(Option<f32>, Color), String> {
+    if let Some((color, offset)) = value.rsplit_once(char::is_whitespace) {
+        if let Some(percent) = offset.strip_suffix('%') {
+            return Ok((
+                Some(parse_number(percent)? / 100.),
+                parse_color(color.trim())?,
+            ));
+        }
+    }
+    Ok((None, parse_color(value)?))
+}
+
+// --- radians clockwise from up, from `deg`, `turn` or `rad`
+fn parse_angle(value: &str) -> Result<f32, String> {
+    if let Some(deg) = value.strip_suffix("deg") {
+        Ok(parse_number(deg)?.to_radians())
+    } else if let Some(turn) = value.strip_suffix("turn") {
+        Ok(parse_number(turn)? * std::f32::consts::TAU)
+    } else if let Some(rad) = value.strip_suffix("rad") {
+        parse_number(rad)
+    } else if value == "0" {
+        Ok(0.)
+    } else {
+        Err(format!("invalid angle `{value}`"))
+    }
+}
+
+// --- an angle or `to <side>`, where the corners are taken as 45° off the sides
+fn parse_direction(value: &str) -> Result<f32, String> {
+    let Some(sides) = value.strip_prefix("to ") else {
+        return parse_angle(value);
+    };
+
+    let [mut x, mut y] = [0., 0.];
+    for side in sides.split_whitespace() {
+        match side {
+            "top" => y -= 1.,
+            "bottom" => y += 1.,
+            "left" => x -= 1.,
+            "right" => x += 1.,
+            _ => return Err(format!("invalid direction `{value}`")),
+        }
+    }
+    if x == 0. && y == 0. {
+        return Err(format!("invalid direction `{value}`"));
+    }
+    Ok(f32::atan2(x, -y).rem_euclid(std::f32::consts::TAU))
+}
+
+// --- `[circle | ellipse] [at <position>]`
+fn parse_radial(value: &str) -> Result<Gradient, String> {
+    let (shape, position) = match value.split_once("at ") {
+        Some((shape, position)) => (shape.trim(), parse_position(position)?),
+        None => (value, [0.5, 0.5]),
+    };
+    match shape {
+        "" | "ellipse" => Ok(Gradient::radial(position)),
+        "circle" => Ok(Gradient::circle(position)),
+        _ => Err(format!("invalid radial gradient shape `{value}`")),
+    }
+}
+
+// --- `[from <angle>] [at <position>]`
+fn parse_conic(value: &str) -> Result<Gradient, String> {
+    let (from, position) = match value.split_once("at ") {
+        Some((from, position)) => (from.trim(), parse_position(position)?),
+        None => (value, [0.5, 0.5]),
+    };
+    let angle = match from {
+        "" => 0.,
+        from => parse_angle(
+            from.strip_prefix("from ")
+                .ok_or_else(|| format!("invalid conic gradient `{value}`"))?
+                .trim(),
+        )?,
+    };
+    Ok(Gradient::conic(position, angle))
+}
+
+// --- `<x> [<y>]`, as percentages of the box or keywords
+fn parse_position(value: &str) -> Result<[f32; 2], String> {
+    let parse = |part: &str| match part {
+        "left" | "top" => Ok(0.),
+        "center" => Ok(0.5),
+        "right" | "bottom" => Ok(1.),
+        part => match part.strip_suffix('%') {
+            Some(percent) => Ok(parse_number(percent)? / 100.),
+            None => Err(format!("invalid position `{value}`")),
+        },
+    };
+
+    let parts = value.split_whitespace().collect::<Vec<_>>();
+    match parts[..] {
+        // --- a lone vertical keyword still means the vertical position
+        ["top"] | ["bottom"] => Ok([0.5, parse(parts[0])?]),
+        [x] => Ok([parse(x)?, 0.5]),
+        // --- vertical keywords may come first, like `top left`
+        [y @ ("top" | "bottom"), x] | [x, y] => Ok([parse(x)?, parse(y)?]),
+        _ => Err(format!("invalid position `{value}`")),
+    }
+}
+
+// --- `none`, or functions applied right to left like in CSS: `translate(<x>, [<y>])`,
+// --- `translateX`, `translateY`, `rotate(<angle>)`, `scale(<x>, [<y>])`, `scaleX`, `scaleY`,
+// --- `skew(<angle>, [<angle>])`, `skewX`, `skewY` & `matrix(<a>, .., <f>)`
+fn parse_transform(value: &str) -> Result<Option<Transform>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+
+    let mut transform = Transform::IDENTITY;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let invalid = || format!("invalid transform `{value}`");
+        let (name, after) = rest.split_once('(').ok_or_else(invalid)?;
+        let (args, after) = after.split_once(')').ok_or_else(invalid)?;
+        rest = after.trim_start();
+
+        let args = args.split(',').map(str::trim).collect::<Vec<_>>();
+        let lengths = || {
+            args.iter()
+                .map(|arg| parse_length(arg))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let numbers = || {
+            args.iter()
+                .map(|arg| parse_number(arg))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let angles = || {
+            args.iter()
+                .map(|arg| parse_angle(arg))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let next = match (name.trim(), args.len()) {
+            ("translate", 1 | 2) => {
+                let lengths = lengths()?;
+                Transform::translate(lengths[0], lengths.get(1).copied().unwrap_or(0.))
+            }
+            ("translateX", 1) => Transform::translate(lengths()?[0], 0.),
+            ("translateY", 1) => Transform::translate(0., lengths()?[0]),
+            ("rotate", 1) => Transform::rotate(angles()?[0]),
+            ("scale", 1 | 2) => {
+                let numbers = numbers()?;
+                Transform::scale(numbers[0], numbers.get(1).copied().unwrap_or(numbers[0]))
+            }
+            ("scaleX", 1) => Transform::scale(numbers()?[0], 1.),
+            ("scaleY", 1) => Transform::scale(1., numbers()?[0]),
+            ("skew", 1 | 2) => {
+                let angles = angles()?;
+                Transform::skew(angles[0], angles.get(1).copied().unwrap_or(0.))
+            }
+            ("skewX", 1) => Transform::skew(angles()?[0], 0.),
+            ("skewY", 1) => Transform::skew(0., angles()?[0]),
+            ("matrix", 6) => {
+                let [a, b, c, d, e, f] = numbers()?[..] else {
+                    return Err(invalid());
+                };
+                Transform::new(a, b, c, d, e, f)
+            }
+            _ => return Err(invalid()),
+        };
+        transform = next.then(&transform);
+    }
+
+    Ok(Some(transform))
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let micros = match value.strip_suffix("ms") {
+        Some(ms) => parse_number(ms)? * 1e3,
+        None => parse_number(value.strip_suffix('s').unwrap_or(value))? * 1e6,
+    };
+
+    if micros.is_finite() && micros >= 0. {
+        Ok(Duration::from_micros(micros.round() as u64))
+    } else {
+        Err(format!("invalid duration `{value}`"))
+    }
+}
+
+fn parse_property(value: &str) -> Result<Option<Property>, String> {
+    let property = match value {
+        "all" => return Ok(None),
+        "left" => Property::Left,
+        "top" => Property::Top,
+        "width" => Property::Width,
+        "height" => Property::Height,
+        "background" | "background-color" => Property::Background,
+        "color" => Property::Foreground,
+        "border-color" => Property::BorderColor,
+        "border-width" => Property::BorderWidth,
+        "border-radius" => Property::Radius,
+        "opacity" => Property::Opacity,
+        "transform" => Property::Transform,
+        other => return Err(format!("`{other}` cannot be transitioned")),
+    };
+
+    Ok(Some(property))
+}
+
+fn parse_easing(value: &str) -> Result<Easing, String> {
+    let args = |name: &str| {
+        value
+            .strip_prefix(name)
+            .and_then(|v| v.strip_prefix('('))
+            .and_then(|v| v.strip_suffix(')'))
+            .map(|v| {
+                v.split(',')
+                    .map(|arg| parse_number(arg.trim()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+    };
+
+    match value {
+        "linear" => return Ok(Easing::Linear),
+        "ease" => return Ok(Easing::Ease),
+        "ease-in" => return Ok(Easing::EaseIn),
+        "ease-out" => return Ok(Easing::EaseOut),
+        "ease-in-out" => return Ok(Easing::EaseInOut),
+        _ => (),
+    }
+
+    if let Some(args) = args("cubic-bezier") {
+        if let [x1, y1, x2, y2] = args?[..] {
+            return Ok(Easing::CubicBezier(x1, y1, x2, y2));
+        }
+    }
+    if let Some(args) = args("spring") {
+        if let [stiffness, damping, mass] = args?[..] {
+            return Ok(Easing::Spring {
+                stiffness,
+                damping,
+                mass,
+            });
+        }
+    }
+
+    Err(format!("invalid easing `{value}`"))
+}
+
+// --- `<property> <duration> [<easing>] [<delay>]`, comma separated
+fn parse_transitions(value: &str) -> Result<Vec<Transition>, String> {
+    split_top_level(value)
+        .into_iter()
+        .map(|transition| {
+            // --- easing functions may contain spaces after their commas
+            let mut parts = Vec::new();
+            let mut rest = transition;
+            while !rest.is_empty() {
+                let end = match rest.find('(') {
+                    Some(open) if rest[..open].find(char::is_whitespace).is_none() => {
+                        rest.find(')').map(|close| close + 1).unwrap_or(rest.len())
+                    }
+                    _ => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+                };
+                parts.push(&rest[..end]);
+                rest = rest[end..].trim_start();
+            }
+
+            let (property, duration, rest) = match parts[..] {
+                [property, duration, ref rest @ ..] => (property, duration, rest),
+                _ => {
+                    return Err(format!(
+                        "expected `<property> <duration>`, found `{transition}`"
+                    ))
+                }
+            };
+
+            let mut out = Transition::new(parse_property(property)?, parse_duration(duration)?);
+            for part in rest {
+                match parse_duration(part) {
+                    Ok(delay) => out.delay = delay,
+                    Err(_) => out.easing = parse_easing(part)?,
+                }
+            }
+
+            Ok(out)
+        })
+        .collect()
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    color::parse(value).ok_or_else(|| format!("invalid color `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::GradientKind;
+
+    fn selector(input: &str) -> Selector {
+        Selector::parse(input).unwrap()
+    }
+
+    fn error(input: &str) -> ParseError {
+        Stylesheet::parse(input).unwrap_err()
+    }
+
+    fn style(block: &str) -> Style {
+        parse_declarations(block).unwrap()
+    }
+
+    #[test]
+    fn compound_selector() {
+        assert_eq!(
+            selector("button.primary.large#submit:hover"),
+            Selector {
+                ty: Some("button".into()),
+                id: Some("submit".into()),
+                classes: vec!["primary".into(), "large".into()],
+                pseudo: Some(Pseudo::Hover),
+            }
+        );
+        assert_eq!(
+            selector("#submit.primary"),
+            Selector {
+                id: Some("submit".into()),
+                classes: vec!["primary".into()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn universal_selector() {
+        assert_eq!(selector("*"), Selector::default());
+        assert_eq!(
+            selector("*.primary"),
+            Selector {
+                classes: vec!["primary".into()],
+                ..Default::default()
+            }
+        );
+        assert!(selector("*").matches("button", None, &[]));
+    }
+
+    #[test]
+    fn bare_pseudo_matches_every_type() {
+        let hover = selector(":hover");
+        assert_eq!(hover, selector("*:hover"));
+        assert_eq!(hover.pseudo, Some(Pseudo::Hover));
+        assert!(hover.matches("text_input", None, &[]));
+        assert_eq!(hover.specificity(), (0, 1, 0));
+    }
+
+    #[test]
+    fn invalid_selectors() {
+        for (input, message) in [
+            ("", "empty selector"),
+            ("div p", "combinators are not supported: `div p`"),
+            (":visited", "unknown pseudo-class `:visited`"),
+            ("div:", "unknown pseudo-class `:`"),
+            ("div.", "invalid identifier ``"),
+            ("a..b", "invalid identifier ``"),
+            ("div#", "invalid identifier ``"),
+            ("a*", "invalid identifier `a*`"),
+            ("div.a*", "invalid identifier `a*`"),
+        ] {
+            assert_eq!(Selector::parse(input), Err(message.to_string()), "{input}");
+        }
+    }
+
+    #[test]
+    fn specificity() {
+        assert_eq!(selector("button").specificity(), (0, 0, 1));
+        assert_eq!(selector("button.a:focus").specificity(), (0, 2, 1));
+        assert_eq!(selector("#id").specificity(), (1, 0, 0));
+        assert!(selector("#id").specificity() > selector(".a.b.c").specificity());
+        assert!(selector(".a").specificity() > selector("button").specificity());
+    }
+
+    #[test]
+    fn matching() {
+        let classes = ["a".to_string(), "b".to_string()];
+        assert!(selector("button.a.b#ok").matches("button", Some("ok"), &classes));
+        assert!(!selector("button.c").matches("button", None, &classes));
+        assert!(!selector("#ok").matches("button", Some("cancel"), &classes));
+        assert!(!selector("container").matches("button", None, &classes));
+    }
+
+    #[test]
+    fn cascade() {
+        let sheet = Stylesheet::parse(
+            ".a { background: red; opacity: 0.5 }
+             button { background: blue; color: white }
+             button { color: black }
+             .a:hover, #ok:active { border-radius: 4px }",
+        )
+        .unwrap();
+        assert_eq!(sheet.rules.len(), 5);
+
+        let styles = sheet.styles_for("button", None, &["a".to_string()]);
+        // --- the class beats the type, even though it comes first
+        assert_eq!(styles.base.background, Some([1., 0., 0., 1.]));
+        // --- same specificity, the later one wins
+        assert_eq!(styles.base.foreground, Some([0., 0., 0., 1.]));
+        assert_eq!(styles.base.opacity, Some(0.5));
+        assert_eq!(styles.hover.and_then(|s| s.radius), Some(4.));
+        assert_eq!(styles.active, None);
+    }
+
+    #[test]
+    fn declarations() {
+        let style = style(
+            "padding: 1 2; margin: 1px 2px 3px 4px; border: 2px #ff000080;
+             font-size: 14px; font-weight: bold; font-family: 'Mono';
+             box-shadow: 0 2px 4px rgba(0, 0, 0, 0.5); z-index: -1; overlay: true;
+             overflow: clip; cursor: pointer; opacity: 2;",
+        );
+        assert_eq!(style.padding, Some(Edges::symmetric(1., 2.)));
+        assert_eq!(
+            style.margin,
+            Some(Edges {
+                top: 1.,
+                right: 2.,
+                bottom: 3.,
+                left: 4.,
+            })
+        );
+        assert_eq!(
+            style.border,
+            Some(Border {
+                width: 2.,
+                color: [1., 0., 0., 128. / 255.],
+            })
+        );
+        assert_eq!(
+            style.font,
+            Some(Font {
+                family: "Mono".into(),
+                size: 14.,
+                weight: 700,
+            })
+        );
+        assert_eq!(
+            style.shadow,
+            Some(Shadow {
+                offset: [0., 2.],
+                blur: 4.,
+                color: [0., 0., 0., 0.5],
+            })
+        );
+        assert_eq!(style.z_index, Some(-1));
+        assert_eq!(style.overlay, Some(true));
+        assert_eq!(style.overflow, Some(Overflow::Hidden));
+        assert_eq!(style.cursor, Some(CursorIcon::Pointer));
+        assert_eq!(style.opacity, Some(1.));
+    }
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(
+            style("color: RebeccaPurple").foreground,
+            Some([102. / 255., 51. / 255., 153. / 255., 1.])
+        );
+        assert_eq!(style("background: transparent").background, Some([0.; 4]));
+        assert_eq!(
+            parse_color("no-such-color"),
+            Err("invalid color `no-such-color`".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_declarations() {
+        for (block, message) in [
+            ("width: 10px", "unknown property `width`"),
+            ("color red", "expected `property: value`, found `color red`"),
+            (
+                "padding: 1 2 3 4 5",
+                "expected 1 to 4 lengths, found `1 2 3 4 5`",
+            ),
+            ("opacity: half", "expected a number, found `half`"),
+            ("z-index: 1.5", "invalid z-index `1.5`"),
+            ("overlay: yes", "expected `true` or `false`, found `yes`"),
+            ("font-weight: heavy", "invalid font-weight `heavy`"),
+            ("cursor: spinner", "unknown cursor `spinner`"),
+        ] {
+            assert_eq!(
+                parse_declarations(block).map_err(|(_, message)| message),
+                Err(message.to_string()),
+                "{block}"
+            );
+        }
+    }
+
+    #[test]
+    fn error_lines() {
+        assert_eq!(
+            error("button {\n  color: red;\n  width: 1px;\n}"),
+            ParseError {
+                line: 3,
+                message: "unknown property `width`".into(),
+            }
+        );
+        // --- comments keep their newlines
+        assert_eq!(error("/* one\ntwo */\ndiv p { }").line, 3);
+        assert_eq!(
+            error("button { color: red }\n\nbutton"),
+            ParseError {
+                line: 3,
+                message: "expected `{` after selector".into(),
+            }
+        );
+        assert_eq!(
+            error("a {}\nbutton { color: red"),
+            ParseError {
+                line: 2,
+                message: "unclosed `{`".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let sheet =
+            Stylesheet::parse("/* a { color: red } */ b { /* c: d */ color: red }").unwrap();
+        assert_eq!(sheet.rules.len(), 1);
+        assert_eq!(sheet.rules[0].selector, selector("b"));
+    }
+
+    fn gradient(value: &str) -> Gradient {
+        parse_gradient(value).unwrap()
+    }
+
+    fn offsets(gradient: &Gradient) -> Vec<f32> {
+        gradient.stops.iter().map(|(offset, _)| *offset).collect()
+    }
+
+    fn angle(gradient: &Gradient) -> f32 {
+        match gradient.kind {
+            GradientKind::Linear { angle } | GradientKind::Conic { angle, .. } => angle,
+            GradientKind::Radial { .. } => panic!("radial gradients have no angle"),
+        }
+    }
+
+    #[test]
+    fn linear_gradients() {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+        // --- top to bottom by default
+        assert_eq!(
+            gradient("linear-gradient(red, blue)"),
+            Gradient::linear(PI)
+                .with_stop(0., [1., 0., 0., 1.])
+                .with_stop(1., [0., 0., 1., 1.])
+        );
+        for (value, expected) in [
+            ("linear-gradient(90deg, red, blue)", FRAC_PI_2),
+            ("linear-gradient(0.25turn, red, blue)", FRAC_PI_2),
+            ("linear-gradient(0, red, blue)", 0.),
+            ("linear-gradient(to right, red, blue)", FRAC_PI_2),
+            ("linear-gradient(to top right, red, blue)", FRAC_PI_4),
+            ("linear-gradient(to bottom, red, blue)", PI),
+            ("linear-gradient(to left, red, blue)", 3. * FRAC_PI_2),
+        ] {
+            assert!((angle(&gradient(value)) - expected).abs() < 1e-5, "{value}");
+        }
+    }
+
+    #[test]
+    fn gradient_stops() {
+        assert_eq!(
+            offsets(&gradient("linear-gradient(red 20%, blue)")),
+            [0.2, 1.]
+        );
+        // --- stops without an offset are spread between the ones that have one
+        assert_eq!(
+            offsets(&gradient(
+                "linear-gradient(red, lime, blue 50%, white, black)"
+            )),
+            [0., 0.25, 0.5, 0.75, 1.]
+        );
+        let spread = offsets(&gradient("linear-gradient(red, lime, blue, white)"));
+        assert!((spread[1] - 1. / 3.).abs() < 1e-6 && (spread[2] - 2. / 3.).abs() < 1e-6);
+
+        // --- commas inside a color don't split the stops
+        let translucent = gradient("linear-gradient(rgba(0, 0, 0, 0.5) 10%, #fff)");
+        assert_eq!(
+            translucent.stops,
+            [(0.1, [0., 0., 0., 0.5]), (1., [1., 1., 1., 1.])]
+        );
+    }
+
+    #[test]
+    fn radial_and_conic_gradients() {
+        assert_eq!(
+            gradient("radial-gradient(red, blue)").kind,
+            Gradient::radial([0.5, 0.5]).kind
+        );
+        assert_eq!(
+            gradient("radial-gradient(circle at 25% top, red, blue)").kind,
+            Gradient::circle([0.25, 0.]).kind
+        );
+        assert_eq!(
+            gradient("radial-gradient(ellipse at bottom, red, blue)").kind,
+            Gradient::radial([0.5, 1.]).kind
+        );
+        assert_eq!(
+            gradient("conic-gradient(red, blue)").kind,
+            Gradient::conic([0.5, 0.5], 0.).kind
+        );
+        let conic = gradient("conic-gradient(from 90deg at left, red, blue)");
+        assert!((angle(&conic) - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert!(matches!(
+            conic.kind,
+            GradientKind::Conic {
+                center: [0., 0.5],
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn invalid_gradients() {
+        for (value, message) in [
+            (
+                "linear-gradient(red)",
+                "a gradient needs at least 2 colors, found `linear-gradient(red)`",
+            ),
+            (
+                "linear-gradient(90deg, red)",
+                "a gradient needs at least 2 colors, found `linear-gradient(90deg, red)`",
+            ),
+            ("linear-gradient(red, nope)", "invalid color `nope`"),
+            ("linear-gradient(to up, red, blue)", "invalid color `to up`"),
+            (
+                "linear-gradient(red, blue",
+                "invalid gradient `linear-gradient(red, blue`",
+            ),
+            (
+                "repeating-linear-gradient(red, blue)",
+                "invalid gradient `repeating-linear-gradient(red, blue)`",
+            ),
+            (
+                "radial-gradient(square, red, blue)",
+                "invalid color `square`",
+            ),
+        ] {
+            assert_eq!(parse_gradient(value), Err(message.to_string()), "{value}");
+        }
+    }
+
+    #[test]
+    fn gradient_properties() {
+        let declared = style(
+            "background: linear-gradient(red, blue); color: radial-gradient(red, blue);
+             border-image: conic-gradient(red, blue)",
+        );
+        assert!(declared.background_gradient.is_some());
+        assert_eq!(declared.background, None);
+        assert!(declared.text_gradient.is_some());
+        assert_eq!(declared.foreground, None);
+        assert!(declared.border_gradient.is_some());
+
+        assert_eq!(style("background-image: none").background_gradient, None);
+    }
+
+    #[test]
+    fn transforms() {
+        let transform = |value: &str| parse_transform(value).unwrap().unwrap();
+
+        // --- applied right to left: scaled first, then moved
+        let list = transform("translate(10px, 20px) scale(2)");
+        assert_eq!(list.apply([1., 1.]), [12., 22.]);
+        assert_eq!(
+            transform("translateX(5px) translateY(3)"),
+            Transform::translate(5., 3.)
+        );
+        assert_eq!(transform("scale(2, 3)"), Transform::scale(2., 3.));
+        assert_eq!(
+            transform("matrix(1, 2, 3, 4, 5, 6)"),
+            Transform::new(1., 2., 3., 4., 5., 6.)
+        );
+        let [x, y] = transform("rotate(0.25turn)").apply([1., 0.]);
+        assert!(x.abs() < 1e-6 && (y - 1.).abs() < 1e-6);
+        assert_eq!(parse_transform("none"), Ok(None));
+
+        let declared = style("transform: skewX(0); transform-origin: left top");
+        assert_eq!(declared.transform, Some(Transform::IDENTITY));
+        assert_eq!(declared.transform_origin, Some([0., 0.]));
+    }
+
+    #[test]
+    fn invalid_transforms() {
+        for (value, message) in [
+            ("spin(1)", "invalid transform `spin(1)`"),
+            ("scale(2", "invalid transform `scale(2`"),
+            ("scale(2) 5", "invalid transform `scale(2) 5`"),
+            (
+                "translate(1, 2, 3)",
+                "invalid transform `translate(1, 2, 3)`",
+            ),
+            ("rotate(90)", "invalid angle `90`"),
+            ("translate(a)", "expected a number, found `a`"),
+        ] {
+            assert_eq!(parse_transform(value), Err(message.to_string()), "{value}");
+        }
+    }
+}
@@ -0,0 +1,1347 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::Path as FilePath;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use roxmltree::{Document, Node};
+
+use crate::color;
+use crate::geometry::{Rect, Transform};
+use crate::path::{self, FillRule, LineCap, LineJoin, Mesh, Path, Stroke};
+use crate::style::Color;
+use crate::text::Bitmap;
+use crate::texture::Texture;
+
+// --- samples per pixel along each side when rasterizing, 16 in all
+const SAMPLES: usize = 4;
+// --- sizes one picture is kept rasterized at, the least recently drawn goes past that
+const CACHED_SIZES: usize = 8;
+// --- how deep gradients may point at each other through `href`
+const MAX_HREF: usize = 8;
+const XLINK: &str = "http://www.w3.org/1999/xlink";
+
+#[derive(Debug, Clone)]
+pub enum SvgError {
+    Io(Arc<std::io::Error>),
+    // --- not well-formed XML
+    Xml(String),
+    // --- XML, but not an SVG this can draw
+    Format(String),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Xml(message) => write!(f, "can't parse SVG: {}", message),
+            Self::Format(message) => write!(f, "can't draw SVG: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+impl From<std::io::Error> for SvgError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(Arc::new(err))
+    }
+}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(err: roxmltree::Error) -> Self {
+        Self::Xml(err.to_string())
+    }
+}
+
+// --- a vector picture read from SVG: paths, basic shapes, groups, transforms, solid
+// --- fills & strokes and linear gradients. text, images, filters, masks & clips are left
+// --- out. clones share the shapes & the rasterized copies
+#[derive(Clone)]
+pub struct Svg {
+    view_box: Rect,
+    width: f32,
+    height: f32,
+    shapes: Rc<Vec<Shape>>,
+    // --- most recently drawn last
+    cache: Rc<RefCell<Vec<(CacheKey, Texture)>>>,
+}
+
+impl fmt::Debug for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Svg")
+            .field("view_box", &self.view_box)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("shapes", &self.shapes.len())
+            .finish()
+    }
+}
+
+impl PartialEq for Svg {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.shapes, &other.shapes)
+    }
+}
+
+// --- width & height in px, and `currentColor`
+type CacheKey = (u32, u32, [u32; 4]);
+
+impl Svg {
+    pub fn parse(text: &str) -> Result<Self, SvgError> {
+        let document = Document::parse(text)?;
+        let root = document.root_element();
+        if root.tag_name().name() != "svg" {
+            return Err(SvgError::Format("the root element isn't `svg`".to_string()));
+        }
+
+        let width = root.attribute("width").and_then(length);
+        let height = root.attribute("height").and_then(length);
+        let view_box = match root.attribute("viewBox").map(numbers) {
+            Some(values) if values.len() == 4 && values[2] > 0. && values[3] > 0. => {
+                Rect::new(values[0], values[1], values[2], values[3])
+            }
+            _ => match (width, height) {
+                (Some(width), Some(height)) => Rect::new(0., 0., width, height),
+                _ => return Err(SvgError::Format("no viewBox or size".to_string())),
+            },
+        };
+
+        let mut parser = Parser {
+            gradients: HashMap::new(),
+            view_box,
+            shapes: Vec::new(),
+        };
+        for node in root.descendants() {
+            if let Some(id) = node.attribute("id") {
+                if node.tag_name().name() == "linearGradient" {
+                    parser.gradients.insert(id, node);
+                }
+            }
+        }
+        // --- presentation attributes on the root apply to everything, like on a group
+        let inherited = inherit(root, &Inherited::default());
+        parser.children(root, &inherited, &Transform::IDENTITY);
+
+        Ok(Self {
+            view_box,
+            width: width.unwrap_or(view_box.width),
+            height: height.unwrap_or(view_box.height),
+            shapes: Rc::new(parser.shapes),
+            cache: Rc::default(),
+        })
+    }
+
+    pub fn open(path: impl AsRef<FilePath>) -> Result<Self, SvgError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    // --- the size it asks for, in px
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    // --- drawn into a bitmap of that size, kept in proportion & centered in it.
+    // --- `current` is what `currentColor` means
+    pub fn rasterize(&self, width: u32, height: u32, current: Color) -> Bitmap {
+        let (w, h) = (width as usize, height as usize);
+        // --- premultiplied
+        let mut pixels = vec![[0f32; 4]; w * h];
+
+        let vb = self.view_box;
+        let scale = (width as f32 / vb.width).min(height as f32 / vb.height);
+        let view = Transform::translate(-vb.x, -vb.y)
+            .then(&Transform::scale(scale, scale))
+            .then(&Transform::translate(
+                (width as f32 - vb.width * scale) / 2.,
+                (height as f32 - vb.height * scale) / 2.,
+            ));
+        let from_pixels = view.invert().unwrap_or_default();
+
+        for shape in self.shapes.iter() {
+            let outline = shape.path.transform(&view);
+            if let Some((paint, rule)) = &shape.fill {
+                let mesh = path::fill(&outline, *rule, [1.; 4]);
+                paint_mesh(&mut pixels, w, h, &mesh, paint, current, &from_pixels);
+            }
+            if let Some((paint, stroke)) = &shape.stroke {
+                let stroke = Stroke {
+                    width: stroke.width * scale,
+                    dashes: stroke.dashes.iter().map(|dash| dash * scale).collect(),
+                    dash_offset: stroke.dash_offset * scale,
+                    ..stroke.clone()
+                };
+                let mesh = path::stroke(&outline, &stroke, [1.; 4]);
+                paint_mesh(&mut pixels, w, h, &mesh, paint, current, &from_pixels);
+            }
+        }
+
+        let rgba = pixels
+            .iter()
+            .flat_map(|[r, g, b, a]| {
+                let unpremultiply = if *a > 0. { 1. / a } else { 0. };
+                [r * unpremultiply, g * unpremultiply, b * unpremultiply, *a]
+                    .map(|i| (i.clamp(0., 1.) * 255.).round() as u8)
+            })
+            .collect();
+
+        Bitmap {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    // --- rasterized once per size & color, and uploaded once as long as it keeps being drawn
+    pub fn texture(&self, width: u32, height: u32, current: Color) -> Texture {
+        let key = (width, height, current.map(f32::to_bits));
+        let mut cache = self.cache.borrow_mut();
+
+        if let Some(idx) = cache.iter().position(|(cached, _)| *cached == key) {
+            let entry = cache.remove(idx);
+            cache.push(entry);
+        } else {
+            if cache.len() >= CACHED_SIZES {
+                cache.remove(0);
+            }
+            cache.push((key, Texture::new(self.rasterize(width, height, current))));
+        }
+        cache.last().unwrap().1.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Paint {
+    Color(Color),
+    CurrentColor(f32),
+    Linear(Gradient),
+}
+
+#[derive(Debug, Clone)]
+struct Gradient {
+    start: [f32; 2],
+    end: [f32; 2],
+    // --- offsets in order, colors with the opacity already applied
+    stops: Vec<(f32, Color)>,
+    // --- from the viewBox into the space start & end are in
+    inverse: Transform,
+}
+
+impl Gradient {
+    fn color_at(&self, point: [f32; 2]) -> Color {
+        let [x, y] = self.inverse.apply(point);
+        let d = [self.end[0] - self.start[0], self.end[1] - self.start[1]];
+        let len2 = d[0] * d[0] + d[1] * d[1];
+        let t = match len2 > 0. {
+            true => ((x - self.start[0]) * d[0] + (y - self.start[1]) * d[1]) / len2,
+            false => 1.,
+        };
+
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return [0.; 4];
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((o0, c0), (o1, c1)) = (pair[0], pair[1]);
+            if t <= o1 {
+                let f = if o1 > o0 { (t - o0) / (o1 - o0) } else { 1. };
+                return std::array::from_fn(|i| c0[i] + (c1[i] - c0[i]) * f);
+            }
+        }
+        last.1
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Shape {
+    // --- in viewBox units, with every transform above it applied
+    path: Path,
+    fill: Option<(Paint, FillRule)>,
+    stroke: Option<(Paint, Stroke)>,
+}
+
+// --- what a child gets from its ancestors when it doesn't say otherwise
+#[derive(Debug, Clone)]
+struct Inherited<'a> {
+    fill: Option<&'a str>,
+    stroke: Option<&'a str>,
+    fill_rule: FillRule,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    cap: LineCap,
+    join: LineJoin,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+    // --- group opacity is multiplied down onto every shape, so overlapping shapes
+    // --- inside a translucent group show through each other
+    opacity: f32,
+}
+
+impl Default for Inherited<'_> {
+    fn default() -> Self {
+        Self {
+            fill: Some("black"),
+            stroke: None,
+            fill_rule: FillRule::NonZero,
+            fill_opacity: 1.,
+            stroke_opacity: 1.,
+            stroke_width: 1.,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            dashes: vec![],
+            dash_offset: 0.,
+            opacity: 1.,
+        }
+    }
+}
+
+struct Parser<'a, 'input> {
+    gradients: HashMap<&'a str, Node<'a, 'input>>,
+    view_box: Rect,
+    shapes: Vec<Shape>,
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+    fn children(&mut self, node: Node<'a, 'input>, inherited: &Inherited<'a>, ctm: &Transform) {
+        for child in node.children().filter(Node::is_element) {
+            self.element(child, inherited, ctm);
+        }
+    }
+
+    fn element(&mut self, node: Node<'a, 'input>, inherited: &Inherited<'a>, ctm: &Transform) {
+        let name = node.tag_name().name();
+        if property(node, "display") == Some("none") {
+            return;
+        }
+
+        let ctm = match node.attribute("transform") {
+            Some(transform) => parse_transform(transform).then(ctm),
+            None => *ctm,
+        };
+        let inherited = inherit(node, inherited);
+
+        let outline = match name {
+            "g" | "a" => return self.children(node, &inherited, &ctm),
+            "path" => node.attribute("d").map(parse_path_data),
+            "rect" => rect(node),
+            "circle" => {
+                let r = number_attr(node, "r");
+                (r > 0.).then(|| ellipse(number_attr(node, "cx"), number_attr(node, "cy"), r, r))
+            }
+            "ellipse" => {
+                let (rx, ry) = (number_attr(node, "rx"), number_attr(node, "ry"));
+                (rx > 0. && ry > 0.)
+                    .then(|| ellipse(number_attr(node, "cx"), number_attr(node, "cy"), rx, ry))
+            }
+            "line" => {
+                let mut path = Path::new();
+                path.move_to(number_attr(node, "x1"), number_attr(node, "y1"))
+                    .line_to(number_attr(node, "x2"), number_attr(node, "y2"));
+                Some(path)
+            }
+            "polyline" | "polygon" => node.attribute("points").map(|points| {
+                let mut path = Path::new();
+                for point in numbers(points).chunks_exact(2) {
+                    path.line_to(point[0], point[1]);
+                }
+                if name == "polygon" {
+                    path.close();
+                }
+                path
+            }),
+            // --- definitions, text & whatever else isn't drawn here
+            _ => None,
+        };
+        let Some(outline) = outline.filter(|path| !path.is_empty()) else {
+            return;
+        };
+
+        // --- gradients spanning the shape are sized by it before any transform
+        let bounds = outline.bounds().unwrap_or_default();
+        let opacity = inherited.opacity;
+        let fill = inherited
+            .fill
+            .and_then(|fill| self.paint(fill, opacity * inherited.fill_opacity, &bounds, &ctm))
+            .map(|paint| (paint, inherited.fill_rule));
+
+        // --- the width goes along with the transform, by its average scale
+        let scale = ctm.determinant().abs().sqrt();
+        let stroke = inherited
+            .stroke
+            .filter(|_| inherited.stroke_width > 0.)
+            .and_then(|stroke| {
+                self.paint(stroke, opacity * inherited.stroke_opacity, &bounds, &ctm)
+            })
+            .map(|paint| {
+                let stroke = Stroke {
+                    width: inherited.stroke_width * scale,
+                    join: inherited.join,
+                    cap: inherited.cap,
+                    dashes: inherited.dashes.iter().map(|dash| dash * scale).collect(),
+                    dash_offset: inherited.dash_offset * scale,
+                };
+                (paint, stroke)
+            });
+
+        if fill.is_some() || stroke.is_some() {
+            self.shapes.push(Shape {
+                path: outline.transform(&ctm),
+                fill,
+                stroke,
+            });
+        }
+    }
+
+    fn paint(&self, value: &str, opacity: f32, bounds: &Rect, ctm: &Transform) -> Option<Paint> {
+        let value = value.trim();
+        if let Some(rest) = value.strip_prefix("url(") {
+            let (target, fallback) = rest.split_once(')')?;
+            let id = target
+                .trim()
+                .trim_matches(['\'', '"'])
+                .trim_start_matches('#');
+            return match self.gradients.get(id) {
+                Some(node) => self.gradient(*node, opacity, bounds, ctm),
+                None => self.paint(fallback, opacity, bounds, ctm),
+            };
+        }
+
+        match value {
+            "none" | "" => None,
+            "currentColor" => Some(Paint::CurrentColor(opacity)),
+            color => color::parse(color).map(|[r, g, b, a]| Paint::Color([r, g, b, a * opacity])),
+        }
+    }
+
+    fn gradient(
+        &self,
+        node: Node<'a, 'input>,
+        opacity: f32,
+        bounds: &Rect,
+        ctm: &Transform,
+    ) -> Option<Paint> {
+        // --- attributes & stops left out are taken from the gradient it points at
+        let chain = self.href_chain(node);
+        let attr = |name: &str| chain.iter().find_map(|node| node.attribute(name));
+        let stops_node = chain
+            .iter()
+            .find(|node| node.children().any(|child| child.has_tag_name("stop")))?;
+
+        let mut stops = Vec::new();
+        let mut last = 0f32;
+        for stop in stops_node
+            .children()
+            .filter(|child| child.has_tag_name("stop"))
+        {
+            let offset = stop.attribute("offset").and_then(fraction).unwrap_or(0.);
+            // --- never going backwards
+            last = offset.clamp(last, 1.);
+            let color = property(stop, "stop-color")
+                .and_then(color::parse)
+                .unwrap_or([0., 0., 0., 1.]);
+            let stop_opacity = property(stop, "stop-opacity")
+                .and_then(fraction)
+                .unwrap_or(1.);
+            stops.push((
+                last,
+                [
+                    color[0],
+                    color[1],
+                    color[2],
+                    color[3] * stop_opacity * opacity,
+                ],
+            ));
+        }
+        if stops.is_empty() {
+            return None;
+        }
+        // --- a single stop paints all of it
+        if stops.len() == 1 {
+            return Some(Paint::Color(stops[0].1));
+        }
+
+        let user_space = attr("gradientUnits") == Some("userSpaceOnUse");
+        let coordinate = |name: &str, default: f32, extent: f32| {
+            let value = attr(name).map(str::trim);
+            match value {
+                Some(value) if value.ends_with('%') => {
+                    let percent = value.trim_end_matches('%').parse::<f32>().ok()? / 100.;
+                    Some(if user_space {
+                        percent * extent
+                    } else {
+                        percent
+                    })
+                }
+                Some(value) => length(value),
+                None => Some(default),
+            }
+        };
+        let (w, h) = (self.view_box.width, self.view_box.height);
+        let start = [coordinate("x1", 0., w)?, coordinate("y1", 0., h)?];
+        let end = [
+            coordinate("x2", if user_space { w } else { 1. }, w)?,
+            coordinate("y2", 0., h)?,
+        ];
+
+        let units = match user_space {
+            true => Transform::IDENTITY,
+            false => {
+                if bounds.width <= 0. || bounds.height <= 0. {
+                    return None;
+                }
+                Transform::new(bounds.width, 0., 0., bounds.height, bounds.x, bounds.y)
+            }
+        };
+        let to_view = attr("gradientTransform")
+            .map(parse_transform)
+            .unwrap_or_default()
+            .then(&units)
+            .then(ctm);
+
+        Some(Paint::Linear(Gradient {
+            start,
+            end,
+            stops,
+            inverse: to_view.invert()?,
+        }))
+    }
+
+    fn href_chain(&self, node: Node<'a, 'input>) -> Vec<Node<'a, 'input>> {
+        let mut chain = vec![node];
+        while chain.len() < MAX_HREF {
+            let last = chain[chain.len() - 1];
+            let href = last.attribute((XLINK, "href")).or(last.attribute("href"));
+            let Some(next) = href.and_then(|href| self.gradients.get(href.trim_start_matches('#')))
+            else {
+                break;
+            };
+            chain.push(*next);
+        }
+        chain
+    }
+}
+
+fn inherit<'a>(node: Node<'a, '_>, parent: &Inherited<'a>) -> Inherited<'a> {
+    let mut inherited = parent.clone();
+    let number = |name: &str| property(node, name).and_then(length);
+
+    if let Some(fill) = property(node, "fill") {
+        inherited.fill = Some(fill);
+    }
+    if let Some(stroke) = property(node, "stroke") {
+        inherited.stroke = Some(stroke);
+    }
+    match property(node, "fill-rule") {
+        Some("evenodd") => inherited.fill_rule = FillRule::EvenOdd,
+        Some("nonzero") => inherited.fill_rule = FillRule::NonZero,
+        _ => (),
+    }
+    if let Some(opacity) = property(node, "fill-opacity").and_then(fraction) {
+        inherited.fill_opacity = opacity;
+    }
+    if let Some(opacity) = property(node, "stroke-opacity").and_then(fraction) {
+        inherited.stroke_opacity = opacity;
+    }
+    if let Some(opacity) = property(node, "opacity").and_then(fraction) {
+        inherited.opacity *= opacity;
+    }
+    if let Some(width) = number("stroke-width") {
+        inherited.stroke_width = width;
+    }
+    match property(node, "stroke-linecap") {
+        Some("butt") => inherited.cap = LineCap::Butt,
+        Some("round") => inherited.cap = LineCap::Round,
+        Some("square") => inherited.cap = LineCap::Square,
+        _ => (),
+    }
+    match property(node, "stroke-linejoin") {
+        Some("miter") | Some("miter-clip") | Some("arcs") => inherited.join = LineJoin::Miter,
+        Some("round") => inherited.join = LineJoin::Round,
+        Some("bevel") => inherited.join = LineJoin::Bevel,
+        _ => (),
+    }
+    match property(node, "stroke-dasharray") {
+        Some("none") => inherited.dashes.clear(),
+        Some(dashes) => inherited.dashes = numbers(dashes),
+        None => (),
+    }
+    if let Some(offset) = number("stroke-dashoffset") {
+        inherited.dash_offset = offset;
+    }
+
+    inherited
+}
+
+// --- from `style` first, then the attribute of the same name
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let declared = node.attribute("style").and_then(|style| {
+        style.split(';').rev().find_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            (property.trim() == name).then(|| value.trim())
+        })
+    });
+    declared.or_else(|| node.attribute(name)).map(str::trim)
+}
+
+fn number_attr(node: Node, name: &str) -> f32 {
+    node.attribute(name).and_then(length).unwrap_or(0.)
+}
+
+// --- a number in px, or in a unit that converts to px
+fn length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number.trim().parse::<f32>().ok()?;
+
+    let px = match unit.trim() {
+        "" | "px" => 1.,
+        "pt" => 4. / 3.,
+        "pc" => 16.,
+        "in" => 96.,
+        "cm" => 96. / 2.54,
+        "mm" => 96. / 25.4,
+        "em" | "rem" => 16.,
+        _ => return None,
+    };
+    Some(number * px)
+}
+
+// --- `0.5` or `50%`, kept within 0..1
+fn fraction(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().ok()? / 100.,
+        None => value.parse::<f32>().ok()?,
+    };
+    Some(fraction.clamp(0., 1.))
+}
+
+fn numbers(value: &str) -> Vec<f32> {
+    let mut cursor = Cursor::new(value);
+    std::iter::from_fn(|| cursor.number()).collect()
+}
+
+// --- a list like `translate(10 20) rotate(45)`, applied right to left
+fn parse_transform(value: &str) -> Transform {
+    let mut transform = Transform::IDENTITY;
+    let mut rest = value;
+
+    while let Some((name, after)) = rest.split_once('(') {
+        let Some((args, after)) = after.split_once(')') else {
+            break;
+        };
+        rest = after;
+
+        let args = numbers(args);
+        let arg = |i: usize| args.get(i).copied();
+        let next = match (
+            name.trim_matches(|ch: char| ch == ',' || ch.is_whitespace()),
+            args.len(),
+        ) {
+            ("matrix", 6) => Transform::new(args[0], args[1], args[2], args[3], args[4], args[5]),
+            ("translate", 1 | 2) => Transform::translate(args[0], arg(1).unwrap_or(0.)),
+            ("scale", 1 | 2) => Transform::scale(args[0], arg(1).unwrap_or(args[0])),
+            ("rotate", 1) => Transform::rotate(args[0].to_radians()),
+            ("rotate", 3) => Transform::translate(-args[1], -args[2])
+                .then(&Transform::rotate(args[0].to_radians()))
+                .then(&Transform::translate(args[1], args[2])),
+            ("skewX", 1) => Transform::skew(args[0].to_radians(), 0.),
+            ("skewY", 1) => Transform::skew(0., args[0].to_radians()),
+            // --- the whole list is dropped when any of it is wrong
+            _ => return Transform::IDENTITY,
+        };
+        transform = next.then(&transform);
+    }
+
+    transform
+}
+
+// --- reads numbers & flags out of path data, where separators are mostly optional
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            bytes: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace() || *b == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        let byte = self.peek().filter(u8::is_ascii_alphabetic)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let at = |pos: usize| self.bytes.get(pos).copied();
+
+        let mut pos = start;
+        if matches!(at(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        let mut dot = false;
+        let mut digits = false;
+        while let Some(byte) = at(pos) {
+            match byte {
+                b'0'..=b'9' => digits = true,
+                // --- a second dot starts the next number: `1.5.5` is 1.5 then .5
+                b'.' if !dot => dot = true,
+                _ => break,
+            }
+            pos += 1;
+        }
+        if !digits {
+            return None;
+        }
+        if matches!(at(pos), Some(b'e' | b'E')) {
+            let mut exp = pos + 1;
+            if matches!(at(exp), Some(b'+' | b'-')) {
+                exp += 1;
+            }
+            if at(exp).is_some_and(|b| b.is_ascii_digit()) {
+                while at(exp).is_some_and(|b| b.is_ascii_digit()) {
+                    exp += 1;
+                }
+                pos = exp;
+            }
+        }
+
+        let number = std::str::from_utf8(&self.bytes[start..pos])
+            .ok()?
+            .parse()
+            .ok()?;
+        self.pos = pos;
+        Some(number)
+    }
+
+    // --- arc flags are a single digit, and may run into what follows
+    fn flag(&mut self) -> Option<bool> {
+        let flag = match self.peek()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<[f32; 2]> {
+        Some([self.number()?, self.number()?])
+    }
+}
+
+// --- everything up to the first mistake is kept, like browsers do
+fn parse_path_data(data: &str) -> Path {
+    let mut path = Path::new();
+    let mut cursor = Cursor::new(data);
+
+    let mut current = [0f32; 2];
+    let mut start = [0f32; 2];
+    // --- the control point a smooth curve mirrors, and whether the last one was cubic
+    let mut last_control: Option<([f32; 2], bool)> = None;
+    let mut command = None;
+
+    loop {
+        match cursor.command() {
+            Some(next) => command = Some(next),
+            None if cursor.peek().is_none() => break,
+            // --- numbers going on repeat the last command, a move repeats as a line
+            None => {
+                command = match command {
+                    Some(b'M') => Some(b'L'),
+                    Some(b'm') => Some(b'l'),
+                    Some(b'Z' | b'z') | None => break,
+                    other => other,
+                }
+            }
+        }
+        let Some(command) = command else {
+            break;
+        };
+
+        let relative = command.is_ascii_lowercase();
+        let base = if relative { current } else { [0., 0.] };
+        let offset = move |p: [f32; 2]| [p[0] + base[0], p[1] + base[1]];
+
+        let mut control = None;
+        let done = (|| {
+            match command.to_ascii_uppercase() {
+                b'M' => {
+                    let p = offset(cursor.point()?);
+                    path.move_to(p[0], p[1]);
+                    (current, start) = (p, p);
+                }
+                b'L' => {
+                    let p = offset(cursor.point()?);
+                    path.line_to(p[0], p[1]);
+                    current = p;
+                }
+                b'H' => {
+                    let x = cursor.number()? + base[0];
+                    path.line_to(x, current[1]);
+                    current[0] = x;
+                }
+                b'V' => {
+                    let y = cursor.number()? + base[1];
+                    path.line_to(current[0], y);
+                    current[1] = y;
+                }
+                b'C' => {
+                    let (c1, c2) = (offset(cursor.point()?), offset(cursor.point()?));
+                    let p = offset(cursor.point()?);
+                    path.cubic_to(c1[0], c1[1], c2[0], c2[1], p[0], p[1]);
+                    (current, control) = (p, Some((c2, true)));
+                }
+                b'S' => {
+                    let c1 = mirror(last_control, current, true);
+                    let (c2, p) = (offset(cursor.point()?), offset(cursor.point()?));
+                    path.cubic_to(c1[0], c1[1], c2[0], c2[1], p[0], p[1]);
+                    (current, control) = (p, Some((c2, true)));
+                }
+                b'Q' => {
+                    let (c, p) = (offset(cursor.point()?), offset(cursor.point()?));
+                    path.quad_to(c[0], c[1], p[0], p[1]);
+                    (current, control) = (p, Some((c, false)));
+                }
+                b'T' => {
+                    let c = mirror(last_control, current, false);
+                    let p = offset(cursor.point()?);
+                    path.quad_to(c[0], c[1], p[0], p[1]);
+                    (current, control) = (p, Some((c, false)));
+                }
+                b'A' => {
+                    let (radii, rotation) = (cursor.point()?, cursor.number()?);
+                    let flags = (cursor.flag()?, cursor.flag()?);
+                    let p = offset(cursor.point()?);
+                    arc_to(&mut path, current, radii, rotation, flags, p);
+                    current = p;
+                }
+                b'Z' => {
+                    path.close();
+                    current = start;
+                }
+                _ => return None,
+            }
+            Some(())
+        })();
+        if done.is_none() {
+            break;
+        }
+        last_control = control;
+    }
+
+    path
+}
+
+// --- the last control point reflected through the current point, or the current point
+// --- itself when the last segment wasn't the same kind of curve
+fn mirror(last: Option<([f32; 2], bool)>, current: [f32; 2], cubic: bool) -> [f32; 2] {
+    match last {
+        Some((control, was_cubic)) if was_cubic == cubic => {
+            [2. * current[0] - control[0], 2. * current[1] - control[1]]
+        }
+        _ => current,
+    }
+}
+
+// --- an SVG elliptical arc, from its end points to its center & angles.
+// --- the flags pick the larger of the two possible arcs, and the clockwise one
+fn arc_to(
+    path: &mut Path,
+    from: [f32; 2],
+    radii: [f32; 2],
+    rotation: f32,
+    (large, sweep): (bool, bool),
+    to: [f32; 2],
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (radii[0].abs(), radii[1].abs());
+    if rx == 0. || ry == 0. {
+        path.line_to(to[0], to[1]);
+        return;
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from[0] - to[0]) / 2., (from[1] - to[1]) / 2.);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // --- radii too small to reach are grown until they just do
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let num = rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1;
+    let den = rx2 * y1 * y1 + ry2 * x1 * x1;
+    let sign = if large == sweep { -1. } else { 1. };
+    let coef = sign * (num / den).max(0.).sqrt();
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+
+    let center = [
+        cos * cx1 - sin * cy1 + (from[0] + to[0]) / 2.,
+        sin * cx1 + cos * cy1 + (from[1] + to[1]) / 2.,
+    ];
+
+    let angle =
+        |u: [f32; 2], v: [f32; 2]| (u[0] * v[1] - u[1] * v[0]).atan2(u[0] * v[0] + u[1] * v[1]);
+    let u = [(x1 - cx1) / rx, (y1 - cy1) / ry];
+    let v = [(-x1 - cx1) / rx, (-y1 - cy1) / ry];
+    let start = angle([1., 0.], u);
+    let mut delta = angle(u, v);
+    if !sweep && delta > 0. {
+        delta -= 2. * PI;
+    } else if sweep && delta < 0. {
+        delta += 2. * PI;
+    }
+
+    elliptic(path, center, [rx, ry], rotation.to_radians(), start, delta);
+}
+
+// --- cubics along an ellipse, a quarter turn at most each
+fn elliptic(
+    path: &mut Path,
+    center: [f32; 2],
+    radii: [f32; 2],
+    rotation: f32,
+    start: f32,
+    sweep: f32,
+) {
+    let (sin, cos) = rotation.sin_cos();
+    let map = |[x, y]: [f32; 2]| {
+        let (x, y) = (x * radii[0], y * radii[1]);
+        [center[0] + cos * x - sin * y, center[1] + sin * x + cos * y]
+    };
+
+    let pieces = (sweep.abs() / (PI / 2.)).ceil().max(1.) as usize;
+    let step = sweep / pieces as f32;
+    let k = 4. / 3. * (step / 4.).tan();
+    for i in 0..pieces {
+        let (a0, a1) = (start + step * i as f32, start + step * (i + 1) as f32);
+        let c1 = map([a0.cos() - k * a0.sin(), a0.sin() + k * a0.cos()]);
+        let c2 = map([a1.cos() + k * a1.sin(), a1.sin() - k * a1.cos()]);
+        let p = map([a1.cos(), a1.sin()]);
+        path.cubic_to(c1[0], c1[1], c2[0], c2[1], p[0], p[1]);
+    }
+}
+
+fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Path {
+    let mut path = Path::new();
+    path.move_to(cx + rx, cy);
+    elliptic(&mut path, [cx, cy], [rx, ry], 0., 0., 2. * PI);
+    path.close();
+    path
+}
+
+fn rect(node: Node) -> Option<Path> {
+    let (x, y) = (number_attr(node, "x"), number_attr(node, "y"));
+    let (w, h) = (number_attr(node, "width"), number_attr(node, "height"));
+    if w <= 0. || h <= 0. {
+        return None;
+    }
+
+    // --- one radius given is used for both
+    let rx = node.attribute("rx").and_then(length);
+    let ry = node.attribute("ry").and_then(length);
+    let (rx, ry) = match (rx, ry) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(r), None) | (None, Some(r)) => (r, r),
+        (None, None) => (0., 0.),
+    };
+    let (rx, ry) = (rx.clamp(0., w / 2.), ry.clamp(0., h / 2.));
+
+    let mut path = Path::new();
+    if rx == 0. || ry == 0. {
+        path.rect(x, y, w, h);
+        return Some(path);
+    }
+
+    let quarter = PI / 2.;
+    path.move_to(x + rx, y).line_to(x + w - rx, y);
+    elliptic(
+        &mut path,
+        [x + w - rx, y + ry],
+        [rx, ry],
+        0.,
+        -quarter,
+        quarter,
+    );
+    path.line_to(x + w, y + h - ry);
+    elliptic(
+        &mut path,
+        [x + w - rx, y + h - ry],
+        [rx, ry],
+        0.,
+        0.,
+        quarter,
+    );
+    path.line_to(x + rx, y + h);
+    elliptic(
+        &mut path,
+        [x + rx, y + h - ry],
+        [rx, ry],
+        0.,
+        quarter,
+        quarter,
+    );
+    path.line_to(x, y + ry);
+    elliptic(&mut path, [x + rx, y + ry], [rx, ry], 0., PI, quarter);
+    path.close();
+    Some(path)
+}
+
+// --- composites the mesh's coverage onto the pixels, each sample counted once
+// --- however many triangles cover it
+fn paint_mesh(
+    pixels: &mut [[f32; 4]],
+    width: usize,
+    height: usize,
+    mesh: &Mesh,
+    paint: &Paint,
+    current: Color,
+    from_pixels: &Transform,
+) {
+    let mut coverage: HashMap<usize, u16> = HashMap::new();
+    let step = 1. / SAMPLES as f32;
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.points[triangle[i] as usize]);
+        let edge = |p: [f32; 2], q: [f32; 2], s: [f32; 2]| {
+            (q[0] - p[0]) * (s[1] - p[1]) - (q[1] - p[1]) * (s[0] - p[0])
+        };
+
+        let left = a[0].min(b[0]).min(c[0]).floor().max(0.) as usize;
+        let top = a[1].min(b[1]).min(c[1]).floor().max(0.) as usize;
+        let right = (a[0].max(b[0]).max(c[0]).ceil().max(0.) as usize).min(width);
+        let bottom = (a[1].max(b[1]).max(c[1]).ceil().max(0.) as usize).min(height);
+
+        for y in top..bottom {
+            for x in left..right {
+                let mut mask = 0u16;
+                for i in 0..SAMPLES * SAMPLES {
+                    let sample = [
+                        x as f32 + (i % SAMPLES) as f32 * step + step / 2.,
+                        y as f32 + (i / SAMPLES) as f32 * step + step / 2.,
+                    ];
+                    let (e0, e1, e2) = (edge(a, b, sample), edge(b, c, sample), edge(c, a, sample));
+                    let inside =
+                        (e0 >= 0. && e1 >= 0. && e2 >= 0.) || (e0 <= 0. && e1 <= 0. && e2 <= 0.);
+                    if inside {
+                        mask |= 1 << i;
+                    }
+                }
+                if mask != 0 {
+                    *coverage.entry(y * width + x).or_default() |= mask;
+                }
+            }
+        }
+    }
+
+    for (idx, mask) in coverage {
+        let covered = mask.count_ones() as f32 / (SAMPLES * SAMPLES) as f32;
+        let color = match paint {
+            Paint::Color(color) => *color,
+            Paint::CurrentColor(opacity) => {
+                [current[0], current[1], current[2], current[3] * opacity]
+            }
+            Paint::Linear(gradient) => {
+                let center = [(idx % width) as f32 + 0.5, (idx / width) as f32 + 0.5];
+                gradient.color_at(from_pixels.apply(center))
+            }
+        };
+
+        let alpha = color[3] * covered;
+        let pixel = &mut pixels[idx];
+        for i in 0..3 {
+            pixel[i] = color[i] * alpha + pixel[i] * (1. - alpha);
+        }
+        pixel[3] = alpha + pixel[3] * (1. - alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(build: impl FnOnce(&mut Path)) -> Path {
+        let mut path = Path::new();
+        build(&mut path);
+        path
+    }
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3
+    }
+
+    // --- the flattened outline stays within a quarter px of the curve
+    fn bounds_close(path: &Path, expected: Rect) {
+        let bounds = path.bounds().unwrap();
+        let near = [
+            (bounds.x, expected.x),
+            (bounds.y, expected.y),
+            (bounds.width, expected.width),
+            (bounds.height, expected.height),
+        ]
+        .iter()
+        .all(|(actual, expected)| (actual - expected).abs() < 0.5);
+        assert!(near, "{bounds:?} != {expected:?}");
+    }
+
+    fn end(path: &Path) -> [f32; 2] {
+        *path.flatten().last().unwrap().0.last().unwrap()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            parse_path_data("M10 20 L30 40 H50 V60 Z"),
+            path(|p| {
+                p.move_to(10., 20.)
+                    .line_to(30., 40.)
+                    .line_to(50., 40.)
+                    .line_to(50., 60.)
+                    .close();
+            })
+        );
+    }
+
+    #[test]
+    fn relative_commands() {
+        assert_eq!(
+            parse_path_data("m10 10 l5 0 h5 v5 z m1 1 l1 1"),
+            path(|p| {
+                p.move_to(10., 10.)
+                    .line_to(15., 10.)
+                    .line_to(20., 10.)
+                    .line_to(20., 15.)
+                    .close();
+                // --- after closing, the pen is back where the subpath started
+                p.move_to(11., 11.).line_to(12., 12.);
+            })
+        );
+    }
+
+    #[test]
+    fn repeated_commands() {
+        // --- coordinates after a move are lines, relative after a relative one
+        assert_eq!(
+            parse_path_data("M0 0 10 0 10 10"),
+            path(|p| {
+                p.move_to(0., 0.).line_to(10., 0.).line_to(10., 10.);
+            })
+        );
+        assert_eq!(
+            parse_path_data("m1 1 2 2 h1 2"),
+            path(|p| {
+                p.move_to(1., 1.)
+                    .line_to(3., 3.)
+                    .line_to(4., 3.)
+                    .line_to(6., 3.);
+            })
+        );
+    }
+
+    #[test]
+    fn compact_numbers() {
+        assert_eq!(
+            parse_path_data("M1.5.5L-1-2e1,3E+1-.5"),
+            path(|p| {
+                p.move_to(1.5, 0.5).line_to(-1., -20.).line_to(30., -0.5);
+            })
+        );
+    }
+
+    #[test]
+    fn curves() {
+        assert_eq!(
+            parse_path_data("M0 0 C1 1 2 1 3 0 S5 -1 6 0 s1 1 2 0"),
+            path(|p| {
+                p.move_to(0., 0.)
+                    .cubic_to(1., 1., 2., 1., 3., 0.)
+                    // --- the first control point mirrors the last one through the pen
+                    .cubic_to(4., -1., 5., -1., 6., 0.)
+                    .cubic_to(7., 1., 7., 1., 8., 0.);
+            })
+        );
+        assert_eq!(
+            parse_path_data("M0 0 Q1 1 2 0 T4 0 t2 0"),
+            path(|p| {
+                p.move_to(0., 0.)
+                    .quad_to(1., 1., 2., 0.)
+                    .quad_to(3., -1., 4., 0.)
+                    .quad_to(5., 1., 6., 0.);
+            })
+        );
+        // --- a smooth curve after another kind has no control point to mirror
+        assert_eq!(
+            parse_path_data("M0 0 Q1 1 2 0 S3 1 4 0"),
+            path(|p| {
+                p.move_to(0., 0.)
+                    .quad_to(1., 1., 2., 0.)
+                    .cubic_to(2., 0., 3., 1., 4., 0.);
+            })
+        );
+    }
+
+    #[test]
+    fn stops_at_the_first_mistake() {
+        let valid = path(|p| {
+            p.move_to(0., 0.).line_to(10., 10.);
+        });
+        assert_eq!(parse_path_data("M0 0 L10 10 L20 X 30"), valid);
+        assert_eq!(parse_path_data("M0 0 L10 10 B1 2"), valid);
+        assert_eq!(parse_path_data("M0 0 L10 10 L20"), valid);
+        assert_eq!(
+            parse_path_data("M0 0 L10 10 Z 5 5"),
+            path(|p| {
+                p.move_to(0., 0.).line_to(10., 10.).close();
+            })
+        );
+        assert!(parse_path_data("").is_empty());
+        assert!(parse_path_data("10 10").is_empty());
+    }
+
+    #[test]
+    fn arcs() {
+        // --- a half circle around (10, 0), the sweep flag picks the side
+        let up = parse_path_data("M0 0 A10 10 0 0 1 20 0");
+        bounds_close(&up, Rect::new(0., -10., 20., 10.));
+        assert!(close(end(&up), [20., 0.]));
+        let down = parse_path_data("M0 0 A10 10 0 0 0 20 0");
+        bounds_close(&down, Rect::new(0., 0., 20., 10.));
+
+        // --- flags may run into the numbers after them
+        assert_eq!(parse_path_data("M0 0a10 10 0 0120 0"), up);
+
+        // --- a quarter of a circle, or the other three quarters
+        let small = parse_path_data("M0 0 A10 10 0 0 0 10 10");
+        bounds_close(&small, Rect::new(0., 0., 10., 10.));
+        let large = parse_path_data("M0 0 A10 10 0 1 0 10 10");
+        bounds_close(&large, Rect::new(-10., 0., 20., 20.));
+        assert!(close(end(&large), [10., 10.]));
+
+        // --- radii too small to reach are grown until they do
+        bounds_close(
+            &parse_path_data("M0 0 A1 1 0 0 1 20 0"),
+            Rect::new(0., -10., 20., 10.),
+        );
+
+        // --- a rotated ellipse, its long axis upright, clockwise from the top bulges right
+        let rotated = parse_path_data("M0 0 A20 10 90 0 1 0 40");
+        bounds_close(&rotated, Rect::new(0., 0., 10., 40.));
+    }
+
+    #[test]
+    fn degenerate_arcs() {
+        assert_eq!(
+            parse_path_data("M0 0 A0 5 0 0 1 10 0"),
+            path(|p| {
+                p.move_to(0., 0.).line_to(10., 0.);
+            })
+        );
+        assert_eq!(
+            parse_path_data("M5 5 A5 5 0 0 1 5 5"),
+            path(|p| {
+                p.move_to(5., 5.);
+            })
+        );
+        // --- a flag that isn't 0 or 1 ends the path
+        assert_eq!(
+            parse_path_data("M0 0 A5 5 0 2 1 10 0"),
+            path(|p| {
+                p.move_to(0., 0.);
+            })
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        assert_eq!(
+            parse_transform("translate(10)"),
+            Transform::translate(10., 0.)
+        );
+        assert_eq!(parse_transform("scale(2)"), Transform::scale(2., 2.));
+        assert_eq!(
+            parse_transform("matrix(1 2 3 4 5 6)"),
+            Transform::new(1., 2., 3., 4., 5., 6.)
+        );
+
+        // --- applied right to left: scaled first, then moved
+        let list = parse_transform("translate(10 20) scale(2)");
+        assert_eq!(list.apply([1., 1.]), [12., 22.]);
+        assert_eq!(parse_transform(" translate(10,20) , scale(2) "), list);
+        assert_eq!(parse_transform("translate(10,20),scale(2)"), list);
+
+        assert!(close(
+            parse_transform("rotate(90)").apply([1., 0.]),
+            [0., 1.]
+        ));
+        // --- around a point, which stays where it is
+        let around = parse_transform("rotate(90 10 10)");
+        assert!(close(around.apply([10., 10.]), [10., 10.]));
+        assert!(close(around.apply([11., 10.]), [10., 11.]));
+        assert!(close(
+            parse_transform("skewX(45)").apply([0., 1.]),
+            [1., 1.]
+        ));
+        assert!(close(
+            parse_transform("skewY(45)").apply([1., 0.]),
+            [1., 1.]
+        ));
+    }
+
+    #[test]
+    fn invalid_transforms() {
+        for value in [
+            "",
+            "translate(1 2 3)",
+            "rotate(1 2)",
+            "matrix(1 2 3)",
+            "scale(2) wobble(1)",
+            "scale()",
+        ] {
+            assert_eq!(parse_transform(value), Transform::IDENTITY, "{value}");
+        }
+        // --- an unclosed one ends the list, what came before is kept
+        assert_eq!(
+            parse_transform("scale(2) translate(1"),
+            Transform::scale(2., 2.)
+        );
+    }
+}
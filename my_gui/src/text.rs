@@ -0,0 +1,304 @@
+use font8x8::{
+    UnicodeFonts, BASIC_FONTS, BLOCK_FONTS, BOX_FONTS, GREEK_FONTS, LATIN_FONTS, MISC_FONTS,
+};
+
+use crate::style::{Color, Font};
+use crate::texture::Texture;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// --- text is drawn with the built-in 8x8 bitmap glyphs scaled to the font size, and CJK
+// --- with 16x16 ones squeezed into the same cell, so every glyph has the same advance no
+// --- matter the family
+const GLYPH_CELL: f32 = 8.;
+const SUBSAMPLES: u32 = 3;
+
+// --- lines of text one element keeps rasterized, the least recently drawn goes past that
+const CACHED_LINES: usize = 64;
+
+// --- drawn for characters the glyph sets don't have
+const MISSING: [u8; 8] = [0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x00];
+
+// --- 16x16 glyphs from GNU Unifont (see fonts/UNIFONT-LICENSE.txt) for the blocks below,
+// --- back to back with 16 rows of 2 bytes each, the leftmost pixel in the highest bit.
+// --- its narrow glyphs have every column doubled so all of them are 16 wide
+const CJK_FONT: &[u8] = include_bytes!("../fonts/unifont-cjk.bin");
+const CJK_GLYPH: usize = 32;
+const CJK_BLOCKS: [(char, char); 9] = [
+    // --- hangul jamo
+    ('\u{1100}', '\u{11FF}'),
+    // --- CJK radicals & kangxi radicals
+    ('\u{2E80}', '\u{2FDF}'),
+    // --- CJK punctuation, hiragana, katakana, bopomofo, hangul compatibility jamo,
+    // --- enclosed & compatibility letters
+    ('\u{3000}', '\u{33FF}'),
+    // --- CJK unified ideographs, extension A & the main block
+    ('\u{3400}', '\u{4DBF}'),
+    ('\u{4E00}', '\u{9FFF}'),
+    // --- hangul syllables
+    ('\u{AC00}', '\u{D7A3}'),
+    // --- CJK compatibility ideographs & forms
+    ('\u{F900}', '\u{FAFF}'),
+    ('\u{FE30}', '\u{FE4F}'),
+    // --- halfwidth & fullwidth forms
+    ('\u{FF00}', '\u{FFEF}'),
+];
+
+// --- the bitmap of one character
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Glyph {
+    // --- 8x8, the lowest bit leftmost, as wide as the font size & cut at the advance
+    Small([u8; 8]),
+    // --- 16x16, the highest bit leftmost, squeezed into the advance
+    Cjk([u16; 16]),
+}
+
+impl Glyph {
+    // --- whether the pixel at `x`, `y` in 0..1 of the glyph's box is set
+    fn covers(&self, x: f32, y: f32) -> bool {
+        if !(0. ..1.).contains(&x) || !(0. ..1.).contains(&y) {
+            return false;
+        }
+        match self {
+            Self::Small(rows) => (rows[(y * 8.) as usize] >> (x * 8.) as usize) & 1 == 1,
+            Self::Cjk(rows) => (rows[(y * 16.) as usize] >> (15 - (x * 16.) as usize)) & 1 == 1,
+        }
+    }
+
+    fn embolden(&mut self) {
+        match self {
+            Self::Small(rows) => rows.iter_mut().for_each(|row| *row |= *row << 1),
+            Self::Cjk(rows) => rows.iter_mut().for_each(|row| *row |= *row >> 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub fn advance(font: &Font) -> f32 {
+    font.size * 7. / GLYPH_CELL
+}
+
+pub fn line_height(font: &Font) -> f32 {
+    (font.size * 1.25).ceil()
+}
+
+pub fn measure(text: &str, font: &Font) -> f32 {
+    text.chars().count() as f32 * advance(font)
+}
+
+// --- char index of the caret position closest to `x`, measured from the start of the text
+pub fn index_at(text: &str, font: &Font, x: f32) -> usize {
+    let idx = (x / advance(font)).round().max(0.) as usize;
+    idx.min(text.chars().count())
+}
+
+fn cjk_glyph(ch: char) -> Option<[u16; 16]> {
+    let mut start = 0;
+    for (first, last) in CJK_BLOCKS {
+        if (first..=last).contains(&ch) {
+            let at = (start + (ch as usize - first as usize)) * CJK_GLYPH;
+            let bytes = &CJK_FONT[at..at + CJK_GLYPH];
+            let rows =
+                std::array::from_fn(|row| u16::from_be_bytes([bytes[row * 2], bytes[row * 2 + 1]]));
+            // --- a blank glyph is a code point the font has nothing for
+            return rows.iter().any(|row| *row != 0).then_some(rows);
+        }
+        start += last as usize - first as usize + 1;
+    }
+    None
+}
+
+fn glyph(ch: char) -> Glyph {
+    if let Some(rows) = cjk_glyph(ch) {
+        return Glyph::Cjk(rows);
+    }
+
+    let rows = BASIC_FONTS
+        .get(ch)
+        .or_else(|| LATIN_FONTS.get(ch))
+        .or_else(|| GREEK_FONTS.get(ch))
+        .or_else(|| BOX_FONTS.get(ch))
+        .or_else(|| BLOCK_FONTS.get(ch))
+        .or_else(|| MISC_FONTS.get(ch))
+        .unwrap_or(MISSING);
+    Glyph::Small(rows)
+}
+
+// --- one line of text in `color`, with `offset` pixels of it scrolled out on the left
+// --- and cut at `width`, the glyphs sit vertically centered in the line height
+pub fn rasterize(text: &str, font: &Font, color: Color, offset: f32, width: u32) -> Bitmap {
+    let height = line_height(font) as u32;
+    let rgb = color.map(|i| (i * 255.) as u8);
+
+    // --- transparent pixels still carry the color so the edges don't get darker when sampled
+    let mut rgba = [rgb[0], rgb[1], rgb[2], 0].repeat((width * height) as usize);
+
+    let advance = advance(font);
+    let top = (height as f32 - font.size) / 2.;
+    let samples = (SUBSAMPLES * SUBSAMPLES) as f32;
+
+    for (idx, ch) in text.chars().enumerate() {
+        let left = idx as f32 * advance - offset;
+        if left + advance <= 0. || ch.is_whitespace() {
+            continue;
+        }
+        if left >= width as f32 {
+            break;
+        }
+
+        let mut glyph = glyph(ch);
+        if font.weight >= 600 {
+            glyph.embolden();
+        }
+        let glyph_width = match glyph {
+            Glyph::Small(_) => font.size,
+            Glyph::Cjk(_) => advance,
+        };
+
+        let x0 = left.floor().max(0.) as u32;
+        let x1 = ((left + advance).ceil() as u32).min(width);
+
+        for py in 0..height {
+            for px in x0..x1 {
+                let mut hits = 0;
+                for sy in 0..SUBSAMPLES {
+                    for sx in 0..SUBSAMPLES {
+                        let x = px as f32 + (sx as f32 + 0.5) / SUBSAMPLES as f32 - left;
+                        let y = py as f32 + (sy as f32 + 0.5) / SUBSAMPLES as f32 - top;
+                        if glyph.covers(x / glyph_width, y / font.size) {
+                            hits += 1;
+                        }
+                    }
+                }
+
+                if hits > 0 {
+                    let alpha = (hits as f32 / samples * color[3] * 255.) as u8;
+                    let a = &mut rgba[((py * width + px) * 4 + 3) as usize];
+                    *a = (*a).max(alpha);
+                }
+            }
+        }
+    }
+
+    Bitmap {
+        width,
+        height,
+        rgba,
+    }
+}
+
+// --- the text, font, color, offset & width a line was rasterized with
+type LineKey = (String, Font, [u32; 4], u32, u32);
+
+// --- lines rasterized by one element, kept as textures so a line drawn the same way
+// --- again is neither rasterized nor uploaded again. clones share them
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TextCache {
+    // --- most recently drawn last
+    lines: Rc<RefCell<Vec<(LineKey, Texture)>>>,
+}
+
+impl TextCache {
+    // --- `rasterize`, through the cache
+    pub fn texture(
+        &self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        offset: f32,
+        width: u32,
+    ) -> Texture {
+        let key = (
+            text.to_string(),
+            font.clone(),
+            color.map(f32::to_bits),
+            offset.to_bits(),
+            width,
+        );
+        let mut lines = self.lines.borrow_mut();
+
+        if let Some(idx) = lines.iter().position(|(cached, _)| *cached == key) {
+            let entry = lines.remove(idx);
+            lines.push(entry);
+        } else {
+            if lines.len() >= CACHED_LINES {
+                lines.remove(0);
+            }
+            let bitmap = rasterize(text, font, color, offset, width);
+            lines.push((key, Texture::new(bitmap)));
+        }
+        lines.last().unwrap().1.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- what an IME commits for Japanese, Chinese & Korean, punctuation included
+    const COMMITTED: &str = "日本語のテキスト、カタカナ。中文输入法，简体繁體！한국어 입력";
+
+    #[test]
+    fn cjk_has_glyphs() {
+        for ch in COMMITTED.chars().filter(|ch| !ch.is_whitespace()) {
+            assert!(matches!(glyph(ch), Glyph::Cjk(_)), "{ch} has no glyph");
+        }
+        assert_eq!(glyph('a'), Glyph::Small(BASIC_FONTS.get('a').unwrap()));
+        // --- outside of every glyph set
+        assert_eq!(glyph('\u{E000}'), Glyph::Small(MISSING));
+    }
+
+    #[test]
+    fn cjk_is_drawn() {
+        let font = Font {
+            size: 16.,
+            ..Default::default()
+        };
+        let width = measure(COMMITTED, &font).ceil() as u32;
+        let bitmap = rasterize(COMMITTED, &font, [0., 0., 0., 1.], 0., width);
+        let advance = advance(&font);
+
+        // --- every cell but the space has ink in it
+        for (idx, ch) in COMMITTED.chars().enumerate() {
+            let (x0, x1) = (
+                (idx as f32 * advance) as u32,
+                ((idx + 1) as f32 * advance) as u32,
+            );
+            let inked = (0..bitmap.height).any(|y| {
+                (x0..x1.min(width)).any(|x| bitmap.rgba[((y * width + x) * 4 + 3) as usize] > 0)
+            });
+            assert_eq!(inked, !ch.is_whitespace(), "{ch} at {idx}");
+        }
+    }
+
+    #[test]
+    fn cached_lines() {
+        let (cache, font) = (TextCache::default(), Font::default());
+        let texture = cache.texture("hello", &font, [0., 0., 0., 1.], 0., 80);
+
+        // --- the same upload for the same line, a new one once anything about it changes
+        assert_eq!(
+            cache.texture("hello", &font, [0., 0., 0., 1.], 0., 80),
+            texture
+        );
+        assert_ne!(
+            cache.texture("hello", &font, [1., 0., 0., 1.], 0., 80),
+            texture
+        );
+        assert_ne!(
+            cache.texture("hello", &font, [0., 0., 0., 1.], 4., 80),
+            texture
+        );
+        assert_eq!(
+            texture.bitmap(),
+            &rasterize("hello", &font, [0., 0., 0., 1.], 0., 80)
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::elements::{bitmap_texture, solid_texture};
+use crate::style::Color;
+use crate::text::Bitmap;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// --- cheap to clone so every element waiting on the same load can keep it
+#[derive(Debug, Clone)]
+pub enum ImageError {
+    Io(Arc<std::io::Error>),
+    // --- not an image, a broken one, or a format that isn't built in
+    Decode(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Decode(message) => write!(f, "can't decode image: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(Arc::new(err))
+    }
+}
+
+impl From<image::ImageError> for ImageError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Decode(err.to_string())
+    }
+}
+
+// --- PNG, JPEG or WebP, told apart by their content
+pub fn decode(bytes: &[u8]) -> Result<Bitmap, ImageError> {
+    let rgba = image::load_from_memory(bytes)?.into_rgba8();
+
+    Ok(Bitmap {
+        width: rgba.width(),
+        height: rgba.height(),
+        rgba: rgba.into_raw(),
+    })
+}
+
+// --- pixels that can be drawn by any number of elements, and are only uploaded once.
+// --- clones share the pixels and the upload
+#[derive(Debug, Clone)]
+pub struct Texture {
+    id: u64,
+    bitmap: Rc<Bitmap>,
+}
+
+impl PartialEq for Texture {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Texture {
+    pub fn new(bitmap: Bitmap) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            bitmap: Rc::new(bitmap),
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        Ok(Self::new(decode(bytes)?))
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.bitmap.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.bitmap.height
+    }
+
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+// --- GPU copies of the textures on screen lately, one is dropped once a frame goes by
+// --- without it being drawn or kept. solid colors too, by their bytes
+#[derive(Default)]
+pub struct TextureCache {
+    views: HashMap<u64, Rc<wgpu::TextureView>>,
+    used: HashSet<u64>,
+    solids: HashMap<[u8; 4], Rc<wgpu::TextureView>>,
+    solids_used: HashSet<[u8; 4]>,
+}
+
+impl TextureCache {
+    pub fn view(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &Texture,
+    ) -> Rc<wgpu::TextureView> {
+        self.used.insert(texture.id);
+        self.views
+            .entry(texture.id)
+            .or_insert_with(|| Rc::new(bitmap_texture(device, queue, &texture.bitmap)))
+            .clone()
+    }
+
+    pub fn solid(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        color: Color,
+    ) -> Rc<wgpu::TextureView> {
+        // --- the same bytes `solid_texture` ends up with
+        let key = color.map(|i| (i * 255.) as u8);
+        self.solids_used.insert(key);
+        self.solids
+            .entry(key)
+            .or_insert_with(|| Rc::new(solid_texture(device, queue, color)))
+            .clone()
+    }
+
+    // --- still on screen, but not drawn again this frame
+    pub fn keep(&mut self, texture: &Texture) {
+        self.used.insert(texture.id);
+    }
+
+    pub fn end_frame(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.views.retain(|id, _| used.contains(id));
+        let used = std::mem::take(&mut self.solids_used);
+        self.solids.retain(|key, _| used.contains(key));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::style::{Color, Font};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub surface: Color,
+    pub primary: Color,
+    pub primary_hover: Color,
+    pub primary_active: Color,
+    pub on_primary: Color,
+    pub text: Color,
+    pub text_muted: Color,
+    pub border: Color,
+    pub focus: Color,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Typography {
+    pub family: String,
+    pub small: f32,
+    pub body: f32,
+    pub title: f32,
+    pub heading: f32,
+}
+
+impl Typography {
+    pub fn font(&self, size: f32) -> Font {
+        Font {
+            family: self.family.clone(),
+            size,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spacing {
+    pub xs: f32,
+    pub sm: f32,
+    pub md: f32,
+    pub lg: f32,
+    pub xl: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Radii {
+    pub sm: f32,
+    pub md: f32,
+    pub lg: f32,
+    pub full: f32,
+}
+
+// --- design tokens, widgets build their default styles out of these
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+    pub typography: Typography,
+    pub spacing: Spacing,
+    pub radii: Radii,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            palette: Palette {
+                background: [0.961, 0.961, 0.957, 1.],
+                surface: [1., 1., 1., 1.],
+                primary: [0.235, 0.639, 0.282, 1.],
+                primary_hover: [0.192, 0.537, 0.235, 1.],
+                primary_active: [0.153, 0.439, 0.188, 1.],
+                on_primary: [1., 1., 1., 1.],
+                text: [0.102, 0.110, 0.118, 1.],
+                text_muted: [0.420, 0.447, 0.475, 1.],
+                border: [0.808, 0.816, 0.824, 1.],
+                focus: [0.169, 0.420, 0.859, 1.],
+            },
+            typography: Typography {
+                family: "sans-serif".to_string(),
+                small: 12.,
+                body: 14.,
+                title: 18.,
+                heading: 24.,
+            },
+            spacing: Spacing {
+                xs: 2.,
+                sm: 4.,
+                md: 8.,
+                lg: 16.,
+                xl: 32.,
+            },
+            radii: Radii {
+                sm: 4.,
+                md: 8.,
+                lg: 12.,
+                full: f32::MAX,
+            },
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            palette: Palette {
+                background: [0.071, 0.075, 0.082, 1.],
+                surface: [0.122, 0.129, 0.141, 1.],
+                primary: [0.306, 0.745, 0.357, 1.],
+                primary_hover: [0.384, 0.800, 0.431, 1.],
+                primary_active: [0.255, 0.643, 0.298, 1.],
+                on_primary: [0.039, 0.102, 0.051, 1.],
+                text: [0.906, 0.914, 0.922, 1.],
+                text_muted: [0.592, 0.612, 0.635, 1.],
+                border: [0.247, 0.259, 0.275, 1.],
+                focus: [0.455, 0.639, 0.980, 1.],
+            },
+            ..Self::light()
+        }
+    }
+}
+
+// --- either a single theme, or a light/dark pair following the OS preference
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemePreference {
+    Fixed(Box<Theme>),
+    System { light: Box<Theme>, dark: Box<Theme> },
+}
+
+impl Default for ThemePreference {
+    fn default() -> Self {
+        Self::System {
+            light: Box::new(Theme::light()),
+            dark: Box::new(Theme::dark()),
+        }
+    }
+}
+
+impl ThemePreference {
+    pub fn resolve(&self, system: winit::window::Theme) -> &Theme {
+        match self {
+            Self::Fixed(theme) => theme,
+            Self::System { light, .. } if system == winit::window::Theme::Light => light,
+            Self::System { dark, .. } => dark,
+        }
+    }
+}
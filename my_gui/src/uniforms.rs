@@ -1,7 +1,15 @@
+// --- one per video draw, matches `Uniforms` in video_shader.wgsl
 #[repr(C)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Uniforms {
-    pub rect: [f32; 4],
+    // --- where the frame's corners are drawn, in NDC: left then right, of the top & bottom edges
+    pub top: [f32; 4],
+    pub bottom: [f32; 4],
+    // --- the part of the frame drawn: x, y, width & height in 0..1
+    pub source: [f32; 4],
+    pub format: u32,
+    pub opacity: f32,
+    pub _padding: [u32; 2],
 }
 
 #[derive(Debug)]
@@ -20,4 +28,8 @@ impl UniformBuffer {
 
         Self { buffer }
     }
+
+    pub fn write(&self, queue: &wgpu::Queue, uniforms: &Uniforms) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(uniforms));
+    }
 }
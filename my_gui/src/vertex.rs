@@ -22,26 +22,47 @@ use bytemuck::{Pod, Zeroable};
 pub struct Vertex {
     pub position: [f32; 3],
     pub tex_coords: [f32; 2],
+    // --- the rest is per-shape data, repeated on each corner,
+    // --- the fragment shader uses it to carve the rounded border out of the quad
+    pub size: [f32; 2],
+    pub radius: f32,
+    pub border_width: f32,
+    pub border_color: [f32; 4],
+    pub opacity: f32,
+    // --- the part of the texture sampled: x, y, width & height in 0..1,
+    // --- and what it's multiplied with
+    pub uv: [f32; 4],
+    pub tint: [f32; 4],
+    // --- kind (0 for none) & placement of the gradients taking over the fill & the border,
+    // --- their colors are the rows of the ramp texture
+    pub gradient: [f32; 4],
+    pub border_gradient: [f32; 4],
+    // --- a shadow's blur radius, the quad reaching past the shadow's box for it to fade out in
+    pub blur: f32,
 }
 
 impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 12] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32,
+        4 => Float32,
+        5 => Float32x4,
+        6 => Float32,
+        7 => Float32x4,
+        8 => Float32x4,
+        9 => Float32x4,
+        10 => Float32x4,
+        11 => Float32,
+    ];
+
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
         use std::mem;
         wgpu::VertexBufferLayout {
             array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-            ],
+            attributes: &Self::ATTRIBUTES,
         }
     }
 }
@@ -0,0 +1,582 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::geometry::{Rect, Transform};
+use crate::uniforms::{UniformBuffer, Uniforms};
+use crate::view::{stencil_state, Stencil};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub enum VideoError {
+    // --- the data doesn't hold exactly one frame of the given format & size
+    FrameSize { expected: usize, actual: usize },
+    Io(Arc<std::io::Error>),
+    // --- a frame file that's broken, or uses what isn't supported
+    Format(String),
+    // --- past the last frame of a source
+    OutOfRange(u64),
+}
+
+impl fmt::Display for VideoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FrameSize { expected, actual } => {
+                write!(f, "frame should be {} bytes, got {}", expected, actual)
+            }
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Format(message) => write!(f, "bad frame file: {}", message),
+            Self::OutOfRange(index) => write!(f, "there's no frame {}", index),
+        }
+    }
+}
+
+impl std::error::Error for VideoError {}
+
+impl From<std::io::Error> for VideoError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(Arc::new(err))
+    }
+}
+
+// --- where a video's frames come from, by index so they can be played from anywhere
+pub trait FrameSource {
+    // --- frames per second
+    fn frame_rate(&self) -> f64;
+
+    // --- `None` for endless ones
+    fn frame_count(&self) -> Option<u64>;
+
+    // --- its timestamp is when it's shown, `index / frame_rate`
+    fn frame(&mut self, index: u64) -> Result<VideoFrame, VideoError>;
+
+    // --- when the last frame is done showing
+    fn duration(&self) -> Option<Duration> {
+        let count = self.frame_count()?;
+        (self.frame_rate() > 0.).then(|| Duration::from_secs_f64(count as f64 / self.frame_rate()))
+    }
+
+    // --- the frame showing at this point, the last one stays up past the end
+    fn index_at(&self, position: Duration) -> Option<u64> {
+        let index = match self.frame_rate() > 0. {
+            true => (position.as_secs_f64() * self.frame_rate()).floor() as u64,
+            false => 0,
+        };
+        match self.frame_count() {
+            Some(0) => None,
+            Some(count) => Some(index.min(count - 1)),
+            None => Some(index),
+        }
+    }
+}
+
+// --- when a frame of this index is shown
+pub fn timestamp(index: u64, frame_rate: f64) -> Duration {
+    match frame_rate > 0. {
+        true => Duration::from_secs_f64(index as f64 / frame_rate),
+        false => Duration::ZERO,
+    }
+}
+
+// --- how a frame's bytes are laid out, planes follow each other without padding.
+// --- the YUV ones are BT.601 limited range, with chroma at half the width & height
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    // --- 4 bytes per pixel, sRGB
+    #[default]
+    Rgba = 0,
+    // --- a Y plane, then a U and a V plane
+    I420 = 1,
+    // --- a Y plane, then one with U & V interleaved
+    Nv12 = 2,
+}
+
+impl PixelFormat {
+    pub fn frame_size(&self, width: u32, height: u32) -> usize {
+        let luma = width as usize * height as usize;
+        let [cw, ch] = chroma_size(width, height);
+        match self {
+            Self::Rgba => luma * 4,
+            Self::I420 | Self::Nv12 => luma + 2 * cw as usize * ch as usize,
+        }
+    }
+}
+
+fn chroma_size(width: u32, height: u32) -> [u32; 2] {
+    [width.div_ceil(2), height.div_ceil(2)]
+}
+
+// --- one decoded picture of a video and when it's shown.
+// --- clones share the bytes and the upload
+#[derive(Debug, Clone)]
+pub struct VideoFrame {
+    id: u64,
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    timestamp: Duration,
+    data: Arc<[u8]>,
+}
+
+impl PartialEq for VideoFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl VideoFrame {
+    pub fn new(
+        format: PixelFormat,
+        width: u32,
+        height: u32,
+        timestamp: Duration,
+        data: impl Into<Arc<[u8]>>,
+    ) -> Result<Self, VideoError> {
+        let data = data.into();
+        let expected = format.frame_size(width, height);
+        if data.len() != expected {
+            return Err(VideoError::FrameSize {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            format,
+            width,
+            height,
+            timestamp,
+            data,
+        })
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    // --- width, height, bytes per pixel & bytes of each plane
+    fn planes(&self) -> Vec<(u32, u32, u32, &[u8])> {
+        let (width, height) = (self.width, self.height);
+        let [cw, ch] = chroma_size(width, height);
+        let luma = width as usize * height as usize;
+        let chroma = cw as usize * ch as usize;
+
+        match self.format {
+            PixelFormat::Rgba => vec![(width, height, 4, &self.data[..])],
+            PixelFormat::I420 => vec![
+                (width, height, 1, &self.data[..luma]),
+                (cw, ch, 1, &self.data[luma..luma + chroma]),
+                (cw, ch, 1, &self.data[luma + chroma..]),
+            ],
+            PixelFormat::Nv12 => vec![
+                (width, height, 1, &self.data[..luma]),
+                (cw, ch, 2, &self.data[luma..]),
+            ],
+        }
+    }
+}
+
+// --- moving color bars, for trying out playback without a decoder
+#[derive(Debug, Clone, Copy)]
+pub struct TestPattern {
+    pub format: PixelFormat,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+}
+
+impl TestPattern {
+    const BARS: [[u8; 3]; 7] = [
+        [192, 192, 192],
+        [192, 192, 0],
+        [0, 192, 192],
+        [0, 192, 0],
+        [192, 0, 192],
+        [192, 0, 0],
+        [0, 0, 192],
+    ];
+
+    pub fn new(format: PixelFormat, width: u32, height: u32, frame_rate: f64) -> Self {
+        Self {
+            format,
+            width,
+            height,
+            frame_rate,
+        }
+    }
+
+    pub fn frame_at(&self, position: Duration) -> VideoFrame {
+        self.render(self.index_at(position).unwrap_or(0))
+    }
+
+    // --- a white bar sweeps across once a second
+    fn render(&self, index: u64) -> VideoFrame {
+        let (width, height) = (self.width.max(1), self.height.max(1));
+        let timestamp = timestamp(index, self.frame_rate);
+        let sweep = (timestamp.as_secs_f64().fract() * width as f64) as u32;
+
+        let pixel = |x: u32, _y: u32| -> [u8; 3] {
+            if x.abs_diff(sweep) < (width / 64).max(1) {
+                return [255, 255, 255];
+            }
+            Self::BARS[(x * 7 / width) as usize]
+        };
+
+        let mut data = Vec::with_capacity(self.format.frame_size(width, height));
+        match self.format {
+            PixelFormat::Rgba => {
+                for y in 0..height {
+                    for x in 0..width {
+                        let [r, g, b] = pixel(x, y);
+                        data.extend_from_slice(&[r, g, b, 255]);
+                    }
+                }
+            }
+            PixelFormat::I420 | PixelFormat::Nv12 => {
+                for y in 0..height {
+                    for x in 0..width {
+                        data.push(to_yuv(pixel(x, y))[0]);
+                    }
+                }
+                // --- each chroma sample is taken from the top-left pixel it covers
+                let [cw, ch] = chroma_size(width, height);
+                let chroma = (0..ch)
+                    .flat_map(|y| (0..cw).map(move |x| (x * 2, y * 2)))
+                    .map(|(x, y)| to_yuv(pixel(x, y)))
+                    .collect::<Vec<_>>();
+                match self.format {
+                    PixelFormat::I420 => {
+                        data.extend(chroma.iter().map(|yuv| yuv[1]));
+                        data.extend(chroma.iter().map(|yuv| yuv[2]));
+                    }
+                    _ => data.extend(chroma.iter().flat_map(|yuv| [yuv[1], yuv[2]])),
+                }
+            }
+        }
+
+        VideoFrame::new(self.format, width, height, timestamp, data)
+            .expect("test pattern is sized for its format")
+    }
+}
+
+impl FrameSource for TestPattern {
+    fn frame_rate(&self) -> f64 {
+        self.frame_rate
+    }
+
+    fn frame_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn frame(&mut self, index: u64) -> Result<VideoFrame, VideoError> {
+        Ok(self.render(index))
+    }
+}
+
+// --- BT.601, limited range
+fn to_yuv([r, g, b]: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = [r, g, b].map(|c| c as f32);
+    let y = 16. + 0.257 * r + 0.504 * g + 0.098 * b;
+    let u = 128. - 0.148 * r - 0.291 * g + 0.439 * b;
+    let v = 128. + 0.439 * r - 0.368 * g - 0.071 * b;
+    [y, u, v].map(|c| c.round().clamp(0., 255.) as u8)
+}
+
+// --- where a frame is drawn and what of it
+#[derive(Debug, Clone)]
+pub struct FrameDraw {
+    pub frame: VideoFrame,
+    // --- window coordinates, before the transform
+    pub rect: Rect,
+    pub transform: Transform,
+    pub source: Rect,
+    pub opacity: f32,
+}
+
+// --- GPU copies of the frames on screen lately, a frame is dropped once one goes by
+// --- without it being drawn or kept
+#[derive(Default)]
+pub struct FrameCache {
+    planes: HashMap<u64, Rc<[wgpu::TextureView; 3]>>,
+    used: HashSet<u64>,
+}
+
+impl FrameCache {
+    pub fn bind_group(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        draw: &FrameDraw,
+        size: &winit::dpi::PhysicalSize<u32>,
+    ) -> wgpu::BindGroup {
+        let frame = &draw.frame;
+        self.used.insert(frame.id);
+        let planes = self
+            .planes
+            .entry(frame.id)
+            .or_insert_with(|| Rc::new(upload(device, queue, frame)))
+            .clone();
+
+        let ndc = |x: f32, y: f32| {
+            let [x, y] = draw.transform.apply([x, y]);
+            [
+                x / size.width as f32 * 2. - 1.,
+                1. - y / size.height as f32 * 2.,
+            ]
+        };
+        let rect = draw.rect;
+        let [top_left, top_right] = [ndc(rect.x, rect.y), ndc(rect.right(), rect.y)];
+        let [bottom_left, bottom_right] =
+            [ndc(rect.x, rect.bottom()), ndc(rect.right(), rect.bottom())];
+        let source = draw.source;
+
+        let uniforms = UniformBuffer::new(device);
+        uniforms.write(
+            queue,
+            &Uniforms {
+                top: [top_left[0], top_left[1], top_right[0], top_right[1]],
+                bottom: [
+                    bottom_left[0],
+                    bottom_left[1],
+                    bottom_right[0],
+                    bottom_right[1],
+                ],
+                source: [source.x, source.y, source.width, source.height],
+                format: frame.format as u32,
+                opacity: draw.opacity,
+                _padding: [0; 2],
+            },
+        );
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Video Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&planes[0]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniforms.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&planes[1]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&planes[2]),
+                },
+            ],
+        })
+    }
+
+    // --- still on screen, but not drawn again this frame
+    pub fn keep(&mut self, frame: &VideoFrame) {
+        self.used.insert(frame.id);
+    }
+
+    pub fn end_frame(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.planes.retain(|id, _| used.contains(id));
+    }
+}
+
+// --- every binding gets a view, the planes a format doesn't have are a blank pixel
+fn upload(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    frame: &VideoFrame,
+) -> [wgpu::TextureView; 3] {
+    let mut views = frame
+        .planes()
+        .into_iter()
+        .map(|(width, height, bpp, bytes)| {
+            let format = match (frame.format, bpp) {
+                (PixelFormat::Rgba, _) => wgpu::TextureFormat::Rgba8UnormSrgb,
+                (_, 2) => wgpu::TextureFormat::Rg8Unorm,
+                _ => wgpu::TextureFormat::R8Unorm,
+            };
+            plane_texture(device, queue, format, [width, height], bpp, bytes)
+        })
+        .collect::<Vec<_>>();
+    while views.len() < 3 {
+        views.push(plane_texture(
+            device,
+            queue,
+            wgpu::TextureFormat::R8Unorm,
+            [1, 1],
+            1,
+            &[0],
+        ));
+    }
+
+    views.try_into().expect("at most three planes")
+}
+
+fn plane_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    format: wgpu::TextureFormat,
+    [width, height]: [u32; 2],
+    bytes_per_pixel: u32,
+    bytes: &[u8],
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: width.max(1),
+        height: height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Video Plane Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    if width > 0 && height > 0 {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            bytes,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_pixel * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+    }
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let plane = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Video Bind Group Layout"),
+        entries: &[
+            plane(0),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            plane(3),
+            plane(4),
+        ],
+    })
+}
+
+// --- drawn like any other paint, within the scissor & the stencil count of its clips,
+// --- with as many samples as the rest
+pub fn pipeline(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    samples: u32,
+) -> wgpu::RenderPipeline {
+    let shader_module =
+        device.create_shader_module(wgpu::include_wgsl!("../shaders/video_shader.wgsl"));
+
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Video Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Video Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: "vs_main",
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: Some(stencil_state(Stencil::Paint)),
+        multisample: wgpu::MultisampleState {
+            count: samples,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
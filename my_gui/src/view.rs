@@ -1,6 +1,10 @@
 use wgpu::util::DeviceExt;
 
-use crate::elements::{Button, IntoElement};
+use crate::elements::{
+    Button, Canvas, Container, Icon, Image, IntoElement, Scroll, TextArea, TextInput, Video,
+};
+use crate::geometry::{Rect, Transform};
+use crate::style::{Cascade, Color, Edges, ElementState, Overflow, Style};
 use crate::vertex::Vertex;
 
 #[rustfmt::skip]
@@ -12,21 +16,217 @@ const RECT_INDICES: &[u16] = &[
 #[derive(Clone)]
 pub enum View {
     Button(Button),
+    Canvas(Canvas),
+    Container(Container),
+    Icon(Icon),
+    Image(Image),
+    Scroll(Scroll),
+    TextInput(TextInput),
+    TextArea(TextArea),
+    Video(Video),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Layout {
+    pub border_box: Rect,
+    // --- inside the border, what overflowing children are cut to
+    pub padding_box: Rect,
+    pub content_box: Rect,
+}
+
+// --- a view as it ends up on the screen: where it is, and which style it's painted with
+pub struct Resolved<'v> {
+    pub view: &'v View,
+    pub path: Vec<usize>,
+    pub layout: Layout,
+    pub style: Style,
+    // --- window rect it's cut to by its ancestors, `None` when nothing clips it
+    pub clip: Option<Rect>,
+    // --- the rounded or transformed ones among those clips, outermost first
+    pub masks: Vec<Clip>,
+    // --- from its layout to where it's painted in the window, its ancestors' included
+    pub transform: Transform,
+}
+
+// --- what a view cuts its children to: `rect` mapped to the window by `transform`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Clip {
+    pub rect: Rect,
+    pub radius: f32,
+    pub transform: Transform,
+}
+
+impl Clip {
+    // --- moving is folded into the rect, anything else stays a transform
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let transform = self.transform.then(transform);
+        match transform.is_translation() {
+            true => Self {
+                rect: self.rect.translate(transform.e, transform.f),
+                transform: Transform::IDENTITY,
+                ..*self
+            },
+            false => Self { transform, ..*self },
+        }
+    }
+
+    // --- scissors only cut along the window's axes, other clips go through the stencil
+    pub fn is_mask(&self) -> bool {
+        self.radius > 0. || !self.transform.is_identity()
+    }
+
+    // --- where it is in the window, as a scissor rect
+    pub fn bounds(&self) -> Rect {
+        self.transform.map_rect(&self.rect)
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let Some(inverse) = self.transform.invert() else {
+            return false;
+        };
+        let [x, y] = inverse.apply([x, y]);
+        if !self.rect.contains(x, y) {
+            return false;
+        }
+
+        // --- only the corners can be outside, measured from the center of their circle
+        let radius = self
+            .radius
+            .min(self.rect.width / 2.)
+            .min(self.rect.height / 2.);
+        let dx = (self.rect.x + radius - x).max(x - (self.rect.right() - radius));
+        let dy = (self.rect.y + radius - y).max(y - (self.rect.bottom() - radius));
+        dx <= 0. || dy <= 0. || dx * dx + dy * dy <= radius * radius
+    }
+}
+
+// --- how a draw goes through the stencil buffer, which counts the rounded clips over each pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stencil {
+    // --- painted where the count matches its own number of rounded clips
+    Paint,
+    // --- adds the clip's shape to the count, or takes it away once its children are done
+    Push,
+    Pop,
+    // --- replaces whatever was there, color & count, to redraw part of the window
+    Clear,
+}
+
+impl Stencil {
+    pub const ALL: [Self; 4] = [Self::Paint, Self::Push, Self::Pop, Self::Clear];
+}
+
+// --- where a view is and what it looks like, without borrowing it
+#[derive(Debug, Clone, Default)]
+pub struct Placement {
+    pub layout: Layout,
+    pub style: Style,
+    pub transform: Transform,
+}
+
+impl Layout {
+    // --- where the children's rects start from, moved by however far they're scrolled
+    pub fn children_origin(&self, view: &View) -> [f32; 2] {
+        let [x, y] = view.element().scroll_offset();
+        [self.content_box.x - x, self.content_box.y - y]
+    }
+
+    // --- margin box in window coordinates
+    pub fn new(margin_box: Rect, style: &Style) -> Self {
+        let border_box = margin_box.inset(style.margin.unwrap_or_default());
+
+        let border = style.border.map(|b| b.width).unwrap_or(0.);
+        let padding_box = border_box.inset(Edges::all(border));
+        let content_box = padding_box.inset(style.padding.unwrap_or_default());
+
+        Self {
+            border_box,
+            padding_box,
+            content_box,
+        }
+    }
+}
+
+// --- the element's own clip when it has one, otherwise its padding box if it hides overflow.
+// --- corners follow the inside of the border
+pub fn clip_of(view: &View, layout: &Layout, style: &Style) -> Option<Clip> {
+    let rect = view
+        .element()
+        .clip(layout)
+        .or_else(|| (style.overflow == Some(Overflow::Hidden)).then_some(layout.padding_box))?;
+    let border = style.border.map_or(0., |border| border.width);
+
+    Some(Clip {
+        rect,
+        radius: (style.radius.unwrap_or(0.) - border).max(0.),
+        transform: Transform::IDENTITY,
+    })
+}
+
+// --- the view's own transform, around its origin in window coordinates
+pub fn transform_of(layout: &Layout, style: &Style) -> Transform {
+    let Some(transform) = style.transform else {
+        return Transform::IDENTITY;
+    };
+    let [x, y] = style.transform_origin.unwrap_or([0.5, 0.5]);
+    let rect = layout.border_box;
+    let origin = [rect.x + x * rect.width, rect.y + y * rect.height];
+
+    Transform::translate(-origin[0], -origin[1])
+        .then(&transform)
+        .then(&Transform::translate(origin[0], origin[1]))
 }
 
 impl View {
-    pub fn vertices(&self, size: &winit::dpi::PhysicalSize<u32>) -> [Vertex; 4] {
+    pub fn element(&self) -> &dyn IntoElement {
         match self {
-            Self::Button(button) => button.vertices(size),
+            Self::Button(button) => button,
+            Self::Canvas(canvas) => canvas,
+            Self::Container(container) => container,
+            Self::Icon(icon) => icon,
+            Self::Image(image) => image,
+            Self::Scroll(scroll) => scroll,
+            Self::TextInput(text_input) => text_input,
+            Self::TextArea(text_area) => text_area,
+            Self::Video(video) => video,
         }
     }
 
-    pub fn indices(&self, base: u32) -> [u32; 6] {
+    pub fn element_mut(&mut self) -> &mut dyn IntoElement {
         match self {
-            Self::Button(button) => button.indices(base),
+            Self::Button(button) => button,
+            Self::Canvas(canvas) => canvas,
+            Self::Container(container) => container,
+            Self::Icon(icon) => icon,
+            Self::Image(image) => image,
+            Self::Scroll(scroll) => scroll,
+            Self::TextInput(text_input) => text_input,
+            Self::TextArea(text_area) => text_area,
+            Self::Video(video) => video,
         }
     }
 
+    pub fn children(&self) -> &[View] {
+        self.element().children()
+    }
+
+    pub fn children_mut(&mut self) -> &mut [View] {
+        self.element_mut().children_mut()
+    }
+
+    pub fn vertices(
+        &self,
+        rect: &Rect,
+        style: &Style,
+        size: &winit::dpi::PhysicalSize<u32>,
+    ) -> [Vertex; 4] {
+        self.element().vertices(rect, style, size)
+    }
+
+    pub fn indices(&self, base: u32) -> [u32; 6] {
+        self.element().indices(base)
+    }
+
     /* pub fn rgba(&self) -> Vec<u8> {
         match self {
             Self::Button(button) => button.rgba(),
@@ -54,21 +254,261 @@ impl View {
         ]
     } */
 
-    pub fn tex_view(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
+    pub fn fill_color(&self, style: &Style) -> Color {
+        self.element().fill_color(style)
+    }
+
+    pub fn num_indices(&self) -> u32 {
         match self {
-            Self::Button(button) => button.texture_view(device, queue),
+            Self::Button(_)
+            | Self::Canvas(_)
+            | Self::Container(_)
+            | Self::Icon(_)
+            | Self::Image(_)
+            | Self::Scroll(_)
+            | Self::TextInput(_)
+            | Self::TextArea(_)
+            | Self::Video(_) => RECT_INDICES.len() as u32,
         }
     }
+}
 
-    pub fn sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
-        match self {
-            Self::Button(button) => button.sampler(device),
+// --- what a view's children get from it while flattening
+#[derive(Clone)]
+struct Parent {
+    origin: [f32; 2],
+    style: Style,
+    clip: Option<Rect>,
+    masks: Vec<Clip>,
+    transform: Transform,
+}
+
+// --- an overlay found along the way, painted once the rest of the tree is done
+struct Deferred<'v> {
+    view: &'v View,
+    path: Vec<usize>,
+    style: Style,
+    rect: Rect,
+    origin: [f32; 2],
+    transform: Transform,
+}
+
+// --- what flattening has collected so far
+#[derive(Default)]
+struct Flattened<'v> {
+    resolved: Vec<Resolved<'v>>,
+    overlays: Vec<Deferred<'v>>,
+}
+
+// --- depth-first with siblings in z-index order, so the result is also the painting order:
+// --- parents below their children, and overlays above everything else
+pub fn flatten<'v>(views: &'v [View], cascade: &Cascade) -> Vec<Resolved<'v>> {
+    fn place<'v>(
+        view: &'v View,
+        style: Style,
+        rect: Rect,
+        cascade: &Cascade,
+        parent: &Parent,
+        path: &mut Vec<usize>,
+        out: &mut Flattened<'v>,
+    ) {
+        let [x, y] = parent.origin;
+        let layout = Layout::new(rect.translate(x, y), &style);
+        let transform = transform_of(&layout, &style).then(&parent.transform);
+
+        let own = clip_of(view, &layout, &style).map(|clip| clip.transformed(&transform));
+        let mut masks = parent.masks.clone();
+        masks.extend(own.filter(Clip::is_mask));
+        let children = Parent {
+            origin: layout.children_origin(view),
+            style: style.clone(),
+            clip: match (parent.clip, own) {
+                (Some(outer), Some(inner)) => Some(outer.intersect(&inner.bounds())),
+                (outer, inner) => inner.map(|inner| inner.bounds()).or(outer),
+            },
+            masks,
+            transform,
+        };
+
+        out.resolved.push(Resolved {
+            view,
+            path: path.clone(),
+            layout,
+            style,
+            clip: parent.clip,
+            masks: parent.masks.clone(),
+            transform,
+        });
+
+        visit(view.children(), cascade, &children, path, out);
+    }
+
+    fn visit<'v>(
+        views: &'v [View],
+        cascade: &Cascade,
+        parent: &Parent,
+        path: &mut Vec<usize>,
+        out: &mut Flattened<'v>,
+    ) {
+        let mut siblings = views
+            .iter()
+            .enumerate()
+            .map(|(idx, view)| {
+                let (style, rect) = view.element().animated(cascade);
+                (idx, view, style.inherit(&parent.style), rect)
+            })
+            .collect::<Vec<_>>();
+        siblings.sort_by_key(|(.., style, _)| style.z_index.unwrap_or(0));
+
+        for (idx, view, style, rect) in siblings {
+            path.push(idx);
+            if style.overlay == Some(true) {
+                out.overlays.push(Deferred {
+                    view,
+                    path: path.clone(),
+                    style,
+                    rect,
+                    origin: parent.origin,
+                    transform: parent.transform,
+                });
+            } else {
+                place(view, style, rect, cascade, parent, path, out);
+            }
+            path.pop();
         }
     }
 
-    pub fn num_indices(&self) -> u32 {
-        match self {
-            Self::Button(_) => RECT_INDICES.len() as u32,
+    let root = Parent {
+        origin: [0., 0.],
+        style: cascade.root.clone(),
+        clip: None,
+        masks: vec![],
+        transform: Transform::IDENTITY,
+    };
+    let mut out = Flattened::default();
+    visit(views, cascade, &root, &mut Vec::new(), &mut out);
+
+    // --- overlays inside overlays are found while placing the outer one, and go above it.
+    // --- they leave their ancestors' clips behind, but still move along with them
+    while !out.overlays.is_empty() {
+        let mut batch = std::mem::take(&mut out.overlays);
+        batch.sort_by_key(|deferred| deferred.style.z_index.unwrap_or(0));
+
+        for deferred in batch {
+            let parent = Parent {
+                origin: deferred.origin,
+                transform: deferred.transform,
+                ..root.clone()
+            };
+            let mut path = deferred.path;
+            place(
+                deferred.view,
+                deferred.style,
+                deferred.rect,
+                cascade,
+                &parent,
+                &mut path,
+                &mut out,
+            );
+        }
+    }
+
+    out.resolved
+}
+
+// --- placements of every view from the root down to the one at `path`
+pub fn placements(views: &[View], cascade: &Cascade, path: &[usize]) -> Vec<Placement> {
+    let mut out: Vec<Placement> = Vec::with_capacity(path.len());
+    let mut views = views;
+    let mut origin = [0., 0.];
+    let mut transform = Transform::IDENTITY;
+
+    for idx in path {
+        let Some(view) = views.get(*idx) else {
+            break;
+        };
+
+        let parent = out.last().map_or(cascade.root, |p| &p.style);
+        let (style, rect) = view.element().animated(cascade);
+        let style = style.inherit(parent);
+        let layout = Layout::new(rect.translate(origin[0], origin[1]), &style);
+        transform = transform_of(&layout, &style).then(&transform);
+
+        origin = layout.children_origin(view);
+        out.push(Placement {
+            layout,
+            style,
+            transform,
+        });
+        views = view.children();
+    }
+
+    out
+}
+
+// --- the top-most view under the point, by painting order so overlays come first,
+// --- parts that are clipped away don't count. the point is taken back through
+// --- each view's transform to test it against its layout
+pub fn hit_test(views: &[View], cascade: &Cascade, x: f32, y: f32) -> Option<(Vec<usize>, Style)> {
+    flatten(views, cascade)
+        .into_iter()
+        .rev()
+        .find(|resolved| {
+            let local = resolved
+                .transform
+                .invert()
+                .map(|inverse| inverse.apply([x, y]));
+            local.is_some_and(|[x, y]| resolved.layout.border_box.contains(x, y))
+                && resolved.clip.is_none_or(|clip| clip.contains(x, y))
+                && resolved.masks.iter().all(|mask| mask.contains(x, y))
+        })
+        .map(|resolved| (resolved.path, resolved.style))
+}
+
+// --- feeds every element its un-animated style so transitions can kick in,
+// --- returns whether anything is still moving
+pub fn update_motion(views: &mut [View], cascade: &Cascade) -> bool {
+    let mut running = false;
+
+    for view in views.iter_mut() {
+        let element = view.element_mut();
+        let (style, rect) = (element.style(cascade), element.rect());
+        running |= element.motion_mut().update(&style, &rect, cascade.now);
+        running |= element.step(cascade.now);
+        running |= update_motion(view.children_mut(), cascade);
+    }
+
+    running
+}
+
+pub fn view_at<'v>(views: &'v [View], path: &[usize]) -> Option<&'v View> {
+    let (first, rest) = path.split_first()?;
+    let view = views.get(*first)?;
+
+    if rest.is_empty() {
+        Some(view)
+    } else {
+        view_at(view.children(), rest)
+    }
+}
+
+pub fn view_at_mut<'v>(views: &'v mut [View], path: &[usize]) -> Option<&'v mut View> {
+    let (first, rest) = path.split_first()?;
+    let view = views.get_mut(*first)?;
+
+    if rest.is_empty() {
+        Some(view)
+    } else {
+        view_at_mut(view.children_mut(), rest)
+    }
+}
+
+// --- applies `f` to the view at `path` and every one of its ancestors,
+// --- the same way `:hover` & `:active` match the whole chain in CSS
+pub fn update_along(views: &mut [View], path: &[usize], mut f: impl FnMut(&mut ElementState)) {
+    for depth in 1..=path.len() {
+        if let Some(view) = view_at_mut(views, &path[..depth]) {
+            f(view.element_mut().state_mut());
         }
     }
 }
@@ -93,11 +533,58 @@ pub fn index_buffer(device: &wgpu::Device, indices: Vec<u32>) -> wgpu::Buffer {
     })
 }
 
+pub const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+// --- draws pass where the count matches the reference, masks move it up or down
+pub fn stencil_state(stencil: Stencil) -> wgpu::DepthStencilState {
+    let pass_op = match stencil {
+        Stencil::Paint => wgpu::StencilOperation::Keep,
+        Stencil::Push => wgpu::StencilOperation::IncrementClamp,
+        Stencil::Pop => wgpu::StencilOperation::DecrementClamp,
+        Stencil::Clear => wgpu::StencilOperation::Replace,
+    };
+    let compare = match stencil {
+        Stencil::Clear => wgpu::CompareFunction::Always,
+        _ => wgpu::CompareFunction::Equal,
+    };
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+
+    wgpu::DepthStencilState {
+        format: STENCIL_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: !0,
+            write_mask: !0,
+        },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
 pub fn render_pipeline(
     device: &wgpu::Device,
     config: &wgpu::SurfaceConfiguration,
     bind_group_layout: &wgpu::BindGroupLayout,
+    stencil: Stencil,
+    samples: u32,
 ) -> wgpu::RenderPipeline {
+    // --- masks only touch the stencil, carved to their rounded shape by `fs_mask`
+    let (entry_point, write_mask) = match stencil {
+        Stencil::Paint | Stencil::Clear => ("fs_main", wgpu::ColorWrites::ALL),
+        Stencil::Push | Stencil::Pop => ("fs_mask", wgpu::ColorWrites::empty()),
+    };
+    let blend = match stencil {
+        Stencil::Clear => wgpu::BlendState::REPLACE,
+        _ => wgpu::BlendState::ALPHA_BLENDING,
+    };
+
     let shader_module = device.create_shader_module(wgpu::include_wgsl!("../shaders/shader.wgsl"));
 
     let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -117,11 +604,11 @@ pub fn render_pipeline(
         },
         fragment: Some(wgpu::FragmentState {
             module: &shader_module,
-            entry_point: "fs_main",
+            entry_point,
             targets: &[Some(wgpu::ColorTargetState {
                 format: config.format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
+                blend: Some(blend),
+                write_mask,
             })],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         }),
@@ -129,14 +616,15 @@ pub fn render_pipeline(
             topology: wgpu::PrimitiveTopology::TriangleList,
             strip_index_format: None,
             front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
+            // --- mirroring transforms turn shapes around, both sides are drawn
+            cull_mode: None,
             polygon_mode: wgpu::PolygonMode::Fill,
             unclipped_depth: false,
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: Some(stencil_state(stencil)),
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: samples,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -1,12 +1,78 @@
 use crate::{
-    view::{index_buffer, render_pipeline, vertex_buffer},
-    View,
+    damage::Damage,
+    elements::{gradient_texture, quad, sampler, shadow_quad, Fill},
+    geometry::Transform,
+    profiler::{FramePhase, Profiler},
+    style::{Cascade, Color, Edges, Gradient, Style, TRANSPARENT},
+    texture::{Texture, TextureCache},
+    theme::Theme,
+    vertex::Vertex,
+    video::{self, FrameCache, FrameDraw},
+    view::{
+        clip_of, flatten, index_buffer, render_pipeline, vertex_buffer, Clip, Placement, Resolved,
+        Stencil, STENCIL_FORMAT,
+    },
+    Rect, View,
 };
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const FOCUS_RING_WIDTH: f32 = 2.;
+const FOCUS_RING_OFFSET: f32 = 2.;
+
+// --- what's asked for until told otherwise, every adapter can do 4
+const DEFAULT_SAMPLE_COUNT: u32 = 4;
+
 use winit::window::Window;
 
+// --- how finished frames reach the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    // --- one frame per refresh, waiting for it, never tears. every surface can do this
+    #[default]
+    Vsync,
+    // --- one frame per refresh, the newest one replacing any still waiting, never tears
+    Mailbox,
+    // --- right away, lowest latency, may tear
+    Immediate,
+}
+
+impl PresentMode {
+    fn wgpu(self) -> wgpu::PresentMode {
+        match self {
+            Self::Vsync => wgpu::PresentMode::Fifo,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+// --- how a draw is cut: the scissor, and what it does with the stencil at which count
+#[derive(Debug, Clone, Copy)]
+struct Pass {
+    scissor: Option<Rect>,
+    stencil: Stencil,
+    reference: u32,
+}
+
+impl Pass {
+    fn paint(resolved: &Resolved) -> Self {
+        Self {
+            scissor: resolved.clip,
+            stencil: Stencil::Paint,
+            reference: resolved.masks.len() as u32,
+        }
+    }
+}
+
 pub struct WindowContext<'a> {
-    pub bg_color: &'a [f64; 4],
+    pub bg_color: [f64; 4],
     pub window: Option<Window>,
     pub size: Option<winit::dpi::PhysicalSize<u32>>,
     // --- surface
@@ -14,16 +80,53 @@ pub struct WindowContext<'a> {
     device: Option<wgpu::Device>,
     queue: Option<wgpu::Queue>,
     config: Option<wgpu::SurfaceConfiguration>,
+    // --- counts the rounded clips over each pixel, sized like the surface
+    stencil: Option<wgpu::TextureView>,
+    // --- samples per pixel asked for, and the counts the surface & stencil formats allow
+    sample_count: u32,
+    sample_counts: Vec<u32>,
+    // --- asked for, and the ones the surface can do
+    present_mode: PresentMode,
+    present_modes: Vec<wgpu::PresentMode>,
+    // --- drawn into when multisampling, then resolved onto the surface
+    msaa: Option<wgpu::TextureView>,
+    // --- drawn into without multisampling, then copied onto the surface, so the last frame
+    // --- is still there to draw over
+    cache: Option<wgpu::Texture>,
+    // --- what changed since the last frame
+    damage: Damage,
+    // --- what every frame draws with
+    pipelines: Option<Pipelines>,
+    // --- whether the render pass is timed on the GPU, and what times it
+    gpu_timing: bool,
+    timer: Option<GpuTimer>,
+    // --- images stay uploaded for as long as they're drawn
+    textures: TextureCache,
+    // --- and video frames for as long as they're showing
+    frames: FrameCache,
 }
 
 impl Default for WindowContext<'_> {
     fn default() -> Self {
         Self {
-            bg_color: &[0.0, 0.0, 0.0, 1.0],
+            bg_color: [0.0, 0.0, 0.0, 1.0],
             surface: None,
             device: None,
             queue: None,
             config: None,
+            stencil: None,
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            sample_counts: vec![1],
+            present_mode: PresentMode::default(),
+            present_modes: vec![],
+            msaa: None,
+            cache: None,
+            damage: Damage::default(),
+            pipelines: None,
+            gpu_timing: false,
+            timer: None,
+            textures: TextureCache::default(),
+            frames: FrameCache::default(),
             size: None,
             window: None,
         }
@@ -35,10 +138,103 @@ impl<'a> WindowContext<'a> {
         self.window.replace(window);
     }
 
-    pub fn set_bg_color(&mut self, bg_color: &'a [f64; 4]) {
+    pub fn set_bg_color(&mut self, bg_color: [f64; 4]) {
+        if self.bg_color != bg_color {
+            self.damage.invalidate();
+        }
         self.bg_color = bg_color;
     }
 
+    // --- 1 (off), 2, 4 or 8 samples per pixel. when the adapter can't do as many,
+    // --- the most it can below that is used
+    pub fn set_sample_count(&mut self, count: u32) {
+        self.sample_count = count;
+        self.create_targets();
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_counts
+            .iter()
+            .copied()
+            .filter(|&count| count <= self.sample_count)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // --- falls back to vsync when the surface can't do it
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        self.present_mode = mode;
+        let mode = self.present_mode().wgpu();
+
+        let (Some(surface), Some(device), Some(config)) = (
+            self.surface.as_ref(),
+            self.device.as_ref(),
+            self.config.as_mut(),
+        ) else {
+            return;
+        };
+        if config.present_mode != mode {
+            config.present_mode = mode;
+            surface.configure(device, config);
+            self.damage.invalidate();
+        }
+    }
+
+    // --- what's actually used, before the surface exists it's what was asked for
+    pub fn present_mode(&self) -> PresentMode {
+        let supported =
+            self.present_modes.is_empty() || self.present_modes.contains(&self.present_mode.wgpu());
+        match supported {
+            true => self.present_mode,
+            false => PresentMode::Vsync,
+        }
+    }
+
+    // --- needs timestamp queries, which not every adapter can do
+    pub fn set_gpu_timing(&mut self, on: bool) {
+        self.gpu_timing = on;
+        self.timer = None;
+
+        let (Some(device), Some(queue)) = (self.device.as_ref(), self.queue.as_ref()) else {
+            return;
+        };
+        if on && device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            self.timer = Some(GpuTimer::new(device, queue));
+        }
+    }
+
+    // --- before the device exists it's what was asked for
+    pub fn gpu_timing(&self) -> bool {
+        match self.device {
+            Some(_) => self.timer.is_some(),
+            None => self.gpu_timing,
+        }
+    }
+
+    // --- the stencil & the multisampled target follow the surface's size & the sample count,
+    // --- the pipelines only its format & the sample count
+    fn create_targets(&mut self) {
+        let (Some(device), Some(queue), Some(config)) = (
+            self.device.as_ref(),
+            self.queue.as_ref(),
+            self.config.as_ref(),
+        ) else {
+            return;
+        };
+        let samples = self.sample_count();
+
+        let current = self.pipelines.as_ref();
+        if current.is_none_or(|pipelines| !pipelines.fits(config, samples)) {
+            self.pipelines = Some(Pipelines::new(device, queue, config, samples));
+        }
+
+        self.stencil = Some(stencil_view(device, config, samples));
+        self.msaa = (samples > 1).then(|| msaa_view(device, config, samples));
+        self.cache = (samples == 1 && config.usage.contains(wgpu::TextureUsages::COPY_DST))
+            .then(|| cache_texture(device, config));
+        self.damage.invalidate();
+    }
+
     pub fn init(&mut self) {
         let window = self.window.as_ref().unwrap();
 
@@ -51,7 +247,8 @@ impl<'a> WindowContext<'a> {
 
         let surface = instance.create_surface(window).unwrap();
 
-        let (device, queue, config) = futures::executor::block_on(async {
+        let requested = self.present_mode.wgpu();
+        let (device, queue, config, sample_counts, modes) = futures::executor::block_on(async {
             let adapter = instance
                 .request_adapter(&wgpu::RequestAdapterOptions {
                     power_preference: wgpu::PowerPreference::default(),
@@ -65,7 +262,11 @@ impl<'a> WindowContext<'a> {
                 .request_device(
                     &wgpu::DeviceDescriptor {
                         label: None,
-                        required_features: wgpu::Features::empty(),
+                        // --- without the first, only 1 & 4 samples are allowed. the second
+                        // --- is only for timing frames on the GPU
+                        required_features: adapter.features()
+                            & (wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+                                | wgpu::Features::TIMESTAMP_QUERY),
                         required_limits: wgpu::Limits::default(),
                     },
                     None,
@@ -83,18 +284,43 @@ impl<'a> WindowContext<'a> {
                 .copied()
                 .unwrap_or(surface_capabilities.formats[0]);
 
+            // --- copied onto when the last frame is kept in a texture of its own
+            let copy = surface_capabilities.usages & wgpu::TextureUsages::COPY_DST;
+            let present_mode = match surface_capabilities.present_modes.contains(&requested) {
+                true => requested,
+                false => wgpu::PresentMode::Fifo,
+            };
+
             let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | copy,
                 format: surface_format,
                 width: size.width,
                 height: size.height,
-                present_mode: surface_capabilities.present_modes[0],
+                present_mode,
                 alpha_mode: surface_capabilities.alpha_modes[0],
                 view_formats: vec![],
                 desired_maximum_frame_latency: 2,
             };
 
-            (device, queue, config)
+            let features = |format: wgpu::TextureFormat| match device
+                .features()
+                .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+            {
+                true => adapter.get_texture_format_features(format),
+                false => format.guaranteed_format_features(device.features()),
+            };
+            let sample_counts = [1, 2, 4, 8]
+                .into_iter()
+                .filter(|&count| {
+                    [config.format, STENCIL_FORMAT]
+                        .into_iter()
+                        .all(|format| features(format).flags.sample_count_supported(count))
+                })
+                .collect();
+
+            let modes = surface_capabilities.present_modes;
+
+            (device, queue, config, sample_counts, modes)
         });
 
         surface.configure(&device, &config);
@@ -104,9 +330,13 @@ impl<'a> WindowContext<'a> {
         self.device = Some(device);
         self.queue = Some(queue);
         self.config = Some(config);
+        self.sample_counts = sample_counts;
         self.size = Some(size);
         self.surface =
             Some(unsafe { std::mem::transmute::<wgpu::Surface<'_>, wgpu::Surface<'a>>(surface) });
+        self.present_modes = modes;
+        self.create_targets();
+        self.set_gpu_timing(self.gpu_timing);
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -120,147 +350,895 @@ impl<'a> WindowContext<'a> {
             if let Some(s) = self.surface.as_mut() {
                 s.configure(self.device.as_ref().unwrap(), self.config.as_ref().unwrap())
             };
+            self.create_targets();
         }
     }
 
-    // --- this function render the whole window
-    pub fn render(&mut self, components: &Vec<View>) -> Result<(), wgpu::SurfaceError> {
-        let mut vertex_buf = Vec::new();
-        let mut index_buf = Vec::new();
-        let mut texture_view_array = Vec::new();
-        let mut sampler_array = Vec::new();
-        // let mut rgba = Vec::new();
+    // --- draws the parts of the window that changed since the last frame,
+    // --- nothing at all when nothing did. whether a frame was presented,
+    // --- every phase is timed into `profiler`
+    pub fn render(
+        &mut self,
+        components: &[View],
+        cascade: &Cascade,
+        profiler: &mut Profiler,
+    ) -> Result<bool, wgpu::SurfaceError> {
+        let device = self.device.as_ref().unwrap();
+        let queue = self.queue.as_ref().unwrap();
+        let size = self.size.as_ref().unwrap();
 
-        let mut num_vertices = 0;
-        let mut num_indices = 0;
+        if let Some(took) = self.timer.as_ref().and_then(|timer| timer.read(device)) {
+            profiler.gpu(took);
+        }
 
-        for component in components {
-            let vertices = component.vertices(self.size.as_ref().unwrap());
-            vertex_buf.extend_from_slice(vertices.as_slice());
+        let resolved = flatten(components, cascade);
+        profiler.lap(FramePhase::Layout);
 
-            let indices = component.indices(num_vertices);
-            index_buf.extend_from_slice(indices.as_slice());
+        let window = Rect::new(0., 0., size.width as f32, size.height as f32);
+        let mut draws = collect(resolved, cascade, size);
+        draws.extend(overlay(profiler, window, size));
+        let bounds = draws
+            .iter()
+            .map(|draw| draw.bounds(size))
+            .collect::<Vec<_>>();
 
-            num_vertices += vertices.len() as u32;
-            num_indices += component.num_indices();
+        // --- without the last frame kept around, any change means drawing all of it
+        let signatures = draws
+            .iter()
+            .map(Draw::signature)
+            .zip(bounds.iter().copied());
+        let mut regions = self.damage.update(signatures.collect(), window);
+        profiler.lap(FramePhase::Paint);
+        if regions.is_empty() {
+            return Ok(false);
+        }
+        if self.msaa.is_none() && self.cache.is_none() {
+            regions = vec![window];
+        }
+        let whole = regions == [window];
 
-            let tv =
-                component.tex_view(self.device.as_ref().unwrap(), self.queue.as_ref().unwrap());
-            texture_view_array.push(tv);
+        let mut vertex_buf = Vec::new();
+        let mut index_buf = Vec::new();
+        let mut recorded = Vec::new();
+        // --- which recorded draw goes in which region, in drawing order
+        let mut steps = Vec::new();
 
-            let s = component.sampler(self.device.as_ref().unwrap());
-            sampler_array.push(s);
+        let mut record = |vertices: &[Vertex], indices: &[u32], resources: Resources| {
+            let base = vertex_buf.len() as u32;
+            let start = index_buf.len() as u32;
+            vertex_buf.extend_from_slice(vertices);
+            index_buf.extend(indices.iter().map(|i| i + base));
+            recorded.push((start..index_buf.len() as u32, resources));
+            recorded.len() - 1
+        };
 
-            // let color = component.rgba();
-            // rgba.extend_from_slice(color.as_slice());
+        // --- each region starts over from the background, unless it's all of the window
+        let background = self
+            .textures
+            .solid(device, queue, encode_srgb(self.bg_color));
+        for region in regions.iter().filter(|_| !whole) {
+            let resources = Resources {
+                pass: Pass {
+                    scissor: Some(*region),
+                    stencil: Stencil::Clear,
+                    reference: 0,
+                },
+                texture: background.clone(),
+                ramp: None,
+                frame: None,
+            };
+            let idx = record(
+                &quad(region, &Style::new(), size),
+                &[0, 1, 2, 0, 2, 3],
+                resources,
+            );
+            steps.push((*region, idx));
         }
 
-        let bind_group_layout = self.device.as_ref().unwrap().create_bind_group_layout(
-            &wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bind Group Layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            },
-        );
+        // --- only what touches a region is uploaded & drawn, in every region it touches
+        let mut drawn = Vec::new();
+        for (draw, bounds) in draws.iter().zip(&bounds) {
+            let touched = regions
+                .iter()
+                .filter(|region| region.overlaps(bounds))
+                .copied()
+                .collect::<Vec<_>>();
+            if touched.is_empty() {
+                if let Paint::Texture(texture) = &draw.paint {
+                    self.textures.keep(texture);
+                }
+                if let Some(frame) = draw.frame.as_ref() {
+                    self.frames.keep(&frame.frame);
+                }
+                continue;
+            }
+
+            let texture = match &draw.paint {
+                Paint::Solid(color) => self.textures.solid(device, queue, *color),
+                Paint::Texture(texture) => self.textures.view(device, queue, texture),
+            };
+            let [fill, border] = &draw.gradients;
+            let ramp = (fill.is_some() || border.is_some())
+                .then(|| gradient_texture(device, queue, fill.as_ref(), border.as_ref()));
+            let resources = Resources {
+                pass: draw.pass,
+                texture,
+                ramp,
+                frame: draw.frame.clone(),
+            };
+            let idx = record(&draw.vertices, &draw.indices, resources);
+            drawn.push((idx, touched));
+        }
+        for region in regions.iter() {
+            let inside = drawn
+                .iter()
+                .filter(|(_, touched)| touched.contains(region))
+                .map(|(idx, _)| (*region, *idx));
+            steps.extend(inside);
+        }
 
-        let bind_group =
-            self.device
-                .as_ref()
-                .unwrap()
-                .create_bind_group(&wgpu::BindGroupDescriptor {
+        // --- one bind group per recorded draw, each one carries its own texture
+        let pipelines = self.pipelines.as_ref().unwrap();
+        let bind_groups = recorded
+            .iter()
+            .map(|(_, resources)| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
                     label: Some("Bind Group"),
-                    layout: &bind_group_layout,
+                    layout: &pipelines.layout,
                     entries: &[
                         wgpu::BindGroupEntry {
                             binding: 0,
-                            resource: wgpu::BindingResource::TextureViewArray(
-                                texture_view_array.iter().collect::<Vec<_>>().as_slice(),
-                            ),
+                            resource: wgpu::BindingResource::TextureView(&resources.texture),
                         },
                         wgpu::BindGroupEntry {
                             binding: 1,
-                            resource: wgpu::BindingResource::SamplerArray(
-                                sampler_array.iter().collect::<Vec<_>>().as_slice(),
+                            resource: wgpu::BindingResource::Sampler(&pipelines.sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(
+                                resources.ramp.as_ref().unwrap_or(&pipelines.no_ramp),
                             ),
                         },
                     ],
-                });
+                })
+            })
+            .collect::<Vec<_>>();
 
-        let pipeline = render_pipeline(
-            self.device.as_ref().unwrap(),
-            self.config.as_ref().unwrap(),
-            &bind_group_layout,
-        );
+        // --- video frames bind their planes & uniforms instead
+        let frames = recorded
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (_, resources))| Some((idx, resources.frame.as_ref()?)))
+            .collect::<Vec<_>>();
+        let video = frames
+            .iter()
+            .map(|(idx, draw)| {
+                let group = self.frames.bind_group(
+                    device,
+                    queue,
+                    &pipelines.video_layout,
+                    &pipelines.sampler,
+                    draw,
+                    size,
+                );
+                (*idx, group)
+            })
+            .collect::<HashMap<_, _>>();
 
         // vertex buffer
-        let vertex_buffer = vertex_buffer(self.device.as_ref().unwrap(), vertex_buf);
-        let index_buffer = index_buffer(self.device.as_ref().unwrap(), index_buf);
+        let vertex_buffer = vertex_buffer(device, vertex_buf);
+        let index_buffer = index_buffer(device, index_buf);
+        profiler.lap(FramePhase::Upload);
 
         // output: SurfaceTexture
-        let output = self.surface.as_ref().unwrap().get_current_texture()?;
+        // --- nothing of this frame makes it on screen, the next one can't draw over it
+        let output = match self.surface.as_ref().unwrap().get_current_texture() {
+            Ok(output) => output,
+            Err(err) => {
+                self.damage.invalidate();
+                return Err(err);
+            }
+        };
 
         // view: TextureView
         let view = output.texture.create_view(&Default::default());
 
-        let mut encoder =
-            self.device
-                .as_ref()
-                .unwrap()
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Render Encoder"),
-                });
+        // --- drawn into whatever keeps the frame for next time: the multisampled target,
+        // --- resolved onto the surface, or the cache, copied onto it after
+        let cache = self
+            .cache
+            .as_ref()
+            .map(|cache| cache.create_view(&Default::default()));
+        let (target, resolve_target) = match (self.msaa.as_ref(), cache.as_ref()) {
+            (Some(msaa), _) => (msaa, Some(&view)),
+            (None, Some(cache)) => (cache, None),
+            (None, None) => (&view, None),
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+        // --- only while the last reading isn't still on its way back
+        let timer = self.timer.as_ref().filter(|timer| timer.is_idle());
 
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
+                    view: target,
+                    resolve_target,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: self.bg_color[0],
-                            g: self.bg_color[1],
-                            b: self.bg_color[2],
-                            a: self.bg_color[3],
-                        }),
+                        load: match whole {
+                            true => wgpu::LoadOp::Clear(wgpu::Color {
+                                r: self.bg_color[0],
+                                g: self.bg_color[1],
+                                b: self.bg_color[2],
+                                a: self.bg_color[3],
+                            }),
+                            false => wgpu::LoadOp::Load,
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: self.stencil.as_ref().unwrap(),
+                    depth_ops: None,
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                }),
                 occlusion_query_set: None,
-                timestamp_writes: None,
+                timestamp_writes: timer.map(GpuTimer::writes),
             });
 
-            render_pass.set_pipeline(&pipeline); // pipeline -> button
-            render_pass.set_bind_group(0, &bind_group, &[]); // pipeline -> button
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..)); // buffer -> button
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16); // buffer -> button
-            render_pass.draw_indexed(0..num_indices, 0, 0..1); // buffer -> button
+            if !steps.is_empty() {
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+
+                for (region, idx) in steps {
+                    let (range, resources) = &recorded[idx];
+                    let pass = resources.pass;
+                    let clip = pass.scissor.map_or(region, |clip| clip.intersect(&region));
+                    let Some([x, y, width, height]) = scissor(Some(clip), size) else {
+                        continue;
+                    };
+                    render_pass.set_stencil_reference(pass.reference);
+                    render_pass.set_scissor_rect(x, y, width, height);
+
+                    // --- video frames place their own quad from the uniforms
+                    if let Some(group) = video.get(&idx) {
+                        render_pass.set_pipeline(&pipelines.video);
+                        render_pass.set_bind_group(0, group, &[]);
+                        render_pass.draw(0..6, 0..1);
+                        continue;
+                    }
+
+                    render_pass.set_pipeline(pipelines.stencil(pass.stencil));
+                    render_pass.set_bind_group(0, &bind_groups[idx], &[]);
+                    render_pass.draw_indexed(range.clone(), 0, 0..1);
+                }
+            }
         }
 
-        self.queue
-            .as_ref()
-            .unwrap()
-            .submit(std::iter::once(encoder.finish()));
+        if let Some(cache) = self.cache.as_ref() {
+            encoder.copy_texture_to_texture(
+                cache.as_image_copy(),
+                output.texture.as_image_copy(),
+                cache.size(),
+            );
+        }
+
+        if let Some(timer) = timer {
+            timer.resolve(&mut encoder);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+        if let Some(timer) = timer {
+            timer.map();
+        }
+        self.textures.end_frame();
+        self.frames.end_frame();
 
         output.present();
+        profiler.lap(FramePhase::Submit);
+
+        Ok(true)
+    }
+}
+
+// --- built once, and again only when the surface format or the sample count changes
+struct Pipelines {
+    format: wgpu::TextureFormat,
+    samples: u32,
+    layout: wgpu::BindGroupLayout,
+    // --- one for every way of using the stencil, in `Stencil::ALL` order
+    stencils: [wgpu::RenderPipeline; 4],
+    video_layout: wgpu::BindGroupLayout,
+    video: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    // --- bound for draws without a gradient
+    no_ramp: wgpu::TextureView,
+}
+
+impl Pipelines {
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        samples: u32,
+    ) -> Self {
+        let layout = bind_group_layout(device);
+        let video_layout = video::bind_group_layout(device);
+
+        Self {
+            format: config.format,
+            samples,
+            stencils: Stencil::ALL
+                .map(|stencil| render_pipeline(device, config, &layout, stencil, samples)),
+            video: video::pipeline(device, config, &video_layout, samples),
+            layout,
+            video_layout,
+            sampler: sampler(device),
+            no_ramp: gradient_texture(device, queue, None, None),
+        }
+    }
+
+    fn fits(&self, config: &wgpu::SurfaceConfiguration, samples: u32) -> bool {
+        self.format == config.format && self.samples == samples
+    }
+
+    fn stencil(&self, stencil: Stencil) -> &wgpu::RenderPipeline {
+        let [paint, push, pop, clear] = &self.stencils;
+        match stencil {
+            Stencil::Paint => paint,
+            Stencil::Push => push,
+            Stencil::Pop => pop,
+            Stencil::Clear => clear,
+        }
+    }
+}
+
+// --- a texture, its sampler & the gradient ramp
+fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Bind Group Layout"),
+        entries: &[
+            texture(0),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            texture(2),
+        ],
+    })
+}
+
+// --- what a draw's texture is made from, only uploaded once it's known to be needed
+enum Paint {
+    Solid(Color),
+    Texture(Texture),
+}
+
+// --- everything about a draw, collected for the whole tree before any of it is recorded
+struct Draw {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    paint: Paint,
+    // --- the fill & border gradients painted instead of the texture's colors
+    gradients: [Option<Gradient>; 2],
+    pass: Pass,
+    // --- drawn through the video pipeline instead
+    frame: Option<FrameDraw>,
+}
+
+impl Draw {
+    // --- what of the window it can touch
+    fn bounds(&self, size: &winit::dpi::PhysicalSize<u32>) -> Rect {
+        let (width, height) = (size.width as f32, size.height as f32);
+        let mut points = self.vertices.iter().map(|vertex| {
+            let [x, y, _] = vertex.position;
+            [(x + 1.) / 2. * width, (1. - y) / 2. * height]
+        });
+        let Some(first) = points.next() else {
+            return Rect::default();
+        };
+        let (min, max) = points.fold((first, first), |(min, max), [x, y]| {
+            (
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            )
+        });
+        let rect = Rect::new(min[0], min[1], max[0] - min[0], max[1] - min[1]);
+
+        self.pass.scissor.map_or(rect, |clip| rect.intersect(&clip))
+    }
+
+    // --- the same for two draws painting the same pixels the same way
+    fn signature(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let floats = |hasher: &mut DefaultHasher, values: &[f32]| {
+            values.iter().for_each(|value| value.to_bits().hash(hasher));
+        };
+
+        bytemuck::cast_slice::<Vertex, u8>(&self.vertices).hash(&mut hasher);
+        self.indices.hash(&mut hasher);
+        match &self.paint {
+            Paint::Solid(color) => floats(&mut hasher, color),
+            Paint::Texture(texture) => texture.id().hash(&mut hasher),
+        }
+        for gradient in self.gradients.iter().flatten() {
+            floats(&mut hasher, &gradient.params());
+            for (offset, color) in &gradient.stops {
+                floats(&mut hasher, &[*offset]);
+                floats(&mut hasher, color);
+            }
+        }
+
+        let scissor = self
+            .pass
+            .scissor
+            .map(|clip| [clip.x, clip.y, clip.width, clip.height]);
+        floats(&mut hasher, &scissor.unwrap_or_default());
+        (self.pass.stencil, self.pass.reference).hash(&mut hasher);
+
+        if let Some(frame) = self.frame.as_ref() {
+            frame.frame.id().hash(&mut hasher);
+            let source = frame.source;
+            floats(
+                &mut hasher,
+                &[source.x, source.y, source.width, source.height],
+            );
+        }
+
+        hasher.finish()
+    }
+}
+
+// --- what a recorded draw binds
+struct Resources {
+    pass: Pass,
+    texture: Rc<wgpu::TextureView>,
+    ramp: Option<wgpu::TextureView>,
+    frame: Option<FrameDraw>,
+}
+
+// --- every draw of the tree in painting order, nothing is uploaded yet
+fn collect(
+    resolved: Vec<Resolved>,
+    cascade: &Cascade,
+    size: &winit::dpi::PhysicalSize<u32>,
+) -> Vec<Draw> {
+    let mut draws = Vec::new();
+    let gradients = |style: &Style| {
+        [
+            style.background_gradient.clone(),
+            style.border_gradient.clone(),
+        ]
+    };
+    let clear = || Paint::Solid(TRANSPARENT);
+
+    // --- rounded or transformed clips whose children are still being drawn, innermost last
+    let mut masks: Vec<(Vec<usize>, Vec<Vertex>, Pass)> = Vec::new();
+
+    for resolved in resolved {
+        let component = resolved.view;
+        let transform = &resolved.transform;
+
+        // --- past the end of a rounded clip's children, its shape comes back out of the stencil
+        // --- overlays start over with no clips at all
+        while let Some((path, vertices, pass)) = masks.last() {
+            if resolved.path.starts_with(path) && masks.len() <= resolved.masks.len() {
+                break;
+            }
+            draws.push(Draw {
+                vertices: vertices.clone(),
+                indices: component.indices(0).to_vec(),
+                paint: clear(),
+                gradients: [None, None],
+                pass: *pass,
+                frame: None,
+            });
+            masks.pop();
+        }
+
+        // --- the shadow goes under the element, the whole of it, not cut out under the background
+        if let Some(shadow) = resolved.style.shadow.as_ref() {
+            let radius = resolved.style.radius.unwrap_or(0.);
+            let mut vertices = shadow_quad(&resolved.layout.border_box, radius, shadow, size);
+            let opacity = resolved.style.opacity.unwrap_or(1.);
+            vertices
+                .iter_mut()
+                .for_each(|vertex| vertex.opacity = opacity);
+            draws.push(Draw {
+                vertices: transformed(&vertices, transform, size),
+                indices: component.indices(0).to_vec(),
+                paint: Paint::Solid(shadow.color),
+                gradients: [None, None],
+                pass: Pass::paint(&resolved),
+                frame: None,
+            });
+        }
+
+        let vertices = component.vertices(&resolved.layout.border_box, &resolved.style, size);
+        draws.push(Draw {
+            vertices: transformed(&vertices, transform, size),
+            indices: component.indices(0).to_vec(),
+            paint: Paint::Solid(component.fill_color(&resolved.style)),
+            gradients: gradients(&resolved.style),
+            pass: Pass::paint(&resolved),
+            frame: None,
+        });
+
+        let placement = Placement {
+            layout: resolved.layout,
+            style: resolved.style.clone(),
+            transform: resolved.transform,
+        };
+        for layer in component.element().layers(&placement) {
+            let vertices = layer.fill.vertices(&layer.rect, &layer.style, size);
+            let (paint, frame) = match layer.fill {
+                Fill::Solid(color) => (Paint::Solid(color), None),
+                Fill::Texture { texture, .. } => (Paint::Texture(texture), None),
+                Fill::Video { frame, source } => {
+                    let draw = FrameDraw {
+                        frame,
+                        rect: layer.rect,
+                        transform: resolved.transform,
+                        source,
+                        opacity: layer.style.opacity.unwrap_or(1.),
+                    };
+                    (clear(), Some(draw))
+                }
+            };
+            draws.push(Draw {
+                vertices: transformed(&vertices, transform, size),
+                indices: component.indices(0).to_vec(),
+                paint,
+                gradients: gradients(&layer.style),
+                pass: Pass::paint(&resolved),
+                frame,
+            });
+        }
+
+        for mesh in component.element().meshes(&placement) {
+            let opacity = resolved.style.opacity.unwrap_or(1.);
+            draws.push(Draw {
+                vertices: transformed(&mesh.vertices(opacity, size), transform, size),
+                indices: mesh.indices,
+                paint: Paint::Solid(mesh.color),
+                gradients: [None, None],
+                pass: Pass::paint(&resolved),
+                frame: None,
+            });
+        }
+
+        if component.element().state().focused {
+            let (rect, style) = focus_ring(&resolved, cascade.theme);
+            draws.push(Draw {
+                vertices: transformed(&quad(&rect, &style, size), transform, size),
+                indices: component.indices(0).to_vec(),
+                paint: clear(),
+                gradients: [None, None],
+                pass: Pass::paint(&resolved),
+                frame: None,
+            });
+        }
+
+        // --- scissors are enough for square clips, rounded or turned ones go through the stencil
+        let clip = clip_of(component, &resolved.layout, &resolved.style)
+            .map(|clip| clip.transformed(transform));
+        if let Some(clip) = clip.filter(Clip::is_mask) {
+            let shape = quad(&clip.rect, &Style::new().with_radius(clip.radius), size);
+            let vertices = transformed(&shape, &clip.transform, size);
+            let reference = resolved.masks.len() as u32;
+            draws.push(Draw {
+                vertices: vertices.clone(),
+                indices: component.indices(0).to_vec(),
+                paint: clear(),
+                gradients: [None, None],
+                pass: Pass {
+                    scissor: resolved.clip,
+                    stencil: Stencil::Push,
+                    reference,
+                },
+                frame: None,
+            });
+
+            let pop = Pass {
+                scissor: resolved.clip,
+                stencil: Stencil::Pop,
+                reference: reference + 1,
+            };
+            masks.push((resolved.path.clone(), vertices, pop));
+        }
+    }
+
+    // --- the clips still open at the end come out too, for whatever's drawn over the tree
+    for (_, vertices, pass) in masks.into_iter().rev() {
+        draws.push(Draw {
+            vertices,
+            indices: vec![0, 1, 2, 0, 2, 3],
+            paint: clear(),
+            gradients: [None, None],
+            pass,
+            frame: None,
+        });
+    }
+
+    draws
+}
+
+// --- the frame time overlay, over everything & never clipped
+fn overlay(profiler: &Profiler, window: Rect, size: &winit::dpi::PhysicalSize<u32>) -> Vec<Draw> {
+    let Some(overlay) = profiler.overlay_shapes(window) else {
+        return vec![];
+    };
+    let shapes = overlay
+        .shapes
+        .into_iter()
+        .map(|(rect, color)| (rect, Paint::Solid(color)));
+    let (rect, texture) = overlay.label;
+    let label = std::iter::once((rect, Paint::Texture(texture)));
+
+    shapes
+        .chain(label)
+        .map(|(rect, paint)| Draw {
+            vertices: quad(&rect, &Style::new(), size).to_vec(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            paint,
+            gradients: [None, None],
+            pass: Pass {
+                scissor: None,
+                stencil: Stencil::Paint,
+                reference: 0,
+            },
+            frame: None,
+        })
+        .collect()
+}
+
+// --- where the GPU timer's readback is at
+const TIMER_IDLE: u8 = 0;
+const TIMER_MAPPING: u8 = 1;
+const TIMER_READY: u8 = 2;
+
+// --- times the render pass on the GPU with a pair of timestamps, read back a frame or
+// --- more later without ever waiting for it
+struct GpuTimer {
+    queries: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+    // --- nanoseconds per tick
+    period: f32,
+}
+
+impl GpuTimer {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let size = 2 * std::mem::size_of::<u64>() as u64;
+        let buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        Self {
+            queries: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Timestamp Queries"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            }),
+            resolve: buffer(
+                "Timestamp Resolve Buffer",
+                wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            ),
+            readback: buffer(
+                "Timestamp Readback Buffer",
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            ),
+            state: Arc::new(AtomicU8::new(TIMER_IDLE)),
+            period: queue.get_timestamp_period(),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.state.load(Ordering::Acquire) == TIMER_IDLE
+    }
+
+    fn writes(&self) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.queries,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
 
-        Ok(())
+    fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.queries, 0..2, &self.resolve, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve, 0, &self.readback, 0, self.resolve.size());
     }
+
+    // --- after the timed pass is submitted
+    fn map(&self) {
+        self.state.store(TIMER_MAPPING, Ordering::Release);
+        let state = self.state.clone();
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let next = match result {
+                    Ok(_) => TIMER_READY,
+                    Err(_) => TIMER_IDLE,
+                };
+                state.store(next, Ordering::Release);
+            });
+    }
+
+    // --- the last timed pass, once it's back
+    fn read(&self, device: &wgpu::Device) -> Option<Duration> {
+        device.poll(wgpu::Maintain::Poll);
+        if self.state.load(Ordering::Acquire) != TIMER_READY {
+            return None;
+        }
+
+        let ticks = {
+            let bytes = self.readback.slice(..).get_mapped_range();
+            let stamps = bytemuck::cast_slice::<u8, u64>(&bytes);
+            stamps[1].saturating_sub(stamps[0])
+        };
+        self.readback.unmap();
+        self.state.store(TIMER_IDLE, Ordering::Release);
+
+        Some(Duration::from_nanos(
+            (ticks as f64 * self.period as f64) as u64,
+        ))
+    }
+}
+
+// --- the background is a linear color, like the clear color, textures hold sRGB
+fn encode_srgb(color: [f64; 4]) -> Color {
+    let encode = |c: f64| {
+        let c = c.clamp(0., 1.) as f32;
+        match c <= 0.0031308 {
+            true => c * 12.92,
+            false => 1.055 * c.powf(1. / 2.4) - 0.055,
+        }
+    };
+    [
+        encode(color[0]),
+        encode(color[1]),
+        encode(color[2]),
+        color[3].clamp(0., 1.) as f32,
+    ]
+}
+
+// --- vertices moved by a transform in window coordinates, from NDC and back
+fn transformed(
+    vertices: &[Vertex],
+    transform: &Transform,
+    size: &winit::dpi::PhysicalSize<u32>,
+) -> Vec<Vertex> {
+    if transform.is_identity() {
+        return vertices.to_vec();
+    }
+
+    let (width, height) = (size.width as f32, size.height as f32);
+    vertices
+        .iter()
+        .map(|vertex| {
+            let [x, y, z] = vertex.position;
+            let [x, y] = transform.apply([(x + 1.) / 2. * width, (1. - y) / 2. * height]);
+            Vertex {
+                position: [x / width * 2. - 1., 1. - y / height * 2., z],
+                ..*vertex
+            }
+        })
+        .collect()
+}
+
+// --- sized & formatted like the surface, to be copied onto it
+fn cache_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Cache Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+// --- the clip in whole pixels within the surface, `None` when nothing of it is left to draw
+fn scissor(clip: Option<Rect>, size: &winit::dpi::PhysicalSize<u32>) -> Option<[u32; 4]> {
+    let Some(clip) = clip else {
+        return Some([0, 0, size.width, size.height]);
+    };
+
+    let x0 = (clip.x.floor().max(0.) as u32).min(size.width);
+    let y0 = (clip.y.floor().max(0.) as u32).min(size.height);
+    let x1 = (clip.right().ceil().max(0.) as u32).min(size.width);
+    let y1 = (clip.bottom().ceil().max(0.) as u32).min(size.height);
+
+    (x1 > x0 && y1 > y0).then_some([x0, y0, x1 - x0, y1 - y0])
+}
+
+fn stencil_view(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    samples: u32,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Stencil Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format: STENCIL_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn msaa_view(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    samples: u32,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Multisampled Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// --- drawn around the focused view, outside of its border box
+fn focus_ring(resolved: &Resolved, theme: &Theme) -> (Rect, Style) {
+    let outset = FOCUS_RING_WIDTH + FOCUS_RING_OFFSET;
+    let rect = resolved.layout.border_box.inset(Edges::all(-outset));
+
+    let style = Style::new()
+        .with_border(FOCUS_RING_WIDTH, theme.palette.focus)
+        .with_radius(resolved.style.radius.unwrap_or(0.) + outset);
+
+    (rect, style)
 }